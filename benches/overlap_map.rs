@@ -0,0 +1,71 @@
+use bible_api::related_media::overlapping_ranges::{ChapterRangePair, OverlapMap, OverlapsWith};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// - `count` ranges in a book of 50 chapters, like the passages of sermons and notes
+/// - Most are a few verses, and some span several chapters
+fn related_media(count: usize) -> OverlapMap<ChapterRangePair, usize> {
+    // a small linear congruential generator, so the ranges are the same every run
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = |max: usize| {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) as usize % max
+    };
+    let mut map = OverlapMap::default();
+    for value in 0..count {
+        let start_chapter = 1 + next(50);
+        let start_verse = 1 + next(30);
+        let (end_chapter, end_verse) = if next(10) == 0 {
+            (start_chapter + 1 + next(3), 1 + next(30))
+        } else {
+            (start_chapter, start_verse + next(10))
+        };
+        map.insert(
+            ChapterRangePair::new(start_chapter, start_verse, end_chapter, end_verse),
+            value,
+        );
+    }
+    map
+}
+
+/// The related media of every verse of a chapter, like when rendering it
+fn render_chapter<'a>(verse_media: impl Fn(ChapterRangePair) -> Vec<&'a usize>) -> usize {
+    (1..=30)
+        .map(|verse| verse_media(ChapterRangePair::from_chapter_verse(25, verse)).len())
+        .sum()
+}
+
+fn overlap_map(c: &mut Criterion) {
+    let mut group = c.benchmark_group("overlapping related media of a chapter");
+    for count in [1_000, 10_000, 50_000] {
+        let map = related_media(count);
+        // build the index before measuring
+        map.get_overlapping(ChapterRangePair::from_chapter_verse(1, 1));
+
+        group.bench_with_input(BenchmarkId::new("interval index", count), &map, |b, map| {
+            b.iter(|| {
+                render_chapter(|verse| {
+                    map.iter_overlapping(black_box(verse))
+                        .map(|(_, value)| value)
+                        .collect()
+                })
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("linear scan", count), &map, |b, map| {
+            b.iter(|| {
+                render_chapter(|verse| {
+                    let verse = black_box(verse);
+                    map.iter()
+                        .filter(|(key, _)| verse.overlaps_with(key))
+                        .map(|(_, value)| value)
+                        .collect()
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, overlap_map);
+criterion_main!(benches);
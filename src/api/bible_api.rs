@@ -1,21 +1,158 @@
 use std::{
     collections::BTreeMap,
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, Range},
 };
 
+use once_cell::{sync::Lazy, unsync::OnceCell};
+use regex::Regex;
+
 use crate::{
-    api::passage::segments::PassageSegments,
-    bible_data::{bible_data::BibleData, formats::parse::ParseBibleData},
-    related_media::related_media::{RelatedMedia, RelatedMediaBookOrganizer},
+    api::passage::{
+        errors::ReferenceError,
+        multi_passage::{BookRange, MultiPassage, MultiPassageEntry},
+        segments::{parse_reference_segments, ChapterVerse, PassageSegment, PassageSegments},
+    },
+    bible_data::book::BibleBook,
+    bible_data::{
+        bible_data::BibleData,
+        formats::json::JSONTranslation,
+        versification::{VersificationMap, Versifications},
+    },
+    related_media::{
+        cross_references::{CrossReference, CrossReferences},
+        interlinear::{Interlinear, JSONInterlinear, Lexicon},
+        related_media::{RelatedMedia, RelatedMediaBookOrganizer},
+    },
 };
 
-use super::passage::passage::Passage;
+use super::{concordance::ConcordanceIndex, passage::passage::Passage, search::SearchIndex};
 
 // #[derive(Default)]
 pub struct ApiData {
+    /// every other loaded translation, by its (uppercase) abbreviation
     pub(crate) bibles: BTreeMap<String, BibleData>,
+    /// the default translation, which references are parsed with
     pub(crate) bible: BibleData,
+    /// mapping tables between the versifications of the translations
+    pub(crate) versifications: Versifications,
     pub(crate) related_media: RelatedMediaBookOrganizer,
+    pub(crate) cross_references: CrossReferences,
+    /// the original language words of each verse
+    pub(crate) interlinear: Interlinear,
+    /// Strong's Hebrew and Greek dictionaries
+    pub(crate) lexicon: Lexicon,
+    /// the index of the interlinear words by Strong's number and lemma, built by the first query
+    pub(crate) concordance_index: OnceCell<ConcordanceIndex>,
+    /// the word index of the default translation, built by the first search
+    pub(crate) search_index: OnceCell<SearchIndex>,
+}
+
+impl ApiData {
+    pub fn get_bible(&self) -> &BibleData {
+        &self.bible
+    }
+
+    /// - Ex: `esv` or `ESV`
+    /// - This includes the default translation
+    pub fn get_translation(&self, abbreviation: &str) -> Option<&BibleData> {
+        let abbreviation = abbreviation.to_uppercase();
+        if self.bible.translation.abbreviation.to_uppercase() == abbreviation {
+            Some(&self.bible)
+        } else {
+            self.bibles.get(&abbreviation)
+        }
+    }
+
+    pub fn get_versifications(&self) -> &Versifications {
+        &self.versifications
+    }
+
+    /// - Ex: `["KJV", "ESV"]` for [`Passage::format_parallel`]
+    /// - Returns [`None`] when any of them isn't loaded
+    pub fn get_translations(&self, abbreviations: &[&str]) -> Option<Vec<&BibleData>> {
+        abbreviations
+            .iter()
+            .map(|abbreviation| self.get_translation(abbreviation))
+            .collect()
+    }
+
+    /// - Strong's Hebrew and Greek dictionaries
+    /// - Ex: `api.get_lexicon().get("G25")`
+    pub fn get_lexicon(&self) -> &Lexicon {
+        &self.lexicon
+    }
+
+    /**
+    - The passages that any verse of `passage` references, most relevant first
+    - `min_votes` leaves out references with fewer votes (references without votes are kept)
+    - They are in the translation of `passage`
+    */
+    pub fn get_cross_references<'a>(
+        &'a self,
+        passage: &Passage,
+        min_votes: Option<i64>,
+    ) -> Vec<Passage<'a>> {
+        let Some(default) = self.in_default_translation(passage) else {
+            return vec![];
+        };
+        self.cross_references
+            .outgoing(default.book.book_number(), &default.segments, min_votes)
+            .into_iter()
+            .filter_map(|reference| {
+                self.cross_reference_passage(passage, reference.to_book, reference.to)
+            })
+            .collect()
+    }
+
+    /// Like [`Self::get_cross_references`], but the verses that reference any verse of `passage`
+    pub fn get_incoming_cross_references<'a>(
+        &'a self,
+        passage: &Passage,
+        min_votes: Option<i64>,
+    ) -> Vec<Passage<'a>> {
+        let Some(default) = self.in_default_translation(passage) else {
+            return vec![];
+        };
+        self.cross_references
+            .incoming(default.book.book_number(), &default.segments, min_votes)
+            .into_iter()
+            .filter_map(|reference| {
+                let from = reference.from;
+                let segment = PassageSegment::chapter_verse(from.chapter, from.verse);
+                self.cross_reference_passage(passage, from.book, segment)
+            })
+            .collect()
+    }
+
+    /// Cross references use the versification of the default translation
+    fn in_default_translation<'a>(&'a self, passage: &Passage) -> Option<Passage<'a>> {
+        passage.in_translation_with(&self.bible, &self.versifications)
+    }
+
+    /// A segment of the default translation, in the translation of `passage`
+    fn cross_reference_passage<'a>(
+        &'a self,
+        passage: &Passage,
+        book: usize,
+        segment: PassageSegment,
+    ) -> Option<Passage<'a>> {
+        let book = self.bible.get_book(book)?;
+        let default = Passage {
+            book,
+            segments: PassageSegments(vec![segment.resolve_in(book)]),
+        };
+        let translation = &passage.book.get_translation().abbreviation;
+        let bible = self.get_translation(translation).unwrap_or(&self.bible);
+        default.in_translation_with(bible, &self.versifications)
+    }
+
+    /// Every loaded translation, starting with the default one
+    pub fn translations(&self) -> Vec<&JSONTranslation> {
+        std::iter::once(&self.bible)
+            .chain(self.bibles.values())
+            .map(|bible| &bible.translation)
+            .collect()
+    }
 }
 
 pub struct Api<'a, T> {
@@ -60,7 +197,13 @@ impl BibleAPI {
         Self(ApiData {
             bibles: Default::default(),
             bible: data,
+            versifications: Versifications::default(),
             related_media: RelatedMediaBookOrganizer::default(),
+            cross_references: CrossReferences::default(),
+            interlinear: Interlinear::default(),
+            lexicon: Lexicon::default(),
+            concordance_index: OnceCell::new(),
+            search_index: OnceCell::new(),
         })
     }
     pub fn api<T>(&self, content: T) -> Api<'_, T> {
@@ -74,6 +217,59 @@ impl BibleAPI {
         self.related_media.add_related_media(list);
     }
 
+    /// Ex: from [`parse_open_bible_file`](crate::related_media::cross_references::parse_open_bible_file)
+    pub fn add_cross_references(&mut self, list: Vec<CrossReference>) {
+        self.cross_references.add_cross_references(list);
+    }
+
+    /// Verses that already have interlinear data are replaced
+    pub fn add_interlinear(&mut self, interlinear: JSONInterlinear) {
+        self.interlinear.add_interlinear(interlinear);
+        self.concordance_index = OnceCell::new();
+    }
+
+    /// Ex: the Hebrew and Greek dictionaries, one after the other
+    pub fn add_lexicon(&mut self, lexicon: Lexicon) {
+        self.lexicon.extend(lexicon);
+    }
+
+    /// - Adds another translation (replacing any loaded translation with the same abbreviation)
+    /// - The default translation doesn't change
+    pub fn add_translation(&mut self, data: BibleData) {
+        let abbreviation = data.translation.abbreviation.to_uppercase();
+        if self.bible.translation.abbreviation.to_uppercase() == abbreviation {
+            self.bible = data;
+            self.search_index = OnceCell::new();
+        } else {
+            self.bibles.insert(abbreviation, data);
+        }
+    }
+
+    /// Adds a mapping table, so passages line up across translations with this versification
+    pub fn add_versification(&mut self, map: VersificationMap) {
+        self.versifications.add(map);
+    }
+
+    /// - Makes a loaded translation the one that references are parsed with
+    /// - Returns `false` when no translation has this abbreviation
+    pub fn set_default_translation(&mut self, abbreviation: &str) -> bool {
+        let Some(mut data) = self.bibles.remove(&abbreviation.to_uppercase()) else {
+            return self.get_translation(abbreviation).is_some();
+        };
+        std::mem::swap(&mut self.bible, &mut data);
+        self.search_index = OnceCell::new();
+        self.bibles
+            .insert(data.translation.abbreviation.to_uppercase(), data);
+        true
+    }
+
+    /// - Like [`Self::parse_reference`], but the passage is in another loaded translation
+    /// - The verses are mapped to the versification of that translation
+    pub fn parse_reference_in(&self, translation: &str, input: &str) -> Option<Passage<'_>> {
+        self.parse_reference(input)?
+            .in_translation_with(self.get_translation(translation)?, &self.versifications)
+    }
+
     /// This is meant to parse only 1 reference
     pub fn parse_reference(&self, input: &str) -> Option<Passage> {
         // match book
@@ -85,19 +281,311 @@ impl BibleAPI {
 
         // match passage reference segments that immediately follow
         let remaining = &input[book_match.end()..];
-        let segments = PassageSegments::try_parse(remaining)?;
+        let segments = PassageSegments::try_parse(book, remaining)?;
 
         let passage = Passage { book, segments };
         Some(passage)
     }
 
-    /// This is meant to fidn and parse all references in an input
-    pub fn find_and_parse_all_references(input: &str) -> Option<Vec<Located<Passage>>> {
-        // hint: use Self::parse_reference on segment splits
-        todo!()
+    /**
+    Like [`Self::parse_reference`], but for user input, so it explains what is wrong
+
+    - The whole input after the book must be chapters and verses
+    - Nothing after the book is the whole book, and numbers without verses are whole chapters
+    - Every chapter and verse must exist in the [`BibleData::reference_array`]
+    - Ranges must not go backwards
+    */
+    pub fn try_parse_reference(&self, input: &str) -> Result<Passage<'_>, ReferenceError> {
+        let book_match =
+            self.bible
+                .book_regex
+                .find(input)
+                .ok_or_else(|| ReferenceError::UnknownBook {
+                    span: unknown_book_span(input),
+                })?;
+        // the book must start the input (Ex: not `Hezekiah and John 3:16`)
+        if !input[..book_match.start()].trim().is_empty() {
+            return Err(ReferenceError::UnknownBook {
+                span: unknown_book_span(&input[..book_match.start()]),
+            });
+        }
+        let book = self
+            .bible
+            .get_book_id(book_match.as_str())
+            .and_then(|book_id| self.bible.get_book(book_id))
+            .ok_or(ReferenceError::UnknownBook {
+                span: book_match.range(),
+            })?;
+
+        let offset = book_match.end();
+        let segments = parse_reference_segments(book, &input[offset..])
+            .map_err(|error| error.offset(offset))?;
+
+        for (segment, span) in segments.iter() {
+            self.validate_segment(book, segment, span.start + offset..span.end + offset)?;
+        }
+
+        Ok(Passage {
+            book,
+            segments: PassageSegments(segments.into_iter().map(|(segment, _)| segment).collect()),
+        })
+    }
+
+    /// Like [`Self::try_parse_passages`], but without the explanation
+    pub fn parse_passages(&self, input: &str) -> Option<MultiPassage<'_>> {
+        self.try_parse_passages(input).ok()
+    }
+
+    /**
+    Like [`Self::try_parse_reference`], but for references in multiple books
+
+    - Ex: `Romans 8:28; Ephesians 2:8-9` or `Matthew 28:1-Mark 1:5`
+    - Each book starts a new passage, and a range that ends with another book (like
+      `Matthew 28:1-Mark 1:5`) is a [`BookRange`]
+    */
+    pub fn try_parse_passages(&self, input: &str) -> Result<MultiPassage<'_>, ReferenceError> {
+        let book_matches = self.bible.book_regex.find_iter(input).collect::<Vec<_>>();
+        let Some(first_match) = book_matches.first() else {
+            return Err(ReferenceError::UnknownBook {
+                span: unknown_book_span(input),
+            });
+        };
+        // `Hezekiah 3:4; John 3:16`
+        if !input[..first_match.start()].trim().is_empty() {
+            return Err(ReferenceError::UnknownBook {
+                span: unknown_book_span(&input[..first_match.start()]),
+            });
+        }
+
+        let mut entries = vec![];
+        // the start of a cross-book range (and where it starts in the input)
+        let mut range_start: Option<(BibleBook, ChapterVerse, usize)> = None;
+        for (index, book_match) in book_matches.iter().enumerate() {
+            let book =
+                self.matched_book(book_match.as_str())
+                    .ok_or(ReferenceError::UnknownBook {
+                        span: book_match.range(),
+                    })?;
+            let offset = book_match.end();
+            let next_match = book_matches.get(index + 1);
+            let chunk = &input[offset..next_match.map_or(input.len(), |next| next.start())];
+
+            // `; ` between books just separates them, but `-` continues into the next book
+            let trimmed = match next_match {
+                Some(_) => {
+                    chunk.trim_end_matches(|ch: char| ch.is_whitespace() || ch == ',' || ch == ';')
+                }
+                None => chunk,
+            };
+            let (segment_input, continues) = match trimmed.strip_suffix(['-', '–']) {
+                Some(shortened) if next_match.is_some() => (shortened, true),
+                _ => (trimmed, false),
+            };
+
+            let mut segments = parse_reference_segments(book, segment_input)
+                .map_err(|error| error.offset(offset))?
+                .into_iter()
+                .map(|(segment, span)| (segment, span.start + offset..span.end + offset))
+                .collect::<Vec<_>>();
+            for (segment, span) in segments.iter() {
+                self.validate_segment(book, segment, span.clone())?;
+            }
+
+            if let Some((start_book, start, start_offset)) = range_start.take() {
+                let (segment, span) = segments.remove(0);
+                let end = ChapterVerse {
+                    chapter: segment.get_ending_chapter(),
+                    verse: segment.get_ending_verse(),
+                };
+                let span = start_offset..span.end;
+                if start_book.book_number() > book.book_number() {
+                    return Err(ReferenceError::ReversedBookRange {
+                        start_book: start_book.get_name().to_string(),
+                        end_book: book.get_name().to_string(),
+                        span,
+                    });
+                }
+                if start_book.book_number() == book.book_number()
+                    && (start.chapter, start.verse) > (end.chapter, end.verse)
+                {
+                    return Err(ReferenceError::ReversedRange { start, end, span });
+                }
+                entries.push(MultiPassageEntry::BookRange(BookRange {
+                    start_book,
+                    start,
+                    end_book: book,
+                    end,
+                }));
+            }
+
+            if continues {
+                // `Matthew 28:1-Mark 1:5-Luke 1:1` would need `Mark 1:5` twice
+                let Some((segment, span)) = segments.pop() else {
+                    return Err(ReferenceError::MalformedSegment {
+                        span: offset + segment_input.len()..offset + trimmed.len(),
+                    });
+                };
+                let start = ChapterVerse {
+                    chapter: segment.get_starting_chapter(),
+                    verse: segment.get_starting_verse(),
+                };
+                let start_offset = if segment_input.trim().is_empty() {
+                    book_match.start()
+                } else {
+                    span.start
+                };
+                range_start = Some((book, start, start_offset));
+            }
+
+            if !segments.is_empty() {
+                entries.push(MultiPassageEntry::Passage(Passage {
+                    book,
+                    segments: PassageSegments(
+                        segments.into_iter().map(|(segment, _)| segment).collect(),
+                    ),
+                }));
+            }
+        }
+        Ok(MultiPassage(entries))
+    }
+
+    fn validate_segment(
+        &self,
+        book: BibleBook,
+        segment: &PassageSegment,
+        span: Range<usize>,
+    ) -> Result<(), ReferenceError> {
+        let start = ChapterVerse {
+            chapter: segment.get_starting_chapter(),
+            verse: segment.get_starting_verse(),
+        };
+        let end = ChapterVerse {
+            chapter: segment.get_ending_chapter(),
+            verse: segment.get_ending_verse(),
+        };
+        for ChapterVerse { chapter, verse } in [start, end] {
+            let Some(verse_count) = self.bible.verse_count(book.book_number(), chapter) else {
+                return Err(ReferenceError::ChapterOutOfRange {
+                    book: book.get_name().to_string(),
+                    chapter,
+                    chapter_count: book.chapter_count(),
+                    span,
+                });
+            };
+            if verse == 0 || verse > verse_count {
+                return Err(ReferenceError::VerseOutOfRange {
+                    book: book.get_name().to_string(),
+                    chapter,
+                    verse,
+                    verse_count,
+                    span,
+                });
+            }
+        }
+        if (start.chapter, start.verse) > (end.chapter, end.verse) {
+            return Err(ReferenceError::ReversedRange { start, end, span });
+        }
+        Ok(())
+    }
+
+    /**
+    This is meant to find and parse all references in an input
+
+    - Every book name/abbreviation match is a candidate, and it only becomes a reference if
+      it is immediately followed by valid reference segments
+    - A book name alone is never a reference here (it is too common in prose), but chapters alone
+      are, like `Psalm 23`
+    - Candidates are scanned from left to right, and the next candidate is only searched for after
+      the end of the previous reference, so references never overlap
+    - Returns [`None`] when no references are found
+    */
+    pub fn find_and_parse_all_references(&self, input: &str) -> Option<Vec<Located<Passage<'_>>>> {
+        let mut positions = PositionTracker::new(input);
+        let mut references = Vec::new();
+        let mut search_start = 0;
+        while let Some(book_match) = self.bible.book_regex.find_at(input, search_start) {
+            // always move forward, even when this candidate isn't a reference
+            search_start = book_match.end();
+
+            let Some(book) = self.matched_book(book_match.as_str()) else {
+                continue;
+            };
+            let Some((mut segments, mut segments_length)) =
+                PassageSegments::try_parse_prefix(book, &input[book_match.end()..])
+            else {
+                continue;
+            };
+
+            // in `John 3:16; 1 John 4:8`, the `1` could be a verse or the start of the next book,
+            // so prefer the next book when it is also a reference and these segments still make
+            // sense without it
+            if let Some(next_match) = self.bible.book_regex.find_at(input, book_match.end()) {
+                let next_is_reference = self
+                    .matched_book(next_match.as_str())
+                    .and_then(|next_book| {
+                        PassageSegments::try_parse_prefix(next_book, &input[next_match.end()..])
+                    })
+                    .is_some();
+                if next_match.start() < book_match.end() + segments_length && next_is_reference {
+                    let shortened_input = &input[book_match.end()..next_match.start()];
+                    if let Some((shortened, shortened_length)) =
+                        PassageSegments::try_parse_prefix(book, shortened_input)
+                    {
+                        // `Ephesians 1:1 John 3:16` can't be `Ephesians 1:` and `1 John 3:16`
+                        let ends_cleanly = shortened_input[shortened_length..]
+                            .chars()
+                            .all(|ch| ch.is_whitespace() || ch == ',' || ch == ';');
+                        if ends_cleanly {
+                            segments = shortened;
+                            segments_length = shortened_length;
+                        }
+                    }
+                }
+            }
+
+            let end = book_match.end() + segments_length;
+            search_start = end;
+
+            let (start_index, start) = positions.advance_to(book_match.start());
+            let (end_index, end) = positions.advance_to(end);
+            references.push(Located {
+                char_range: CharacterRange {
+                    start_index,
+                    end_index,
+                },
+                lined_range: LineRange { start, end },
+                content: Passage { book, segments },
+            });
+        }
+        if references.is_empty() {
+            None
+        } else {
+            Some(references)
+        }
+    }
+}
+
+impl BibleAPI {
+    fn matched_book(&self, book_match: &str) -> Option<BibleBook<'_>> {
+        self.bible
+            .get_book_id(book_match)
+            .and_then(|book_id| self.bible.get_book(book_id))
     }
 }
 
+/// Everything before the chapters and verses, like `Hezekiah` in `Hezekiah 3:4`
+fn unknown_book_span(input: &str) -> Range<usize> {
+    static BOOK_LIKE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^\s*(?P<book>.*?)[\s0-9:;,\-–]*$").unwrap());
+    BOOK_LIKE
+        .captures(input)
+        .and_then(|captures| captures.name("book"))
+        .map_or(0..input.len(), |book| book.range())
+}
+
+/// - Something found in a larger input, along with where it was found
+/// - Ex: a [`Passage`] found in a markdown file (for, but not limited to, usage by an LSP)
+#[derive(Clone, Debug)]
 pub struct Located<T> {
     // pub char_index: usize,
     pub char_range: CharacterRange,
@@ -106,17 +594,473 @@ pub struct Located<T> {
     pub content: T,
 }
 
+/// - Index of the characters (not bytes) in the input
+/// - `end_index` is exclusive
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct CharacterRange {
     pub start_index: u32,
     pub end_index: u32,
 }
 
+/// `end` is exclusive
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct LineRange {
     pub start: LinePosition,
     pub end: LinePosition,
 }
 
+impl LineRange {
+    /// This includes `end`, so a cursor right after a reference is still on it
+    pub fn contains(&self, position: LinePosition) -> bool {
+        self.start <= position && position <= self.end
+    }
+}
+
+/// - Both are index 0
+/// - `character` is counted in characters (not bytes) from the start of the line
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct LinePosition {
     pub line: u32,
     pub character: u32,
 }
+
+/// Converts byte indices to character indices and line positions in a single pass, as long as
+/// it is only asked to move forward
+struct PositionTracker<'a> {
+    input: &'a str,
+    byte_index: usize,
+    char_index: u32,
+    position: LinePosition,
+}
+
+impl<'a> PositionTracker<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            byte_index: 0,
+            char_index: 0,
+            position: LinePosition {
+                line: 0,
+                character: 0,
+            },
+        }
+    }
+
+    fn advance_to(&mut self, byte_index: usize) -> (u32, LinePosition) {
+        for ch in self.input[self.byte_index..byte_index].chars() {
+            self.char_index += 1;
+            if ch == '\n' {
+                self.position.line += 1;
+                self.position.character = 0;
+            } else {
+                self.position.character += 1;
+            }
+        }
+        self.byte_index = byte_index;
+        (self.char_index, self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::passage::formatting_template::PassageFormatterBuilder,
+        bible_data::formats::{
+            json::{test_bible_data, test_json_bible},
+            parse::ParseBibleData,
+        },
+        related_media::{cross_references::parse_open_bible, interlinear::Lexicon},
+    };
+
+    use super::*;
+
+    fn labels(api: &BibleAPI, input: &str) -> Vec<String> {
+        api.find_and_parse_all_references(input)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|located| {
+                format!(
+                    "{} {}",
+                    located.content.book.get_name(),
+                    located.content.segments.label()
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_references_in_prose() {
+        let api = BibleAPI::load(test_bible_data());
+        assert_eq!(
+            labels(
+                &api,
+                "Read Eph 1:1-4 and then John 3:16,17; 1 Jn 4:8 before Romans (not Rom. 8:28)."
+            ),
+            vec![
+                "Ephesians 1:1-4",
+                "John 3:16,17",
+                "1 John 4:8",
+                "Romans 8:28"
+            ]
+        );
+        assert!(api
+            .find_and_parse_all_references("Romans and John, but no references")
+            .is_none());
+    }
+
+    #[test]
+    fn find_references_locations() {
+        let api = BibleAPI::load(test_bible_data());
+        let input = "# Notes\n\nSee — “John 3:16” and\n\tEph 2:8-9.";
+        let references = api.find_and_parse_all_references(input).unwrap();
+        assert_eq!(references.len(), 2);
+
+        let chars = input.chars().collect::<Vec<_>>();
+        for (located, expected) in references.iter().zip(["John 3:16", "Eph 2:8-9"]) {
+            let range = located.char_range;
+            let found: String = chars[range.start_index as usize..range.end_index as usize]
+                .iter()
+                .collect();
+            assert_eq!(found, expected);
+        }
+
+        assert_eq!(
+            references[0].lined_range,
+            LineRange {
+                start: LinePosition {
+                    line: 2,
+                    character: 7
+                },
+                end: LinePosition {
+                    line: 2,
+                    character: 16
+                },
+            }
+        );
+        assert_eq!(
+            references[1].lined_range,
+            LineRange {
+                start: LinePosition {
+                    line: 3,
+                    character: 1
+                },
+                end: LinePosition {
+                    line: 3,
+                    character: 10
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn find_references_overlapping_candidates() {
+        let api = BibleAPI::load(test_bible_data());
+        // the `1 John` candidate overlaps the end of `Ephesians 1:1`
+        assert_eq!(
+            labels(&api, "Ephesians 1:1 John 3:16"),
+            vec!["Ephesians 1:1", "John 3:16"]
+        );
+        // the longest book name wins
+        assert_eq!(
+            labels(&api, "Song of Songs 2:1 and 1 John 1:1"),
+            vec!["Song of Solomon 2:1", "1 John 1:1"]
+        );
+        // non-ASCII digits are not verse numbers
+        assert_eq!(labels(&api, "John ٣:١٦"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn try_parse_valid_references() {
+        let api = BibleAPI::load(test_bible_data());
+        let passage = api
+            .try_parse_reference("Ephesians 1:1-2,4-6,22-2:2,5,3:21-4:2")
+            .unwrap();
+        assert_eq!(passage.label(), "Ephesians 1:1-2,4-6; 22-2:2,5; 3:21-4:2");
+        assert_eq!(
+            api.try_parse_reference("1 Jn 4:8 ").unwrap().label(),
+            "1 John 4:8"
+        );
+    }
+
+    #[test]
+    fn parse_chapters_and_books() {
+        let api = BibleAPI::load(test_bible_data());
+        let label = |input: &str| api.try_parse_reference(input).unwrap().label();
+        assert_eq!(label("Psalm 23"), "Psalms 23");
+        assert_eq!(label("Romans 8-9"), "Romans 8-9");
+        assert_eq!(label("Jude"), "Jude");
+        assert_eq!(label("Genesis 1-3; 5"), "Genesis 1-3; 5");
+        assert_eq!(label("Genesis 1-2:3"), "Genesis 1:1-2:3");
+        assert_eq!(label("John 3; 4:1-3,5"), "John 3; 4:1-3,5");
+        assert_eq!(label("John 3:16; 4"), "John 3:16; 4");
+        assert_eq!(label("John 3:16, 4"), "John 3:16,4");
+        assert_eq!(api.parse_reference("Jude").unwrap().label(), "Jude");
+
+        // the verse bounds come from the reference array
+        let verses = |input: &str| api.try_parse_reference(input).unwrap().into_iter().count();
+        assert_eq!(verses("Psalm 23"), 6);
+        assert_eq!(verses("Romans 15-16"), 33 + 27);
+        assert_eq!(verses("Genesis"), 31 + 25 + 24 + 26 + 32);
+        assert_eq!(verses("Genesis 1-3; 5"), 31 + 25 + 24 + 32);
+
+        let formatter = PassageFormatterBuilder::new().build();
+        assert_eq!(
+            api.try_parse_reference("Psalm 23")
+                .unwrap()
+                .format(&formatter),
+            api.try_parse_reference("Psalm 23:1-6")
+                .unwrap()
+                .format(&formatter)
+                .replace("23:1-6", "23")
+        );
+
+        assert_eq!(
+            api.try_parse_reference("Jude 2").err().unwrap().to_string(),
+            "Jude 2 does not exist (Jude has 1 chapter)"
+        );
+    }
+
+    #[test]
+    fn cross_references() {
+        let mut api = BibleAPI::load(test_bible_data());
+        api.add_cross_references(
+            parse_open_bible(
+                "Gen.1.1\tProv.8.22-Prov.8.30\t327\nGen.1.1\tHeb.11.3\t1155\nJohn.1.3\tHeb.11.3\t412\nJohn.1.3\tPs.33\t5",
+            )
+            .unwrap(),
+        );
+        let passage_labels =
+            |passages: Vec<Passage>| passages.iter().map(Passage::label).collect::<Vec<_>>();
+        let verse = api.bible.get_verse(1, 1, 1).unwrap();
+        assert_eq!(
+            passage_labels(api.api(verse).get_cross_references(None)),
+            ["Hebrews 11:3", "Proverbs 8:22-30"]
+        );
+        assert_eq!(
+            passage_labels(api.api(verse).get_cross_references(Some(500))),
+            ["Hebrews 11:3"]
+        );
+
+        let passage = api.parse_reference("John 1:1-5").unwrap();
+        assert_eq!(
+            passage_labels(api.api(passage).get_cross_references(None)),
+            ["Hebrews 11:3", "Psalms 33"]
+        );
+        // the fixture only has the first chapter of Hebrews
+        let passage = Passage {
+            book: api.bible.get_book(58).unwrap(),
+            segments: PassageSegments(vec![PassageSegment::chapter_verse_range(11, 1, 40)]),
+        };
+        assert_eq!(
+            passage_labels(api.api(passage).get_incoming_cross_references(None)),
+            ["Genesis 1:1", "John 1:3"]
+        );
+    }
+
+    #[test]
+    fn interlinear_words() {
+        let mut api = BibleAPI::load(test_bible_data());
+        api.add_interlinear(
+            serde_json::from_str(
+                r#"{
+  "translation": "KJV",
+  "verses": [
+    {
+      "book": 1,
+      "chapter": 1,
+      "verse": 1,
+      "words": [
+        { "text": "בְּרֵאשִׁית", "strong": "H7225", "gloss": "in beginning", "alignment": [{ "start": 0, "end": 16 }] },
+        { "text": "בָּרָא", "strong": "H1254a", "morph": "HVqp3ms", "alignment": [{ "start": 21, "end": 28 }] },
+        { "text": "אֱלֹהִים", "strong": "H430", "alignment": [{ "start": 17, "end": 20 }] }
+      ]
+    }
+  ]
+}"#,
+            )
+            .unwrap(),
+        );
+        api.add_lexicon(
+            Lexicon::parse(
+                r#"{"H1254":{"lemma":"בָּרָא","xlit":"bârâʼ","pron":"baw-raw'","kjv_def":"create"}}"#,
+            )
+            .unwrap(),
+        );
+        let verse = api.bible.get_verse(1, 1, 1).unwrap();
+        let words = api.api(verse).get_interlinear_words();
+        let aligned = words
+            .iter()
+            .map(|word| (word.word.text.as_str(), word.translation.join(" ")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            aligned,
+            [
+                ("בְּרֵאשִׁית", "In the beginning".to_string()),
+                ("בָּרָא", "created".to_string()),
+                ("אֱלֹהִים", "God".to_string())
+            ]
+        );
+        let entry = words[1].entry.unwrap();
+        assert_eq!(entry.pronunciation.as_deref(), Some("baw-raw'"));
+        assert!(words[0].entry.is_none());
+
+        let verse = api.bible.get_verse(1, 1, 2).unwrap();
+        assert!(api.api(verse).get_interlinear_words().is_empty());
+    }
+
+    #[test]
+    fn find_chapter_references_in_prose() {
+        let api = BibleAPI::load(test_bible_data());
+        assert_eq!(
+            labels(&api, "Psalm 23 and Romans 8-9, but not Jude or John."),
+            vec!["Psalms 23", "Romans 8-9"]
+        );
+        // `pp` is an abbreviation of Philippians, which only has 4 chapters
+        assert_eq!(
+            labels(&api, "see pp. 12-15 for details"),
+            Vec::<String>::new()
+        );
+        assert_eq!(labels(&api, "Jude 2 and Romans 8"), vec!["Romans 8"]);
+    }
+
+    /// The KJV fixture, but with a different abbreviation and John 3:16
+    fn test_translation(abbreviation: &str) -> BibleData {
+        let mut bible = test_json_bible();
+        bible.translation.abbreviation = abbreviation.to_string();
+        bible.translation.name = format!("{} Version", abbreviation);
+        let john = bible.bible.iter_mut().find(|book| book.id == 43).unwrap();
+        john.content[2][15] = Some(format!("John 3:16 in the {}", abbreviation));
+        bible.as_bible_data().unwrap()
+    }
+
+    #[test]
+    fn load_multiple_translations() {
+        let mut api = BibleAPI::load(test_bible_data());
+        api.add_translation(test_translation("ABC"));
+        api.add_translation(test_translation("XYZ"));
+        assert_eq!(
+            api.translations()
+                .iter()
+                .map(|translation| translation.abbreviation.as_str())
+                .collect::<Vec<_>>(),
+            vec!["KJV", "ABC", "XYZ"]
+        );
+        assert_eq!(
+            api.get_translation("abc").unwrap().translation.name,
+            "ABC Version"
+        );
+        assert!(api.get_translation("NET").is_none());
+
+        let formatter = PassageFormatterBuilder::new()
+            .verse("{content}")
+            .text("{segments}")
+            .build();
+        let passage = api.parse_reference("John 3:16").unwrap();
+        assert!(passage.format(&formatter).starts_with("For God so loved"));
+        assert_eq!(
+            passage
+                .in_translation(api.get_translation("XYZ").unwrap())
+                .unwrap()
+                .format(&formatter),
+            "John 3:16 in the XYZ"
+        );
+        assert_eq!(
+            api.parse_reference_in("abc", "Jn 3:16")
+                .unwrap()
+                .format(&formatter),
+            "John 3:16 in the ABC"
+        );
+
+        assert!(api.set_default_translation("xyz"));
+        assert!(!api.set_default_translation("NET"));
+        assert_eq!(api.get_bible().translation.abbreviation, "XYZ");
+        assert_eq!(api.translations().len(), 3);
+        assert!(api
+            .parse_reference("John 3:16")
+            .unwrap()
+            .format(&formatter)
+            .ends_with("XYZ"));
+    }
+
+    #[test]
+    fn try_parse_invalid_references() {
+        let api = BibleAPI::load(test_bible_data());
+        let error = |input: &str| api.try_parse_reference(input).err().unwrap();
+
+        assert_eq!(
+            error("Ephesians 9:99"),
+            ReferenceError::ChapterOutOfRange {
+                book: String::from("Ephesians"),
+                chapter: 9,
+                chapter_count: 6,
+                span: 10..14
+            }
+        );
+        assert_eq!(
+            error("Eph 1:2, 99"),
+            ReferenceError::VerseOutOfRange {
+                book: String::from("Ephesians"),
+                chapter: 1,
+                verse: 99,
+                verse_count: 23,
+                span: 9..11
+            }
+        );
+        assert_eq!(
+            error("Jude 2:1").to_string(),
+            "Jude 2 does not exist (Jude has 1 chapter)"
+        );
+        assert_eq!(
+            error("John 3:17-16"),
+            ReferenceError::ReversedRange {
+                start: ChapterVerse {
+                    chapter: 3,
+                    verse: 17
+                },
+                end: ChapterVerse {
+                    chapter: 3,
+                    verse: 16
+                },
+                span: 5..12
+            }
+        );
+        assert_eq!(
+            error("Hezekiah 3:4"),
+            ReferenceError::UnknownBook { span: 0..8 }
+        );
+        // a book later in the input isn't the book of the reference
+        assert_eq!(
+            error("Hezekiah and John 3:16"),
+            ReferenceError::UnknownBook { span: 0..12 }
+        );
+        assert_eq!(
+            error("hello John 3:16"),
+            ReferenceError::UnknownBook { span: 0..5 }
+        );
+        assert_eq!(
+            api.try_parse_reference("  John 3:16").unwrap().label(),
+            "John 3:16"
+        );
+    }
+
+    #[test]
+    fn try_parse_malformed_references() {
+        let api = BibleAPI::load(test_bible_data());
+        let span = |input: &str| match api.try_parse_reference(input) {
+            Err(ReferenceError::MalformedSegment { span }) => span,
+            _ => panic!("{} is not malformed", input),
+        };
+        assert_eq!(span("John 3:16:17"), 5..12);
+        assert_eq!(span("John 3:16,,17"), 10..11);
+        assert_eq!(span("John 3:16,"), 9..10);
+        assert_eq!(span("John 3:16abc"), 9..10);
+        assert_eq!(span("John 3:"), 5..7);
+        assert_eq!(span("John 3-"), 5..7);
+        assert_eq!(span("John 99999999999999999999999:1"), 5..28);
+        // these used to panic
+        assert!(api.parse_reference("John 3:16:17").is_none());
+        assert!(api
+            .parse_reference("John 99999999999999999999999:1")
+            .is_none());
+    }
+}
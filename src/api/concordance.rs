@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    bible_data::{book::BibleBook, verse::BibleVerse},
+    related_media::{
+        cross_references::BookVerse,
+        interlinear::{align_words, normalize_strong, AlignedWord, Interlinear, InterlinearWord},
+        morphology::Morphology,
+    },
+};
+
+use super::{
+    bible_api::BibleAPI,
+    passage::{
+        passage::Passage,
+        segments::{PassageSegment, PassageSegments},
+    },
+};
+
+/// Where a word is: its verse and its position in the verse's interlinear words
+type Occurrences = Vec<(BookVerse, usize)>;
+
+/**
+An index of every interlinear word by its Strong's number and its lemma
+
+- Lemmas are matched case-insensitively, and so are transliterations (Ex: `agapē`)
+*/
+pub struct ConcordanceIndex {
+    strongs: BTreeMap<String, Occurrences>,
+    lemmas: HashMap<String, Occurrences>,
+}
+
+impl ConcordanceIndex {
+    pub fn build(interlinear: &Interlinear) -> Self {
+        let mut strongs: BTreeMap<String, Occurrences> = BTreeMap::new();
+        let mut lemmas: HashMap<String, Occurrences> = HashMap::new();
+        for (verse, words) in interlinear.iter() {
+            for (index, word) in words.words.iter().enumerate() {
+                if let Some(strong) = word.strong.as_deref().and_then(normalize_strong) {
+                    strongs.entry(strong).or_default().push((*verse, index));
+                }
+                let mut names = [word.lemma.as_deref(), word.transliteration.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .map(str::to_lowercase)
+                    .collect::<Vec<_>>();
+                names.dedup();
+                for name in names {
+                    lemmas.entry(name).or_default().push((*verse, index));
+                }
+            }
+        }
+        Self { strongs, lemmas }
+    }
+
+    /**
+    - Every occurrence of a Strong's number or lemma, in canonical order
+    - A Strong's number without a letter after it includes the ones with it (Ex: `H1254` is
+      `H1254a` too)
+    */
+    fn occurrences(&self, word: &str) -> Occurrences {
+        let mut occurrences = match normalize_strong(word) {
+            Some(strong) => self
+                .strongs
+                .range(strong.clone()..)
+                .take_while(|(key, _)| key.starts_with(&strong))
+                .filter(|(key, _)| {
+                    key[strong.len()..]
+                        .chars()
+                        .all(|ch| ch.is_ascii_alphabetic())
+                })
+                .flat_map(|(_, occurrences)| occurrences.iter().copied())
+                .collect(),
+            None => self
+                .lemmas
+                .get(&word.trim().to_lowercase())
+                .cloned()
+                .unwrap_or_default(),
+        };
+        occurrences.sort_unstable();
+        occurrences
+    }
+}
+
+/// A word of the original language text that matched a concordance query
+#[derive(Clone)]
+pub struct Occurrence<'a> {
+    /// the verse in the default translation
+    pub verse: BibleVerse<'a>,
+    /// the position of the word in the verse's interlinear words
+    pub index: usize,
+    pub word: AlignedWord<'a>,
+}
+
+impl Occurrence<'_> {
+    /// The gloss of the word, or the words of the translation it is aligned to
+    pub fn gloss(&self) -> Option<String> {
+        match &self.word.word.gloss {
+            Some(gloss) => Some(gloss.clone()),
+            None if !self.word.translation.is_empty() => Some(self.word.translation.join(" ")),
+            None => None,
+        }
+    }
+}
+
+/// Every occurrence of a Strong's number or lemma (see [`BibleAPI::concordance`])
+#[derive(Clone)]
+pub struct Concordance<'a> {
+    /// in canonical order
+    pub occurrences: Vec<Occurrence<'a>>,
+}
+
+impl<'a> Concordance<'a> {
+    /**
+    - Only the occurrences whose word is kept by `keep`
+    - Ex: only the words with a gloss, with `.filter(|word| word.gloss.is_some())`
+    */
+    pub fn filter(mut self, keep: impl Fn(&InterlinearWord) -> bool) -> Self {
+        self.occurrences
+            .retain(|occurrence| keep(occurrence.word.word));
+        self
+    }
+
+    /**
+    - Only the occurrences whose parsed morphology (see [`InterlinearWord::morphology`]) is kept
+      by `keep`
+    - Words without a valid morphology code are left out
+    - Ex: only the aorist imperatives, with
+      `.filter_morphology(|morph| morph.main().tense == Some(Tense::Aorist) && morph.main().mood == Some(Mood::Imperative))`
+    */
+    pub fn filter_morphology(self, keep: impl Fn(&Morphology) -> bool) -> Self {
+        self.filter(|word| {
+            word.morphology()
+                .is_some_and(|morphology| keep(&morphology))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.occurrences.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.occurrences.is_empty()
+    }
+
+    /// Every verse with an occurrence (once each), in canonical order
+    pub fn verses(&self) -> Vec<BibleVerse<'a>> {
+        let mut verses: Vec<BibleVerse<'a>> = vec![];
+        for occurrence in self.occurrences.iter() {
+            let verse = occurrence.verse;
+            let is_last = verses.last().is_some_and(|last| {
+                (
+                    last.get_book().book_number(),
+                    last.chapter_number(),
+                    last.verse_number(),
+                ) == (
+                    verse.get_book().book_number(),
+                    verse.chapter_number(),
+                    verse.verse_number(),
+                )
+            });
+            if !is_last {
+                verses.push(verse);
+            }
+        }
+        verses
+    }
+
+    /// - A passage for each book with an occurrence, with each of its verses
+    /// - Ex: `John 3:16; 13:34-35`
+    pub fn passages(&self) -> Vec<Passage<'a>> {
+        let mut passages: Vec<Passage<'a>> = vec![];
+        for verse in self.verses() {
+            let book = verse.get_book();
+            let (chapter, number) = (verse.chapter_number(), verse.verse_number());
+            match passages.last_mut() {
+                Some(passage) if passage.book.book_number() == book.book_number() => {
+                    match passage.segments.last_mut() {
+                        // extend `13:1-3` to `13:1-4`
+                        Some(segment)
+                            if segment.get_ending_chapter() == chapter
+                                && segment.get_ending_verse() + 1 == number =>
+                        {
+                            *segment = PassageSegment::chapter_verse_range(
+                                chapter,
+                                segment.get_starting_verse(),
+                                number,
+                            );
+                        }
+                        _ => passage
+                            .segments
+                            .push(PassageSegment::chapter_verse(chapter, number)),
+                    }
+                }
+                _ => passages.push(Passage {
+                    book,
+                    segments: PassageSegments(vec![PassageSegment::chapter_verse(chapter, number)]),
+                }),
+            }
+        }
+        passages
+    }
+
+    /// The number of occurrences in each book, in canonical order
+    pub fn book_counts(&self) -> Vec<(BibleBook<'a>, usize)> {
+        let mut counts: Vec<(BibleBook<'a>, usize)> = vec![];
+        for occurrence in self.occurrences.iter() {
+            let book = occurrence.verse.get_book();
+            match counts.last_mut() {
+                Some((last, count)) if last.book_number() == book.book_number() => *count += 1,
+                _ => counts.push((book, 1)),
+            }
+        }
+        counts
+    }
+
+    /// - Each gloss (see [`Occurrence::gloss`]) and how many times it is used, most used first
+    /// - Ex: `[("love", 106), ("charity", 27), ("dear", 1)]`
+    pub fn glosses(&self) -> Vec<(String, usize)> {
+        let mut glosses: Vec<(String, usize)> = vec![];
+        for gloss in self.occurrences.iter().filter_map(Occurrence::gloss) {
+            match glosses.iter_mut().find(|(used, _)| *used == gloss) {
+                Some((_, count)) => *count += 1,
+                None => glosses.push((gloss, 1)),
+            }
+        }
+        // the order they are first used breaks ties
+        glosses.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        glosses
+    }
+}
+
+impl BibleAPI {
+    /**
+    Every occurrence of a Strong's number (Ex: `G26`) or lemma (Ex: `ἀγάπη` or `agapē`) in the
+    interlinear data
+
+    - The verses are in the default translation
+    - The index is built by the first query (and rebuilt when interlinear data is added)
+    */
+    pub fn concordance(&self, word: &str) -> Concordance<'_> {
+        let index = self
+            .concordance_index
+            .get_or_init(|| ConcordanceIndex::build(&self.interlinear));
+        let mut verse_words = None;
+        let occurrences = index
+            .occurrences(word)
+            .into_iter()
+            .filter_map(|(key, index)| {
+                let verse = self.bible.get_verse(key.book, key.chapter, key.verse)?;
+                // the words of a verse are only aligned once for all of its occurrences
+                let words = match &verse_words {
+                    Some((last, words)) if *last == key => words,
+                    _ => {
+                        let interlinear =
+                            self.interlinear
+                                .get_verse(key.book, key.chapter, key.verse)?;
+                        let content = interlinear
+                            .translation
+                            .as_deref()
+                            .and_then(|translation| self.get_translation(translation))
+                            .and_then(|bible| bible.get_verse(key.book, key.chapter, key.verse))
+                            .and_then(|verse| verse.get_content());
+                        let words = align_words(interlinear, content, &self.lexicon);
+                        &verse_words.insert((key, words)).1
+                    }
+                };
+                Some(Occurrence {
+                    verse,
+                    index,
+                    word: words.get(index)?.clone(),
+                })
+            })
+            .collect();
+        Concordance { occurrences }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bible_data::formats::json::test_bible_data,
+        related_media::morphology::{Mood, Person, Tense},
+    };
+
+    use super::*;
+
+    fn api() -> BibleAPI {
+        let mut api = BibleAPI::load(test_bible_data());
+        api.add_interlinear(
+            serde_json::from_str(
+                r#"{
+  "translation": "KJV",
+  "verses": [
+    {
+      "book": 43,
+      "chapter": 3,
+      "verse": 16,
+      "words": [
+        { "text": "ἠγάπησεν", "lemma": "ἀγαπάω", "strong": "G25", "morph": "V-AAI-3S", "alignment": [{ "start": 11, "end": 16 }] }
+      ]
+    },
+    {
+      "book": 43,
+      "chapter": 13,
+      "verse": 34,
+      "words": [
+        { "text": "ἀγαπᾶτε", "lemma": "ἀγαπάω", "strong": "G25", "morph": "V-PAS-2P", "gloss": "love" },
+        { "text": "ἠγάπησα", "lemma": "ἀγαπάω", "strong": "G0025", "morph": "V-AAI-1S", "gloss": "have loved" },
+        { "text": "ἀγαπᾶτε", "lemma": "ἀγαπάω", "strong": "G25", "morph": "V-PAS-2P", "gloss": "love" }
+      ]
+    },
+    {
+      "book": 43,
+      "chapter": 13,
+      "verse": 35,
+      "words": [{ "text": "ἀγάπην", "lemma": "ἀγάπη", "transliteration": "agapē", "strong": "G26", "gloss": "love" }]
+    },
+    {
+      "book": 62,
+      "chapter": 4,
+      "verse": 8,
+      "words": [{ "text": "ἀγάπη", "lemma": "ἀγάπη", "strong": "G26", "gloss": "love" }]
+    }
+  ]
+}"#,
+            )
+            .unwrap(),
+        );
+        api
+    }
+
+    #[test]
+    fn concordance_by_strongs_number() {
+        let api = api();
+        let concordance = api.concordance("G25");
+        assert_eq!(concordance.len(), 4);
+        assert_eq!(
+            concordance
+                .passages()
+                .iter()
+                .map(Passage::label)
+                .collect::<Vec<_>>(),
+            ["John 3:16; 13:34"]
+        );
+        assert_eq!(concordance.verses().len(), 2);
+        assert_eq!(
+            concordance.glosses(),
+            [
+                ("love".to_string(), 2),
+                ("loved".to_string(), 1),
+                ("have loved".to_string(), 1)
+            ]
+        );
+
+        // only the aorists
+        let aorists = concordance
+            .clone()
+            .filter_morphology(|morph| morph.main().tense == Some(Tense::Aorist));
+        assert_eq!(aorists.len(), 2);
+        let subjunctives = concordance.filter_morphology(|morph| {
+            morph.main().mood == Some(Mood::Subjunctive)
+                && morph.main().person == Some(Person::Second)
+        });
+        assert_eq!(subjunctives.len(), 2);
+    }
+
+    #[test]
+    fn concordance_by_lemma() {
+        let api = api();
+        let concordance = api.concordance("ἀγάπη");
+        let counts = concordance
+            .book_counts()
+            .into_iter()
+            .map(|(book, count)| (book.get_name(), count))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [("John", 1), ("1 John", 1)]);
+        assert_eq!(api.concordance("AGAPĒ").len(), 1);
+        assert_eq!(api.concordance("g026").len(), 2);
+        assert!(api.concordance("G9999").is_empty());
+    }
+}
@@ -1,2 +1,5 @@
 pub mod bible_api;
+pub mod concordance;
 pub mod passage;
+pub mod search;
+pub mod suggestions;
@@ -0,0 +1,130 @@
+use std::{fmt::Display, ops::Range};
+
+use super::segments::ChapterVerse;
+
+/// - Why a reference couldn't be parsed, or why it doesn't exist
+/// - Every `span` is the byte range of the offending part of the input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReferenceError {
+    /// - No book name or abbreviation was found
+    /// - Ex: `Hezekiah 3:4`
+    UnknownBook { span: Range<usize> },
+    /// - The text after the book isn't a valid list of segments
+    /// - Ex: `John 3:16:17` or `John 3:16,,17`
+    MalformedSegment { span: Range<usize> },
+    /// - Ex: `Jude 2:1`
+    ChapterOutOfRange {
+        book: String,
+        chapter: usize,
+        chapter_count: usize,
+        span: Range<usize>,
+    },
+    /// - Ex: `Ephesians 1:99`
+    VerseOutOfRange {
+        book: String,
+        chapter: usize,
+        verse: usize,
+        verse_count: usize,
+        span: Range<usize>,
+    },
+    /// - Ex: `John 3:17-16`
+    ReversedRange {
+        start: ChapterVerse,
+        end: ChapterVerse,
+        span: Range<usize>,
+    },
+    /// - Ex: `Mark 1:5-Matthew 28:1`
+    ReversedBookRange {
+        start_book: String,
+        end_book: String,
+        span: Range<usize>,
+    },
+}
+
+impl ReferenceError {
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            ReferenceError::UnknownBook { span }
+            | ReferenceError::MalformedSegment { span }
+            | ReferenceError::ChapterOutOfRange { span, .. }
+            | ReferenceError::VerseOutOfRange { span, .. }
+            | ReferenceError::ReversedRange { span, .. }
+            | ReferenceError::ReversedBookRange { span, .. } => span.clone(),
+        }
+    }
+
+    /// For when the input that was parsed is part of a larger input
+    pub(crate) fn offset(mut self, offset: usize) -> Self {
+        match &mut self {
+            ReferenceError::UnknownBook { span }
+            | ReferenceError::MalformedSegment { span }
+            | ReferenceError::ChapterOutOfRange { span, .. }
+            | ReferenceError::VerseOutOfRange { span, .. }
+            | ReferenceError::ReversedRange { span, .. }
+            | ReferenceError::ReversedBookRange { span, .. } => {
+                *span = span.start + offset..span.end + offset;
+            }
+        }
+        self
+    }
+}
+
+impl Display for ReferenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceError::UnknownBook { .. } => write!(f, "Unknown book"),
+            ReferenceError::MalformedSegment { .. } => write!(
+                f,
+                "Expected chapters and verses like `3:16`, `3:16-18` or `3:16-4:2`"
+            ),
+            ReferenceError::ChapterOutOfRange {
+                book,
+                chapter,
+                chapter_count,
+                ..
+            } => write!(
+                f,
+                "{} {} does not exist ({} has {})",
+                book,
+                chapter,
+                book,
+                plural(*chapter_count, "chapter")
+            ),
+            ReferenceError::VerseOutOfRange {
+                book,
+                chapter,
+                verse,
+                verse_count,
+                ..
+            } => write!(
+                f,
+                "{} {}:{} does not exist ({} {} has {})",
+                book,
+                chapter,
+                verse,
+                book,
+                chapter,
+                plural(*verse_count, "verse")
+            ),
+            ReferenceError::ReversedRange { start, end, .. } => write!(
+                f,
+                "{}:{} comes after {}:{}",
+                start.chapter, start.verse, end.chapter, end.verse
+            ),
+            ReferenceError::ReversedBookRange {
+                start_book,
+                end_book,
+                ..
+            } => write!(f, "{} comes after {}", start_book, end_book),
+        }
+    }
+}
+
+impl std::error::Error for ReferenceError {}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("{} {}", count, noun),
+        _ => format!("{} {}s", count, noun),
+    }
+}
@@ -1,3 +1,7 @@
+use std::borrow::Cow;
+
+use crate::bible_data::bible_data::{ParagraphStyle, TextStyle, VerseMarkup};
+
 #[derive(Clone, Debug)]
 enum TemplateSegment {
     Raw(String),
@@ -73,12 +77,45 @@ pub trait TemplateFormatting {
 }
 
 pub struct PassageFormatterBuilder {
-    // can use book, chapter, verse, content
+    // can use book, chapter, verse, content, headings, styled_content, footnotes
     pub verse: Option<String>,
 
     // the text that joins all verses together
     pub join_verses: Option<String>,
 
+    // the text that joins verses at a new paragraph (or after a heading)
+    pub join_paragraphs: Option<String>,
+
+    // the text that joins verses at a new line of poetry
+    pub join_lines: Option<String>,
+
+    // the text that is repeated before a line of poetry for each level of indentation past 1
+    pub indent: Option<String>,
+
+    // can use book, chapter, verse, heading
+    pub heading: Option<String>,
+
+    // this is for red letters, divine names, and so on in styled_content
+    // can use style, text
+    pub style: Option<String>,
+
+    // can use book, chapter, verse, number, origin, text
+    pub footnote: Option<String>,
+
+    // the text that joins footnotes together
+    pub join_footnotes: Option<String>,
+
+    // where a footnote is in styled_content, when footnotes are collected
+    // can use the same as footnote
+    pub footnote_marker: Option<String>,
+
+    // the footnotes after the passage, when footnotes are collected
+    // can use footnotes
+    pub footnotes: Option<String>,
+
+    // number the footnotes of a passage and put them after it, instead of in each verse
+    pub collect_footnotes: bool,
+
     // can use verses, the segment label, book
     pub segment: Option<String>,
 
@@ -95,6 +132,12 @@ pub struct PassageFormatterBuilder {
 
     // can use book, segments label, segments
     pub text: Option<String>,
+
+    // the text that joins all passages (of different books) together
+    pub join_passages: Option<String>,
+
+    // can use passages, the label of all passages
+    pub passages: Option<String>,
 }
 
 impl PassageFormatterBuilder {
@@ -102,15 +145,50 @@ impl PassageFormatterBuilder {
         Self {
             verse: None,
             join_verses: None,
+            join_paragraphs: None,
+            join_lines: None,
+            indent: None,
+            heading: None,
+            style: None,
+            footnote: None,
+            join_footnotes: None,
+            footnote_marker: None,
+            footnotes: None,
+            collect_footnotes: false,
             segment: None,
             join_segments: None,
             chapter: None,
             join_chapters: None,
             text: None,
+            join_passages: None,
+            passages: None,
         }
     }
 
-    // can use book, chapter, verse, content
+    /**
+    A formatter that lays verses out in paragraphs and lines of poetry, with their headings
+
+    ```text
+    ### Psalms 23:1-2
+
+    #### The LORD Is My Shepherd
+
+    [1] The LORD is my shepherd; I shall not want.
+    [2] He makes me lie down in green pastures.
+      He leads me beside still waters.
+    ```
+    */
+    pub fn paragraphs() -> Self {
+        Self::new()
+            .verse("{headings}[{verse}] {styled_content}")
+            .join_verses(" ")
+            .join_paragraphs("\n\n")
+            .join_lines("\n")
+            .indent("  ")
+            .heading("#### {heading}\n\n")
+    }
+
+    // can use book, chapter, verse, content, headings, styled_content, footnotes
     pub fn verse(mut self, verse: impl Into<String>) -> Self {
         self.verse = Some(verse.into());
         self
@@ -122,6 +200,69 @@ impl PassageFormatterBuilder {
         self
     }
 
+    // the text that joins verses at a new paragraph (or after a heading)
+    pub fn join_paragraphs(mut self, join_paragraphs: impl Into<String>) -> Self {
+        self.join_paragraphs = Some(join_paragraphs.into());
+        self
+    }
+
+    // the text that joins verses at a new line of poetry
+    pub fn join_lines(mut self, join_lines: impl Into<String>) -> Self {
+        self.join_lines = Some(join_lines.into());
+        self
+    }
+
+    // the text that is repeated before a line of poetry for each level of indentation past 1
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = Some(indent.into());
+        self
+    }
+
+    // can use book, chapter, verse, heading
+    pub fn heading(mut self, heading: impl Into<String>) -> Self {
+        self.heading = Some(heading.into());
+        self
+    }
+
+    // this is for red letters, divine names, and so on in styled_content
+    // can use style, text
+    pub fn style(mut self, style: impl Into<String>) -> Self {
+        self.style = Some(style.into());
+        self
+    }
+
+    // can use book, chapter, verse, number, origin, text
+    pub fn footnote(mut self, footnote: impl Into<String>) -> Self {
+        self.footnote = Some(footnote.into());
+        self
+    }
+
+    // the text that joins footnotes together
+    pub fn join_footnotes(mut self, join_footnotes: impl Into<String>) -> Self {
+        self.join_footnotes = Some(join_footnotes.into());
+        self
+    }
+
+    // where a footnote is in styled_content, when footnotes are collected
+    // can use the same as footnote
+    pub fn footnote_marker(mut self, footnote_marker: impl Into<String>) -> Self {
+        self.footnote_marker = Some(footnote_marker.into());
+        self
+    }
+
+    // the footnotes after the passage, when footnotes are collected
+    // can use footnotes
+    pub fn footnotes(mut self, footnotes: impl Into<String>) -> Self {
+        self.footnotes = Some(footnotes.into());
+        self
+    }
+
+    // number the footnotes of a passage and put them after it, instead of in each verse
+    pub fn collect_footnotes(mut self) -> Self {
+        self.collect_footnotes = true;
+        self
+    }
+
     // can use verses, the segment label, book
     pub fn segment(mut self, segment: impl Into<String>) -> Self {
         self.segment = Some(segment.into());
@@ -154,12 +295,41 @@ impl PassageFormatterBuilder {
         self
     }
 
+    // the text that joins all passages (of different books) together
+    pub fn join_passages(mut self, join_passages: impl Into<String>) -> Self {
+        self.join_passages = Some(join_passages.into());
+        self
+    }
+
+    // can use passages, the label of all passages
+    pub fn passages(mut self, passages: impl Into<String>) -> Self {
+        self.passages = Some(passages.into());
+        self
+    }
+
     pub fn build(self) -> PassageFormatter {
+        let join_verses = self.join_verses.unwrap_or_else(|| String::from("\n"));
         PassageFormatter {
             verse: self
                 .verse
                 .unwrap_or_else(|| String::from("[{chapter}:{verse}] {content}")),
-            join_verses: self.join_verses.unwrap_or_else(|| String::from("\n")),
+            join_paragraphs: self.join_paragraphs.unwrap_or_else(|| join_verses.clone()),
+            join_lines: self.join_lines.unwrap_or_else(|| join_verses.clone()),
+            join_verses,
+            indent: self.indent.unwrap_or_default(),
+            heading: self.heading.unwrap_or_else(|| String::from("{heading}\n")),
+            style: self.style.unwrap_or_else(|| String::from("{text}")),
+            footnote: self
+                .footnote
+                .unwrap_or_else(|| String::from("[{number}] {text}")),
+            join_footnotes: self.join_footnotes.unwrap_or_else(|| String::from("\n")),
+            footnote_marker: self
+                .footnote_marker
+                .unwrap_or_else(|| String::from("[{number}]")),
+            footnotes: self
+                .footnotes
+                .unwrap_or_else(|| String::from("\n\n{footnotes}")),
+            collect_footnotes: self.collect_footnotes,
             segment: self.segment.unwrap_or_else(|| String::from("{verses}")),
             join_segments: self.join_segments.unwrap_or_else(|| String::from("\n\n")),
             chapter: self.chapter.unwrap_or_else(|| String::from("{verses}")),
@@ -167,17 +337,52 @@ impl PassageFormatterBuilder {
             text: self
                 .text
                 .unwrap_or_else(|| String::from("### {book} {label}\n\n{segments}")),
+            join_passages: self.join_passages.unwrap_or_else(|| String::from("\n\n")),
+            passages: self.passages.unwrap_or_else(|| String::from("{passages}")),
         }
     }
 }
 
 pub struct PassageFormatter {
-    // can use book, chapter, verse, content
+    // can use book, chapter, verse, content, headings, styled_content, footnotes
     pub verse: String,
 
     // the text that joins all verses together
     pub join_verses: String,
 
+    // the text that joins verses at a new paragraph (or after a heading)
+    pub join_paragraphs: String,
+
+    // the text that joins verses at a new line of poetry
+    pub join_lines: String,
+
+    // the text that is repeated before a line of poetry for each level of indentation past 1
+    pub indent: String,
+
+    // can use book, chapter, verse, heading
+    pub heading: String,
+
+    // this is for red letters, divine names, and so on in styled_content
+    // can use style, text
+    pub style: String,
+
+    // can use book, chapter, verse, number, origin, text
+    pub footnote: String,
+
+    // the text that joins footnotes together
+    pub join_footnotes: String,
+
+    // where a footnote is in styled_content, when footnotes are collected
+    // can use the same as footnote
+    pub footnote_marker: String,
+
+    // the footnotes after the passage, when footnotes are collected
+    // can use footnotes
+    pub footnotes: String,
+
+    // number the footnotes of a passage and put them after it, instead of in each verse
+    pub collect_footnotes: bool,
+
     // can use verses, the segment label, book
     pub segment: String,
 
@@ -194,6 +399,130 @@ pub struct PassageFormatter {
 
     // can use book, segments label, segments
     pub text: String,
+
+    // the text that joins all passages (of different books) together
+    pub join_passages: String,
+
+    // can use passages, the label of all passages
+    pub passages: String,
+}
+
+impl PassageFormatter {
+    /// - The text that goes before a paragraph (or line of poetry) that starts in `style`
+    /// - Ex: `join_lines` and 1 `indent` for `Poetry(2)`
+    pub fn paragraph_break(&self, style: ParagraphStyle) -> String {
+        match style {
+            ParagraphStyle::Prose => self.join_paragraphs.clone(),
+            ParagraphStyle::Poetry(level) => format!(
+                "{}{}",
+                self.join_lines,
+                self.indent.repeat(level.saturating_sub(1) as usize)
+            ),
+        }
+    }
+
+    /// The text that joins a verse (with `verse` as its markup) to the verse before it
+    pub fn verse_separator(&self, verse: &VerseMarkup) -> Cow<'_, str> {
+        let style = verse
+            .paragraphs()
+            .iter()
+            .find(|paragraph| paragraph.offset == 0)
+            .map(|paragraph| paragraph.style);
+        match style {
+            _ if !verse.headings().is_empty() => Cow::Borrowed(&self.join_paragraphs),
+            Some(style) => Cow::Owned(self.paragraph_break(style)),
+            None => Cow::Borrowed(&self.join_verses),
+        }
+    }
+}
+
+/**
+The `content` of a verse with its paragraph breaks (after the start of the verse) and styles
+(from `verse`, its markup)
+
+- Ex: `He said, Follow me.` with `Follow me` in red letters is
+  `He said, <span class="words-of-jesus">Follow me</span>.` when `style_template` is
+  `<span class="{style}">{text}</span>`
+- `markers` are put where each of the verse's footnotes are (Ex: `[1]`), and can be empty
+*/
+pub fn styled_content(
+    content: &str,
+    verse: &VerseMarkup,
+    formatter: &PassageFormatter,
+    style_template: &FormattingTemplate,
+    markers: &[String],
+) -> String {
+    enum Event<'a> {
+        End(TextStyle),
+        Footnote(&'a str),
+        Break(ParagraphStyle),
+        Start(TextStyle),
+    }
+    let mut events = vec![];
+    // longer styles start first and end last, so that they are nested
+    let mut styles = verse.styles().iter().collect::<Vec<_>>();
+    styles.sort_by_key(|styled| (styled.range.start, std::cmp::Reverse(styled.range.end)));
+    for styled in styles.iter() {
+        events.push((styled.range.start, 3, Event::Start(styled.style)));
+    }
+    for styled in styles.iter().rev() {
+        events.push((styled.range.end, 0, Event::End(styled.style)));
+    }
+    for paragraph in verse
+        .paragraphs()
+        .iter()
+        .filter(|paragraph| paragraph.offset > 0)
+    {
+        events.push((paragraph.offset, 2, Event::Break(paragraph.style)));
+    }
+    for (note, marker) in verse.footnotes().iter().zip(markers) {
+        events.push((note.offset, 1, Event::Footnote(marker)));
+    }
+    events.sort_by_key(|(offset, order, _)| (*offset, *order));
+
+    // the text of each style that has started, inside of the text of the verse
+    let mut open: Vec<(Option<TextStyle>, String)> = vec![(None, String::new())];
+    let close = |open: &mut Vec<(Option<TextStyle>, String)>| {
+        let (style, text) = open.pop().unwrap();
+        let params = StyleFormatParameters {
+            style: style.map_or("", |style| style.name()),
+            text: &text,
+        };
+        let filled = style_template.fill(&params).unwrap();
+        open.last_mut().unwrap().1.push_str(&filled);
+    };
+    let mut written = 0;
+    for (offset, _, event) in events {
+        let offset = offset.clamp(written, content.len());
+        if let Some(text) = content.get(written..offset) {
+            open.last_mut().unwrap().1.push_str(text);
+            written = offset;
+        }
+        match event {
+            Event::Start(style) => open.push((Some(style), String::new())),
+            Event::End(style) => {
+                if let Some(index) = open.iter().rposition(|(open, _)| *open == Some(style)) {
+                    while open.len() > index {
+                        close(&mut open);
+                    }
+                }
+            }
+            Event::Footnote(marker) => open.last_mut().unwrap().1.push_str(marker),
+            Event::Break(style) => {
+                let text = &mut open.last_mut().unwrap().1;
+                text.truncate(text.trim_end().len());
+                text.push_str(&formatter.paragraph_break(style));
+            }
+        }
+    }
+    open.last_mut()
+        .unwrap()
+        .1
+        .push_str(content.get(written..).unwrap_or_default());
+    while open.len() > 1 {
+        close(&mut open);
+    }
+    open.pop().unwrap().1
 }
 
 pub struct VerseFormatParameters<'a> {
@@ -201,15 +530,134 @@ pub struct VerseFormatParameters<'a> {
     pub chapter: usize,
     pub verse: usize,
     pub content: &'a str,
+    /// the abbreviation of the translation that `content` is from
+    pub translation: &'a str,
+    /// the abbreviation and content of this verse in other translations (for `{content:ESV}`)
+    pub parallel: &'a [(&'a str, Option<Cow<'a, str>>)],
+    /// the filled heading template for each heading before this verse
+    pub headings: &'a str,
+    /// the content with its paragraph breaks and styles (see [`styled_content`])
+    pub styled_content: &'a str,
+    /// the filled footnote template for each footnote of this verse (empty when they're collected)
+    pub footnotes: &'a str,
+}
+
+impl<'a> VerseFormatParameters<'a> {
+    /// - The content of this verse in another translation
+    /// - A verse that translation doesn't have is empty
+    fn parallel_content(&self, translation: &str) -> Result<&str, String> {
+        if translation.eq_ignore_ascii_case(self.translation) {
+            return Ok(self.content);
+        }
+        self.parallel
+            .iter()
+            .find(|(abbreviation, _)| abbreviation.eq_ignore_ascii_case(translation))
+            .map(|(_, content)| content.as_deref().unwrap_or(""))
+            .ok_or_else(|| format!("'{}' is not a loaded translation.", translation))
+    }
 }
 
 impl<'a> TemplateFormatting for VerseFormatParameters<'a> {
     fn variables(&self, variable: &str) -> Result<String, String> {
+        if let Some(translation) = variable.strip_prefix("content:") {
+            return Ok(self.parallel_content(translation)?.to_string());
+        }
         Ok(match variable {
             "book" => self.book.to_string(),
             "chapter" => self.chapter.to_string(),
             "verse" => self.verse.to_string(),
             "content" => self.content.to_string(),
+            "translation" => self.translation.to_string(),
+            "headings" => self.headings.to_string(),
+            "styled_content" => self.styled_content.to_string(),
+            "footnotes" => self.footnotes.to_string(),
+            _ => Err(format!(
+                "'{}' is not a valid template identifier.",
+                variable
+            ))?,
+        })
+    }
+}
+
+pub struct HeadingFormatParameters<'a> {
+    pub book: &'a str,
+    pub chapter: usize,
+    pub verse: usize,
+    pub heading: &'a str,
+}
+
+impl<'a> TemplateFormatting for HeadingFormatParameters<'a> {
+    fn variables(&self, variable: &str) -> Result<String, String> {
+        Ok(match variable {
+            "book" => self.book.to_string(),
+            "chapter" => self.chapter.to_string(),
+            "verse" => self.verse.to_string(),
+            "heading" => self.heading.to_string(),
+            _ => Err(format!(
+                "'{}' is not a valid template identifier.",
+                variable
+            ))?,
+        })
+    }
+}
+
+pub struct StyleFormatParameters<'a> {
+    /// Ex: `words-of-jesus` (see [`TextStyle::name`])
+    pub style: &'a str,
+    pub text: &'a str,
+}
+
+impl<'a> TemplateFormatting for StyleFormatParameters<'a> {
+    fn variables(&self, variable: &str) -> Result<String, String> {
+        Ok(match variable {
+            "style" => self.style.to_string(),
+            "text" => self.text.to_string(),
+            _ => Err(format!(
+                "'{}' is not a valid template identifier.",
+                variable
+            ))?,
+        })
+    }
+}
+
+pub struct FootnoteFormatParameters<'a> {
+    pub book: &'a str,
+    pub chapter: usize,
+    pub verse: usize,
+    /// - Where the footnote is in the passage, starting at 1
+    /// - Without `collect_footnotes`, this is where it is in the verse
+    pub number: usize,
+    /// Ex: `1:1` (empty when the note doesn't say)
+    pub origin: &'a str,
+    pub text: &'a str,
+}
+
+impl<'a> TemplateFormatting for FootnoteFormatParameters<'a> {
+    fn variables(&self, variable: &str) -> Result<String, String> {
+        Ok(match variable {
+            "book" => self.book.to_string(),
+            "chapter" => self.chapter.to_string(),
+            "verse" => self.verse.to_string(),
+            "number" => self.number.to_string(),
+            "origin" => self.origin.to_string(),
+            "text" => self.text.to_string(),
+            _ => Err(format!(
+                "'{}' is not a valid template identifier.",
+                variable
+            ))?,
+        })
+    }
+}
+
+pub struct FootnotesFormatParameters<'a> {
+    /// the filled footnote template for each footnote of the passage
+    pub footnotes: &'a str,
+}
+
+impl<'a> TemplateFormatting for FootnotesFormatParameters<'a> {
+    fn variables(&self, variable: &str) -> Result<String, String> {
+        Ok(match variable {
+            "footnotes" => self.footnotes.to_string(),
             _ => Err(format!(
                 "'{}' is not a valid template identifier.",
                 variable
@@ -281,3 +729,21 @@ impl<'a> TemplateFormatting for PassageFormatParameters<'a> {
         })
     }
 }
+
+pub struct MultiPassageFormatParameters<'a> {
+    pub label: &'a str,
+    pub passages: &'a str,
+}
+
+impl<'a> TemplateFormatting for MultiPassageFormatParameters<'a> {
+    fn variables(&self, variable: &str) -> Result<String, String> {
+        Ok(match variable {
+            "label" => self.label.to_string(),
+            "passages" => self.passages.to_string(),
+            _ => Err(format!(
+                "'{}' is not a valid template identifier.",
+                variable
+            ))?,
+        })
+    }
+}
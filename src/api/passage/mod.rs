@@ -1,4 +1,7 @@
+pub mod errors;
 pub mod formatting_template;
 pub mod iter;
+pub mod multi_passage;
+pub mod parallel;
 pub mod passage;
 pub mod segments;
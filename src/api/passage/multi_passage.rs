@@ -0,0 +1,341 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::bible_data::{bible_data::BibleData, book::BibleBook, verse::BibleVerse};
+
+use super::{
+    formatting_template::{FormattingTemplate, MultiPassageFormatParameters, PassageFormatter},
+    iter::PassageIterator,
+    passage::Passage,
+    segments::{ChapterVerse, PassageSegment, PassageSegments},
+};
+
+/// - A range that starts in one book and ends in another
+/// - Ex: `Matthew 28:1-Mark 1:5`
+#[derive(Clone, Copy)]
+pub struct BookRange<'a> {
+    pub start_book: BibleBook<'a>,
+    pub start: ChapterVerse,
+    pub end_book: BibleBook<'a>,
+    pub end: ChapterVerse,
+}
+
+impl<'a> BookRange<'a> {
+    /// Ex: `Matthew 28:1-Mark 1:5`
+    pub fn label(&self) -> String {
+        format!(
+            "{} {}:{}-{} {}:{}",
+            self.start_book.get_name(),
+            self.start.chapter,
+            self.start.verse,
+            self.end_book.get_name(),
+            self.end.chapter,
+            self.end.verse
+        )
+    }
+
+    /**
+    Splits this into a passage for each book
+
+    - `Matthew 28:1-Mark 1:5` is `Matthew 28:1-20` and `Mark 1:1-5`
+    - Any books in between are whole books, like `Mark` in `Matthew 28:1-Luke 1:5`
+    */
+    pub fn passages(&self) -> Vec<Passage<'a>> {
+        let first = ChapterVerse {
+            chapter: 1,
+            verse: 1,
+        };
+        let mut passages = vec![];
+        let mut book = Some(self.start_book);
+        while let Some(current) =
+            book.filter(|book| book.book_number() <= self.end_book.book_number())
+        {
+            let start = if current.book_number() == self.start_book.book_number() {
+                self.start
+            } else {
+                first
+            };
+            let segment = if current.book_number() == self.end_book.book_number() {
+                PassageSegment::between(start, self.end)
+            } else if start == first {
+                PassageSegment::full_book(current)
+            } else {
+                PassageSegment::between(start, last_verse(current))
+            };
+            passages.push(Passage {
+                book: current,
+                segments: PassageSegments(vec![segment]),
+            });
+            book = current.next_book();
+        }
+        passages
+    }
+}
+
+fn last_verse(book: BibleBook) -> ChapterVerse {
+    let chapter = book.chapter_count();
+    ChapterVerse {
+        chapter,
+        verse: book
+            .get_chapter(chapter)
+            .map_or(0, |chapter| chapter.verse_count()),
+    }
+}
+
+#[derive(Clone)]
+pub enum MultiPassageEntry<'a> {
+    /// - Ex: `Romans 8:28` or `Ephesians 1:1-4,6`
+    Passage(Passage<'a>),
+    /// - Ex: `Matthew 28:1-Mark 1:5`
+    BookRange(BookRange<'a>),
+}
+
+impl<'a> MultiPassageEntry<'a> {
+    pub fn label(&self) -> String {
+        match self {
+            MultiPassageEntry::Passage(passage) => passage.label(),
+            MultiPassageEntry::BookRange(book_range) => book_range.label(),
+        }
+    }
+
+    /// The passages of every book this covers
+    pub fn passages(&self) -> Vec<Passage<'a>> {
+        match self {
+            MultiPassageEntry::Passage(passage) => vec![passage.clone()],
+            MultiPassageEntry::BookRange(book_range) => book_range.passages(),
+        }
+    }
+}
+
+/// - An ordered list of passages that may be in different books
+/// - Ex: `Romans 8:28; Ephesians 2:8-9` or `Matthew 28:19-Mark 1:1; John 3:16`
+#[derive(Clone, Default)]
+pub struct MultiPassage<'a>(pub Vec<MultiPassageEntry<'a>>);
+
+impl<'a> MultiPassage<'a> {
+    /// - The same references in another translation, so they can be formatted in that translation
+    /// - Returns [`None`] when the translation doesn't have one of the books
+    pub fn in_translation<'b>(&self, bible: &'b BibleData) -> Option<MultiPassage<'b>> {
+        let entries = self
+            .0
+            .iter()
+            .map(|entry| match entry {
+                MultiPassageEntry::Passage(passage) => {
+                    Some(MultiPassageEntry::Passage(passage.in_translation(bible)?))
+                }
+                MultiPassageEntry::BookRange(book_range) => {
+                    Some(MultiPassageEntry::BookRange(BookRange {
+                        start_book: bible.get_book(book_range.start_book.book_number())?,
+                        start: book_range.start,
+                        end_book: bible.get_book(book_range.end_book.book_number())?,
+                        end: book_range.end,
+                    }))
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(MultiPassage(entries))
+    }
+
+    /// Ex: `Romans 8:28; Ephesians 2:8-9`
+    pub fn label(&self) -> String {
+        self.0
+            .iter()
+            .map(MultiPassageEntry::label)
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// The passages of every entry, split so that each is in 1 book
+    pub fn passages(&self) -> Vec<Passage<'a>> {
+        self.0
+            .iter()
+            .flat_map(MultiPassageEntry::passages)
+            .collect()
+    }
+
+    /// - Each passage is formatted like [`Passage::format`] and joined with `join_passages`
+    /// - The `passages` template can use `{label}` and `{passages}`
+    pub fn format(&self, formatter: &PassageFormatter) -> String {
+        let passages_template = FormattingTemplate::from_template(&formatter.passages).unwrap();
+        let passages = self
+            .passages()
+            .iter()
+            .map(|passage| passage.format(formatter))
+            .collect::<Vec<_>>()
+            .join(&formatter.join_passages);
+        let params = MultiPassageFormatParameters {
+            label: &self.label(),
+            passages: &passages,
+        };
+        passages_template.fill(&params).unwrap()
+    }
+}
+
+impl<'a> Deref for MultiPassage<'a> {
+    type Target = Vec<MultiPassageEntry<'a>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> DerefMut for MultiPassage<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Iterates over every verse of every passage, across books
+pub struct MultiPassageIterator<'a> {
+    passages: std::vec::IntoIter<Passage<'a>>,
+    passage_iterator: Option<PassageIterator<'a>>,
+}
+
+impl<'a> IntoIterator for MultiPassage<'a> {
+    type Item = BibleVerse<'a>;
+
+    type IntoIter = MultiPassageIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MultiPassageIterator {
+            passages: self.passages().into_iter(),
+            passage_iterator: None,
+        }
+    }
+}
+
+impl<'a> Iterator for MultiPassageIterator<'a> {
+    type Item = BibleVerse<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let next = self.passage_iterator.as_mut().and_then(|iter| iter.next());
+            if next.is_some() {
+                return next;
+            }
+            // move on to the next passage, or finish
+            self.passage_iterator = Some(self.passages.next()?.into_iter());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{
+            bible_api::BibleAPI, passage::errors::ReferenceError,
+            passage::formatting_template::PassageFormatterBuilder,
+        },
+        bible_data::formats::{
+            json::{test_bible_data, test_json_bible},
+            parse::ParseBibleData,
+        },
+    };
+
+    use super::*;
+
+    fn passage_labels(passages: &MultiPassage) -> Vec<String> {
+        passages.passages().iter().map(Passage::label).collect()
+    }
+
+    #[test]
+    fn parse_multiple_books() {
+        let api = BibleAPI::load(test_bible_data());
+        let passages = api
+            .try_parse_passages("Romans 8:28; Eph 2:8-9, 1 Jn 4:8; Jude")
+            .unwrap();
+        assert_eq!(
+            passages.label(),
+            "Romans 8:28; Ephesians 2:8-9; 1 John 4:8; Jude"
+        );
+        assert_eq!(passages.len(), 4);
+        assert_eq!(passages.clone().into_iter().count(), 1 + 2 + 1 + 25);
+
+        let verses = api
+            .parse_passages("John 3:16; Romans 3:23")
+            .unwrap()
+            .into_iter()
+            .map(|verse| verse.get_content().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(verses.len(), 2);
+        assert!(verses[0].starts_with("For God so loved the world"));
+        assert!(verses[1].starts_with("For all have sinned"));
+    }
+
+    #[test]
+    fn parse_book_ranges() {
+        let api = BibleAPI::load(test_bible_data());
+        let passages = api.try_parse_passages("Matthew 28:1-Mark 1:5").unwrap();
+        assert_eq!(passages.label(), "Matthew 28:1-Mark 1:5");
+        assert_eq!(
+            passage_labels(&passages),
+            vec!["Matthew 28:1-20", "Mark 1:1-5"]
+        );
+        assert_eq!(passages.into_iter().count(), 20 + 5);
+
+        // the books in between are whole books
+        let passages = api
+            .try_parse_passages("Ps 23; Matt 28:19 – Luke 1:1, John 3:16")
+            .unwrap();
+        assert_eq!(
+            passages.label(),
+            "Psalms 23; Matthew 28:19-Luke 1:1; John 3:16"
+        );
+        assert_eq!(
+            passage_labels(&passages),
+            vec![
+                "Psalms 23",
+                "Matthew 28:19-20",
+                "Mark",
+                "Luke 1:1",
+                "John 3:16"
+            ]
+        );
+        assert_eq!(passages.into_iter().count(), 6 + 2 + 678 + 1 + 1);
+
+        // books that aren't in the translation are skipped, instead of ending the range
+        let mut bible = test_json_bible();
+        bible.bible.retain(|book| book.id != 41);
+        let api = BibleAPI::load(bible.as_bible_data().unwrap());
+        let passages = api.try_parse_passages("Matthew 28:19-Luke 1:1").unwrap();
+        assert_eq!(
+            passage_labels(&passages),
+            vec!["Matthew 28:19-20", "Luke 1:1"]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_book_ranges() {
+        let api = BibleAPI::load(test_bible_data());
+        let error = |input: &str| api.try_parse_passages(input).err().unwrap();
+        assert_eq!(
+            error("Mark 1:5-Matthew 28:1"),
+            ReferenceError::ReversedBookRange {
+                start_book: String::from("Mark"),
+                end_book: String::from("Matthew"),
+                span: 5..21,
+            }
+        );
+        assert_eq!(error("Romans 8:28; Ephesians 9:1").span(), 23..26);
+        assert_eq!(error("Hezekiah 3:4; John 3:16").span(), 0..8);
+        assert_eq!(error("Matthew 28:1-Mark 1:5-Luke 1:1").span(), 21..22);
+        assert!(api.parse_passages("John 3:16;").is_none());
+    }
+
+    #[test]
+    fn format_multiple_books() {
+        let api = BibleAPI::load(test_bible_data());
+        let formatter = PassageFormatterBuilder::new()
+            .verse("{chapter}:{verse}")
+            .join_verses(",")
+            .text("{book} {label} ({segments})")
+            .join_passages(" / ")
+            .passages("[{label}] {passages}")
+            .build();
+        assert_eq!(
+            api.parse_passages("Romans 6:23; Matthew 28:19-Mark 1:2")
+                .unwrap()
+                .format(&formatter),
+            "[Romans 6:23; Matthew 28:19-Mark 1:2] Romans 6:23 (6:23) / Matthew 28:19-20 (28:19,28:20) / Mark 1:1-2 (1:1,1:2)"
+        );
+    }
+}
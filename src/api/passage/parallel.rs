@@ -0,0 +1,315 @@
+use crate::bible_data::{bible_data::BibleData, versification::Versifications};
+
+use super::{
+    formatting_template::{FormattingTemplate, PassageFormatter, VerseFormatParameters},
+    passage::Passage,
+    segments::ChapterVerse,
+};
+
+/// How [`Passage::format_parallel`] lays out the translations
+#[derive(Clone, Copy, Debug)]
+pub enum ParallelLayout {
+    /// - Plain text columns, with each verse wrapped to `width` characters
+    /// - Verses are aligned, so every row starts at the same line in each column
+    Columns { width: usize },
+    /// A Markdown table with a row for each verse and a column for each translation
+    MarkdownTable,
+    /// An HTML `<table>` with a row for each verse and a column for each translation
+    Html,
+}
+
+/// A row of [`Passage::format_parallel`]
+struct ParallelRow {
+    /// Ex: `3:16`
+    reference: String,
+    /// The filled verse template for each translation
+    cells: Vec<String>,
+}
+
+impl Passage<'_> {
+    /**
+    Renders the verses of this passage in each of `translations`, aligned verse by verse
+
+    - Each cell is the verse template of `formatter` (so use `{content}` rather than the
+      default `[{chapter}:{verse}] {content}`), and `{translation}` is the translation of that
+      cell
+    - Verses that a translation doesn't have are empty
+    - Ex: `John 3:16` in the KJV and ESV as a [`ParallelLayout::MarkdownTable`]
+
+    ```text
+    | | KJV | ESV |
+    |---|---|---|
+    | 3:16 | For God so loved the world, that he gave ... | For God so loved the world, that he gave ... |
+    ```
+    */
+    pub fn format_parallel(
+        &self,
+        translations: &[&BibleData],
+        formatter: &PassageFormatter,
+        layout: ParallelLayout,
+    ) -> String {
+        self.format_parallel_with(translations, &Versifications::default(), formatter, layout)
+    }
+
+    /// Like [`Self::format_parallel`], but verses are mapped to the versification of each
+    /// translation with `versifications`, so Malachi 4:5 in the KJV lines up with Malachi 3:23
+    /// in a Hebrew versification
+    pub fn format_parallel_with(
+        &self,
+        translations: &[&BibleData],
+        versifications: &Versifications,
+        formatter: &PassageFormatter,
+        layout: ParallelLayout,
+    ) -> String {
+        let verse_template = FormattingTemplate::from_template(&formatter.verse).unwrap();
+        let book = self.book.get_name();
+
+        let rows = self
+            .clone()
+            .into_iter()
+            .map(|bible_verse| {
+                let (chapter, verse) = (bible_verse.chapter_number(), bible_verse.verse_number());
+                let parallel = translations
+                    .iter()
+                    .map(|bible| {
+                        (
+                            bible.translation.abbreviation.as_str(),
+                            self.content_in(bible, versifications, ChapterVerse { chapter, verse }),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let cells = parallel
+                    .iter()
+                    .map(|(translation, content)| {
+                        let params = VerseFormatParameters {
+                            book,
+                            chapter,
+                            verse,
+                            content: content.as_deref().unwrap_or(""),
+                            translation,
+                            parallel: &parallel,
+                            headings: "",
+                            styled_content: content.as_deref().unwrap_or(""),
+                            footnotes: "",
+                        };
+                        verse_template.fill(&params).unwrap()
+                    })
+                    .collect();
+                ParallelRow {
+                    reference: format!("{}:{}", chapter, verse),
+                    cells,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let headers = translations
+            .iter()
+            .map(|bible| bible.translation.abbreviation.as_str())
+            .collect::<Vec<_>>();
+        match layout {
+            ParallelLayout::Columns { width } => columns(&headers, &rows, width),
+            ParallelLayout::MarkdownTable => markdown_table(&headers, &rows),
+            ParallelLayout::Html => html_table(&headers, &rows),
+        }
+    }
+}
+
+fn columns(headers: &[&str], rows: &[ParallelRow], width: usize) -> String {
+    let reference_width = rows
+        .iter()
+        .map(|row| row.reference.chars().count())
+        .max()
+        .unwrap_or(0);
+    let pad = |text: &str, width: usize| {
+        let padding = width.saturating_sub(text.chars().count());
+        format!("{}{}", text, " ".repeat(padding))
+    };
+    let line = |reference: &str, cells: &[&str]| {
+        let cells = cells
+            .iter()
+            .map(|cell| pad(cell, width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        format!("{}  {}", pad(reference, reference_width), cells)
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![line("", headers)];
+    for row in rows {
+        let wrapped = row
+            .cells
+            .iter()
+            .map(|cell| wrap(cell, width))
+            .collect::<Vec<_>>();
+        let height = wrapped.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        for index in 0..height {
+            let reference = if index == 0 {
+                row.reference.as_str()
+            } else {
+                ""
+            };
+            let cells = wrapped
+                .iter()
+                .map(|lines| lines.get(index).map_or("", String::as_str))
+                .collect::<Vec<_>>();
+            lines.push(line(reference, &cells));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Wraps on spaces, but a word longer than `width` gets a line of its own
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+fn markdown_table(headers: &[&str], rows: &[ParallelRow]) -> String {
+    let escape = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+    let mut lines = vec![
+        format!("| | {} |", headers.join(" | ")),
+        format!("|---|{}", "---|".repeat(headers.len())),
+    ];
+    for row in rows {
+        let cells = row
+            .cells
+            .iter()
+            .map(|cell| escape(cell))
+            .collect::<Vec<_>>();
+        lines.push(format!("| {} | {} |", row.reference, cells.join(" | ")));
+    }
+    lines.join("\n")
+}
+
+fn html_table(headers: &[&str], rows: &[ParallelRow]) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    let mut html = String::from("<table class=\"parallel\">\n<thead>\n<tr><th></th>");
+    for header in headers {
+        html.push_str(&format!("<th>{}</th>", escape(header)));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+    for row in rows {
+        html.push_str(&format!("<tr><th>{}</th>", row.reference));
+        for cell in row.cells.iter() {
+            html.push_str(&format!("<td>{}</td>", escape(cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{bible_api::BibleAPI, passage::formatting_template::PassageFormatterBuilder},
+        bible_data::formats::{
+            json::{test_bible_data, test_json_bible},
+            parse::ParseBibleData,
+        },
+    };
+
+    use super::*;
+
+    /// The KJV fixture as a translation where Genesis 1 is shouting (and 1:5 is missing)
+    fn loud_api() -> BibleAPI {
+        let mut api = BibleAPI::load(test_bible_data());
+        let mut loud = test_json_bible();
+        loud.translation.abbreviation = String::from("LOUD");
+        for verse in loud.bible[0].content[0].iter_mut() {
+            *verse = verse.as_ref().map(|content| content.to_uppercase());
+        }
+        loud.bible[0].content[0][4] = None;
+        api.add_translation(loud.as_bible_data().unwrap());
+        api
+    }
+
+    #[test]
+    fn parallel_verse_templates() {
+        let api = loud_api();
+        let translations = api.get_translations(&["loud"]).unwrap();
+        let formatter = PassageFormatterBuilder::new()
+            .verse("{verse}. {content} ({translation}) / {content:LOUD}")
+            .text("{segments}")
+            .build();
+        assert_eq!(
+            api.parse_reference("Gen 1:1")
+                .unwrap()
+                .format_with_translations(&formatter, &translations),
+            "1. In the beginning God created the heaven and the earth. (KJV) / IN THE BEGINNING GOD CREATED THE HEAVEN AND THE EARTH."
+        );
+        assert!(api.get_translations(&["KJV", "NET"]).is_none());
+    }
+
+    #[test]
+    fn parallel_layouts() {
+        let api = loud_api();
+        let translations = api.get_translations(&["KJV", "LOUD"]).unwrap();
+        let formatter = PassageFormatterBuilder::new().verse("{content}").build();
+        let passage = api.parse_reference("Genesis 1:3,5").unwrap();
+
+        assert_eq!(
+            passage.format_parallel(
+                &translations,
+                &formatter,
+                ParallelLayout::MarkdownTable
+            ),
+            "| | KJV | LOUD |
+|---|---|---|
+| 1:3 | And God said, Let there be light: and there was light. | AND GOD SAID, LET THERE BE LIGHT: AND THERE WAS LIGHT. |
+| 1:5 | And God called the light Day, and the darkness he called Night. And the evening and the morning were the first day. |  |"
+        );
+
+        assert_eq!(
+            passage.format_parallel(
+                &translations,
+                &formatter,
+                ParallelLayout::Columns { width: 24 }
+            ),
+            "     KJV                       LOUD
+1:3  And God said, Let there   AND GOD SAID, LET THERE
+     be light: and there was   BE LIGHT: AND THERE WAS
+     light.                    LIGHT.
+1:5  And God called the light
+     Day, and the darkness he
+     called Night. And the
+     evening and the morning
+     were the first day."
+        );
+
+        let formatter = PassageFormatterBuilder::new()
+            .verse("<b>{translation}</b> {content}")
+            .build();
+        let html = api.parse_reference("Genesis 1:1").unwrap().format_parallel(
+            &translations,
+            &formatter,
+            ParallelLayout::Html,
+        );
+        assert_eq!(
+            html,
+            "<table class=\"parallel\">
+<thead>
+<tr><th></th><th>KJV</th><th>LOUD</th></tr>
+</thead>
+<tbody>
+<tr><th>1:1</th><td>&lt;b&gt;KJV&lt;/b&gt; In the beginning God created the heaven and the earth.</td><td>&lt;b&gt;LOUD&lt;/b&gt; IN THE BEGINNING GOD CREATED THE HEAVEN AND THE EARTH.</td></tr>
+</tbody>
+</table>"
+        );
+    }
+}
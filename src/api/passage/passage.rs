@@ -1,13 +1,17 @@
-use itertools::Itertools;
+use std::borrow::Cow;
 
-use crate::bible_data::book::BibleBook;
+use crate::{
+    api::bible_api::Api,
+    bible_data::{bible_data::BibleData, book::BibleBook, versification::Versifications},
+};
 
 use super::{
     formatting_template::{
-        ChapterFormatParameters, FormattingTemplate, PassageFormatParameters, PassageFormatter,
-        SegmentFormatParameters, VerseFormatParameters,
+        styled_content, ChapterFormatParameters, FootnoteFormatParameters,
+        FootnotesFormatParameters, FormattingTemplate, HeadingFormatParameters,
+        PassageFormatParameters, PassageFormatter, SegmentFormatParameters, VerseFormatParameters,
     },
-    segments::PassageSegments,
+    segments::{ChapterVerse, PassageSegment, PassageSegments},
 };
 
 #[derive(Clone)]
@@ -17,6 +21,102 @@ pub struct Passage<'a> {
 }
 
 impl Passage<'_> {
+    /// - The same reference in another translation, so it can be formatted in that translation
+    /// - Returns [`None`] when the translation doesn't have this book
+    /// - The verse numbers stay the same (see [`Self::in_translation_with`])
+    pub fn in_translation<'b>(&self, bible: &'b BibleData) -> Option<Passage<'b>> {
+        self.in_translation_with(bible, &Versifications::default())
+    }
+
+    /// - The content of a verse of this passage in another translation
+    /// - When the verse is split up in that versification (like 3 John 1:14), the parts are joined
+    ///   with a space
+    pub fn content_in<'b>(
+        &self,
+        bible: &'b BibleData,
+        versifications: &Versifications,
+        verse: ChapterVerse,
+    ) -> Option<Cow<'b, str>> {
+        let book = self.book.book_number();
+        let contents = versifications
+            .map_verse(
+                book,
+                verse,
+                &self.book.get_translation().versification,
+                &bible.translation.versification,
+            )
+            .into_iter()
+            .filter_map(|verse| {
+                bible
+                    .get_verse(book, verse.chapter, verse.verse)?
+                    .get_content()
+            })
+            .collect::<Vec<_>>();
+        match contents.as_slice() {
+            [] => None,
+            [content] => Some(Cow::Borrowed(content)),
+            _ => Some(Cow::Owned(contents.join(" "))),
+        }
+    }
+
+    /**
+    Like [`Self::in_translation`], but the verses are mapped to the versification of `bible`
+
+    - Ex: `Malachi 4:5-6` in the KJV is `Malachi 3:23-24` in a Hebrew versification
+    - Each segment starts at the first verse its start maps to, and ends at the last verse its
+      end maps to
+    */
+    pub fn in_translation_with<'b>(
+        &self,
+        bible: &'b BibleData,
+        versifications: &Versifications,
+    ) -> Option<Passage<'b>> {
+        let book = bible.get_book(self.book.book_number())?;
+        let (from, to) = (
+            &self.book.get_translation().versification,
+            &bible.translation.versification,
+        );
+        let map = |verse: ChapterVerse| {
+            versifications.map_verse(self.book.book_number(), verse, from, to)
+        };
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| {
+                let start = ChapterVerse {
+                    chapter: segment.get_starting_chapter(),
+                    verse: segment.get_starting_verse(),
+                };
+                let end = ChapterVerse {
+                    chapter: segment.get_ending_chapter(),
+                    verse: segment.get_ending_verse(),
+                };
+                // verses that don't exist in `to` keep their numbers
+                let mapped_start = map(start).first().copied().unwrap_or(start);
+                let mapped_end = map(end).last().copied().unwrap_or(end);
+                if (mapped_start, mapped_end) == (start, end) {
+                    segment.resolve_in(book)
+                } else {
+                    PassageSegment::between(mapped_start, mapped_end)
+                }
+            })
+            .collect::<Vec<PassageSegment>>();
+        Some(Passage {
+            book,
+            segments: PassageSegments(segments),
+        })
+    }
+
+    /// Ex: `Ephesians 1:1-4,6`, `Psalms 23` or `Jude` (for the whole book)
+    pub fn label(&self) -> String {
+        let segments = self.segments.label();
+        if segments.is_empty() {
+            self.book.get_name().to_string()
+        } else {
+            format!("{} {}", self.book.get_name(), segments)
+        }
+    }
+
     // pub fn format2(&self, formatter: &PassageFormatter) -> String {
     //     let segment_template = FormattingTemplate::from_template(&formatter.segment).unwrap();
     //     let chapter_template = FormattingTemplate::from_template(&formatter.chapter).unwrap();
@@ -98,12 +198,49 @@ impl Passage<'_> {
     ```
     */
     pub fn format(&self, formatter: &PassageFormatter) -> String {
+        self.format_with_translations(formatter, &[])
+    }
+
+    /**
+    Like [`Self::format`], but the verse template can also use the content of each verse in
+    `translations`
+
+    - Ex: `[{verse}] {content:KJV} / {content:ESV}`
+    - `{translation}` is the abbreviation of this passage's translation
+    - With `collect_footnotes`, the footnotes are numbered through the passage, marked in
+      `{styled_content}`, and put after it (instead of in `{footnotes}`)
+    */
+    pub fn format_with_translations(
+        &self,
+        formatter: &PassageFormatter,
+        translations: &[&BibleData],
+    ) -> String {
+        self.format_with_versifications(formatter, translations, &Versifications::default())
+    }
+
+    /// Like [`Self::format_with_translations`], but verses are mapped to the versification of
+    /// each translation with `versifications`
+    pub fn format_with_versifications(
+        &self,
+        formatter: &PassageFormatter,
+        translations: &[&BibleData],
+        versifications: &Versifications,
+    ) -> String {
         // let book = self.book;
         let book = self.book.get_name();
+        let translation = self.book.get_translation().abbreviation.as_str();
         let segment_template = FormattingTemplate::from_template(&formatter.segment).unwrap();
         let chapter_template = FormattingTemplate::from_template(&formatter.chapter).unwrap();
         let verse_template = FormattingTemplate::from_template(&formatter.verse).unwrap();
         let passage_template = FormattingTemplate::from_template(&formatter.text).unwrap();
+        let heading_template = FormattingTemplate::from_template(&formatter.heading).unwrap();
+        let style_template = FormattingTemplate::from_template(&formatter.style).unwrap();
+        let footnote_template = FormattingTemplate::from_template(&formatter.footnote).unwrap();
+        let marker_template =
+            FormattingTemplate::from_template(&formatter.footnote_marker).unwrap();
+        let footnotes_template = FormattingTemplate::from_template(&formatter.footnotes).unwrap();
+        // the filled footnote template of each footnote so far, when they're collected
+        let mut collected = Vec::new();
 
         let segment_range_content = self
             .segments
@@ -119,22 +256,97 @@ impl Passage<'_> {
                         let end_verse = if chapter == seg.get_ending_chapter() {
                             seg.get_ending_verse()
                         } else {
-                            self.book.get_chapter(chapter).unwrap().verse_count()
+                            self.book
+                                .get_chapter(chapter)
+                                .map_or(0, |chapter| chapter.verse_count())
                         };
-                        let verse_range_content = (start_verse..=end_verse)
-                            .filter_map(|verse| {
-                                let content =
-                                    &self.book.get_verse(chapter, verse)?.get_content()?;
-                                let params = VerseFormatParameters {
-                                    book,
-                                    chapter,
-                                    verse,
+                        let mut verse_range_content = String::new();
+                        for verse in start_verse..=end_verse {
+                            let Some(bible_verse) = self.book.get_verse(chapter, verse) else {
+                                continue;
+                            };
+                            let Some(content) = bible_verse.get_content() else {
+                                continue;
+                            };
+                            let data = bible_verse.get_data();
+                            let parallel = translations
+                                .iter()
+                                .map(|bible| {
+                                    (
+                                        bible.translation.abbreviation.as_str(),
+                                        self.content_in(
+                                            bible,
+                                            versifications,
+                                            ChapterVerse { chapter, verse },
+                                        ),
+                                    )
+                                })
+                                .collect::<Vec<_>>();
+                            let headings = data
+                                .headings()
+                                .iter()
+                                .map(|heading| {
+                                    let params = HeadingFormatParameters {
+                                        book,
+                                        chapter,
+                                        verse,
+                                        heading,
+                                    };
+                                    heading_template.fill(&params).unwrap()
+                                })
+                                .collect::<String>();
+                            let (footnotes, markers): (Vec<_>, Vec<_>) = data
+                                .footnotes()
+                                .iter()
+                                .enumerate()
+                                .map(|(index, note)| {
+                                    let number = if formatter.collect_footnotes {
+                                        collected.len() + index + 1
+                                    } else {
+                                        index + 1
+                                    };
+                                    let params = FootnoteFormatParameters {
+                                        book,
+                                        chapter,
+                                        verse,
+                                        number,
+                                        origin: note.origin.as_deref().unwrap_or_default(),
+                                        text: &note.text,
+                                    };
+                                    (
+                                        footnote_template.fill(&params).unwrap(),
+                                        marker_template.fill(&params).unwrap(),
+                                    )
+                                })
+                                .unzip();
+                            let (footnotes, markers) = if formatter.collect_footnotes {
+                                collected.extend(footnotes);
+                                (String::new(), markers)
+                            } else {
+                                (footnotes.join(&formatter.join_footnotes), vec![])
+                            };
+                            let params = VerseFormatParameters {
+                                book,
+                                chapter,
+                                verse,
+                                content,
+                                translation,
+                                parallel: &parallel,
+                                headings: &headings,
+                                styled_content: &styled_content(
                                     content,
-                                };
-                                Some(verse_template.fill(&params).unwrap())
-                            })
-                            .collect::<Vec<_>>()
-                            .join(&formatter.join_verses);
+                                    data,
+                                    formatter,
+                                    &style_template,
+                                    &markers,
+                                ),
+                                footnotes: &footnotes,
+                            };
+                            if !verse_range_content.is_empty() {
+                                verse_range_content.push_str(&formatter.verse_separator(data));
+                            }
+                            verse_range_content.push_str(&verse_template.fill(&params).unwrap());
+                        }
 
                         let params = ChapterFormatParameters {
                             book,
@@ -164,6 +376,113 @@ impl Passage<'_> {
             segments: &segment_range_content,
             label: &self.segments.label(),
         };
-        passage_template.fill(&params).unwrap()
+        let mut text = passage_template.fill(&params).unwrap();
+        if !collected.is_empty() {
+            let params = FootnotesFormatParameters {
+                footnotes: &collected.join(&formatter.join_footnotes),
+            };
+            text.push_str(&footnotes_template.fill(&params).unwrap());
+        }
+        text
+    }
+}
+
+impl<'a> Api<'a, Passage<'_>> {
+    /// The passages that this passage references, most relevant first (see
+    /// [`ApiData::get_cross_references`](crate::api::bible_api::ApiData::get_cross_references))
+    pub fn get_cross_references(&self, min_votes: Option<i64>) -> Vec<Passage<'a>> {
+        self.api.get_cross_references(self, min_votes)
+    }
+
+    /// The verses that reference any verse of this passage, most relevant first
+    pub fn get_incoming_cross_references(&self, min_votes: Option<i64>) -> Vec<Passage<'a>> {
+        self.api.get_incoming_cross_references(self, min_votes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{bible_api::BibleAPI, passage::formatting_template::PassageFormatterBuilder},
+        bible_data::{
+            bible_data::TextStyle,
+            formats::{json::test_json_bible, parse::ParseBibleData, usfm::USFMBible},
+        },
+    };
+
+    #[test]
+    fn format_paragraphs() {
+        let psalm = r#"\id PSA
+\h Psalms
+\c 23
+\s1 The LORD Is My Shepherd
+\q1
+\v 1 The \nd Lord\nd* is my shepherd;
+\q2 I shall not want.
+\q1
+\v 2 He makes me lie down in green pastures.
+\q2 He leads me beside still waters.
+"#;
+        let bible = USFMBible::parse(test_json_bible().translation, &[psalm])
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        let api = BibleAPI::load(bible);
+        let passage = api.parse_reference("Psalms 23:1-2").unwrap();
+        let verse = passage.book.get_verse(23, 1).unwrap();
+        assert_eq!(verse.get_headings(), ["The LORD Is My Shepherd"]);
+        assert!(verse.starts_paragraph());
+        assert_eq!(verse.get_styles()[0].style, TextStyle::DivineName);
+
+        let formatter = PassageFormatterBuilder::paragraphs()
+            .style("*{text}*")
+            .build();
+        assert_eq!(
+            passage.format(&formatter),
+            "### Psalms 23:1-2\n\n#### The LORD Is My Shepherd\n\n[1] The *Lord* is my shepherd;\n  I shall not want.\n[2] He makes me lie down in green pastures.\n  He leads me beside still waters."
+        );
+        // only the verses and their content are in the default format
+        assert_eq!(
+            passage.format(&PassageFormatterBuilder::new().build()),
+            "### Psalms 23:1-2\n\n[23:1] The Lord is my shepherd; I shall not want.\n[23:2] He makes me lie down in green pastures. He leads me beside still waters."
+        );
+    }
+
+    #[test]
+    fn format_footnotes() {
+        let john = r#"\id JHN
+\h John
+\c 1
+\p
+\v 1 In the beginning was the Word,\f + \fr 1:1 \ft Or the Logos\f* and the Word was with God.
+\v 2 He was in the beginning with God.\f + \ft Or the Father\f*
+"#;
+        let bible = USFMBible::parse(test_json_bible().translation, &[john])
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        let api = BibleAPI::load(bible);
+        let passage = api.parse_reference("John 1:1-2").unwrap();
+        let verse = passage.book.get_verse(1, 1).unwrap();
+        assert_eq!(verse.get_footnotes()[0].origin.as_deref(), Some("1:1"));
+
+        let formatter = PassageFormatterBuilder::new()
+            .verse("[{verse}] {content} {footnotes}")
+            .footnote("({origin}) {text}")
+            .build();
+        assert_eq!(
+            passage.format(&formatter),
+            "### John 1:1-2\n\n[1] In the beginning was the Word, and the Word was with God. (1:1) Or the Logos\n[2] He was in the beginning with God. () Or the Father"
+        );
+
+        let formatter = PassageFormatterBuilder::new()
+            .verse("[{verse}] {styled_content}")
+            .join_verses(" ")
+            .collect_footnotes()
+            .build();
+        assert_eq!(
+            passage.format(&formatter),
+            "### John 1:1-2\n\n[1] In the beginning was the Word,[1] and the Word was with God. [2] He was in the beginning with God.[2]\n\n[1] Or the Logos\n[2] Or the Father"
+        );
     }
 }
@@ -1,4 +1,4 @@
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -9,9 +9,11 @@ use crate::{
     related_media::overlapping_ranges::RangePair,
 };
 
+use super::errors::ReferenceError;
+
 /// - This is a single chapter/verse reference
 /// - Ex: `1:2` in `John 1:2`
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ChapterVerse {
     pub chapter: usize,
     pub verse: usize,
@@ -33,8 +35,29 @@ pub struct ChapterRange {
     pub end: ChapterVerse,
 }
 
+/// - This is a range of whole chapters
+/// - Ex: `23` in `Psalm 23` or `8-9` in `Romans 8-9`
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct FullChapterRange {
+    pub start_chapter: usize,
+    pub end_chapter: usize,
+    /// The last verse of `end_chapter` (resolved from the reference array when parsing), so this
+    /// knows where it ends without the Bible data
+    pub end_verse: usize,
+}
+
+/// - This is every chapter of a book
+/// - Ex: `Jude`
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct FullBook {
+    /// The last chapter of the book (resolved from the reference array when parsing)
+    pub end_chapter: usize,
+    /// The last verse of `end_chapter` (resolved from the reference array when parsing)
+    pub end_verse: usize,
+}
+
 /// Remember, these correspond to
-/// ```
+/// ```text
 ///                `Ephesians 1:1-4,5-7,2:2-3:4,6`
 ///                          |     |   |       | |
 ///                ----------+     |   |       | |
@@ -61,6 +84,12 @@ pub enum PassageSegment {
     /// - This is a range of verse references across a multiple chapters
     /// - Ex: `John 1:2-3:4`
     ChapterRange(ChapterRange),
+    /// - This is a range of whole chapters
+    /// - Ex: `Psalm 23` or `Romans 8-9`
+    FullChapterRange(FullChapterRange),
+    /// - This is every chapter of a book
+    /// - Ex: `Jude`
+    FullBook(FullBook),
 }
 
 impl PassageSegment {
@@ -95,9 +124,80 @@ impl PassageSegment {
             },
         })
     }
+
+    /// The simplest segment from `start` to `end` (inclusive)
+    pub fn between(start: ChapterVerse, end: ChapterVerse) -> Self {
+        match (start, end) {
+            _ if start == end => Self::chapter_verse(start.chapter, start.verse),
+            _ if start.chapter == end.chapter => {
+                Self::chapter_verse_range(start.chapter, start.verse, end.verse)
+            }
+            _ => Self::chapter_range(start.chapter, start.verse, end.chapter, end.verse),
+        }
+    }
 }
 
 impl PassageSegment {
+    pub fn full_chapter_range(start_chapter: usize, end_chapter: usize, end_verse: usize) -> Self {
+        Self::FullChapterRange(FullChapterRange {
+            start_chapter,
+            end_chapter,
+            end_verse,
+        })
+    }
+
+    /// - The verse bounds are resolved from the book's reference array
+    /// - Returns [`None`] when either chapter isn't in the book
+    fn full_chapters_of(book: BibleBook, start_chapter: usize, end_chapter: usize) -> Option<Self> {
+        book.verse_count(start_chapter)?;
+        let end_verse = book.verse_count(end_chapter)?;
+        Some(Self::full_chapter_range(
+            start_chapter,
+            end_chapter,
+            end_verse,
+        ))
+    }
+
+    /// The verse bounds are resolved from the book
+    pub fn full_book(book: BibleBook) -> Self {
+        let end_chapter = book.chapter_count();
+        let end_verse = book
+            .get_chapter(end_chapter)
+            .map_or(0, |chapter| chapter.verse_count());
+        Self::FullBook(FullBook {
+            end_chapter,
+            end_verse,
+        })
+    }
+
+    /// - Whole chapters and books end at the last verse of `book`, which may be a different
+    ///   translation than they were parsed with
+    /// - Every other segment stays the same
+    pub fn resolve_in(self, book: BibleBook) -> Self {
+        match self {
+            // chapters that aren't in `book` keep their verse bounds
+            PassageSegment::FullChapterRange(chapters) => {
+                Self::full_chapters_of(book, chapters.start_chapter, chapters.end_chapter)
+                    .unwrap_or(self)
+            }
+            PassageSegment::FullBook(_) => Self::full_book(book),
+            segment => segment,
+        }
+    }
+
+    /// Whether this covers whole chapters (so it has no verse numbers in its label)
+    pub fn is_full_chapters(&self) -> bool {
+        matches!(
+            self,
+            PassageSegment::FullChapterRange(_) | PassageSegment::FullBook(_)
+        )
+    }
+}
+
+impl PassageSegment {
+    /// - Whole chapters are labeled like `8-9` or `23`
+    /// - Whole books are labeled by all their chapters, like `1-50` (but see
+    ///   [`PassageSegments::label`])
     pub fn label(&self) -> String {
         match self {
             PassageSegment::ChapterVerse(chapter_verse) => {
@@ -118,6 +218,14 @@ impl PassageSegment {
                     book_range.end.verse
                 )
             }
+            PassageSegment::FullChapterRange(_) | PassageSegment::FullBook(_) => {
+                let (start, end) = (self.get_starting_chapter(), self.get_ending_chapter());
+                if start == end {
+                    format!("{}", start)
+                } else {
+                    format!("{}-{}", start, end)
+                }
+            }
         }
     }
 }
@@ -130,12 +238,29 @@ impl PassageSegments {
         Self(vec![])
     }
 
-    pub fn try_parse(segment_input: &str) -> Option<Self> {
+    /// - Parses the segments right after a book name
+    /// - Nothing after the book name means the whole book
+    pub fn try_parse(book: BibleBook, segment_input: &str) -> Option<Self> {
+        if segment_input.trim().is_empty() {
+            return Some(Self(vec![PassageSegment::full_book(book)]));
+        }
+        Self::try_parse_prefix(book, segment_input).map(|(segments, _)| segments)
+    }
+
+    /// - Parses the segments at the start of `segment_input` (the text right after a book name)
+    /// - Also returns how many bytes of `segment_input` were consumed, so callers can locate the
+    ///   end of the reference
+    /// - This never matches a whole book, because book names alone are too common in prose
+    pub fn try_parse_prefix(book: BibleBook, segment_input: &str) -> Option<(Self, usize)> {
         let segment_match = POST_BOOK_VALID_REFERENCE_SEGMENT_CHARACTERS
             .find_iter(segment_input)
             .next()?;
-        let segments = parse_reference_segments(segment_match.as_str());
-        Some(segments)
+        let segments = parse_reference_segments(book, segment_match.as_str())
+            .ok()?
+            .into_iter()
+            .map(|(segment, _)| segment)
+            .collect();
+        Some((Self(segments), segment_match.end()))
     }
 
     /// nobody ought to call this unless their segment_input is validated by the regex
@@ -146,6 +271,7 @@ impl PassageSegments {
     pub fn label(&self) -> String {
         let mut previous_chapter: Option<usize> = None;
         let mut label_segments: Vec<String> = vec![];
+        let mut after_full_chapters = false;
         // let mut label_str = String::new();
         for seg in self.0.iter() {
             let next_seg = match seg {
@@ -187,6 +313,9 @@ impl PassageSegments {
                         )
                     }
                 }
+                // the book name alone is the label
+                PassageSegment::FullBook(_) if self.0.len() == 1 => String::new(),
+                PassageSegment::FullChapterRange(_) | PassageSegment::FullBook(_) => seg.label(),
             };
             let ending_chapter = seg.get_ending_chapter();
             // // if new chapter, add '; '
@@ -197,6 +326,16 @@ impl PassageSegments {
             // else {
             //     label_segments.push(String::from(","));
             // }
+            if seg.is_full_chapters() || after_full_chapters {
+                // `8` in `Romans 8; 9` would otherwise read as a verse
+                if !label_segments.is_empty() {
+                    label_segments.push(String::from("; "));
+                }
+                label_segments.push(next_seg);
+                after_full_chapters = seg.is_full_chapters();
+                previous_chapter = (!after_full_chapters).then_some(ending_chapter);
+                continue;
+            }
             if let Some(prev) = previous_chapter {
                 match prev == ending_chapter {
                     // if same chapter, add ','
@@ -232,6 +371,7 @@ impl PassageSegment {
             PassageSegment::ChapterVerse(chapter_verse) => chapter_verse.verse,
             PassageSegment::ChapterVerseRange(chapter_range) => chapter_range.verses.start,
             PassageSegment::ChapterRange(book_range) => book_range.start.verse,
+            PassageSegment::FullChapterRange(_) | PassageSegment::FullBook(_) => 1,
         }
     }
 
@@ -240,6 +380,8 @@ impl PassageSegment {
             PassageSegment::ChapterVerse(chapter_verse) => chapter_verse.chapter,
             PassageSegment::ChapterVerseRange(chapter_range) => chapter_range.chapter,
             PassageSegment::ChapterRange(book_range) => book_range.start.chapter,
+            PassageSegment::FullChapterRange(chapters) => chapters.start_chapter,
+            PassageSegment::FullBook(_) => 1,
         }
     }
 
@@ -248,6 +390,8 @@ impl PassageSegment {
             PassageSegment::ChapterVerse(chapter_verse) => chapter_verse.verse,
             PassageSegment::ChapterVerseRange(chapter_range) => chapter_range.verses.end,
             PassageSegment::ChapterRange(book_range) => book_range.end.verse,
+            PassageSegment::FullChapterRange(chapters) => chapters.end_verse,
+            PassageSegment::FullBook(book) => book.end_verse,
         }
     }
 
@@ -256,137 +400,179 @@ impl PassageSegment {
             PassageSegment::ChapterVerse(chapter_verse) => chapter_verse.chapter,
             PassageSegment::ChapterVerseRange(chapter_range) => chapter_range.chapter,
             PassageSegment::ChapterRange(book_range) => book_range.end.chapter,
+            PassageSegment::FullChapterRange(chapters) => chapters.end_chapter,
+            PassageSegment::FullBook(book) => book.end_chapter,
         }
     }
 }
 
+// `\d` would also match non-ASCII digits, which `usize::from_str` can't parse
 static POST_BOOK_VALID_REFERENCE_SEGMENT_CHARACTERS: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^ *\d+:\d+( *[,:;\-–] *\d+)*").unwrap());
+    Lazy::new(|| Regex::new(r"^ *[0-9]+( *[,:;\-–] *[0-9]+)*").unwrap());
 
-static NON_SEGMENT_CHARACTERS: Lazy<Regex> = Lazy::new(|| Regex::new(r"[^\d,:;-]+").unwrap());
-
-static TRAILING_NON_DIGITS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\D+$)").unwrap());
+#[derive(Copy, Clone, Debug)]
+enum SegmentToken {
+    Number(usize),
+    /// `:`
+    ChapterSeparator,
+    /// `-` or `–`
+    RangeSeparator,
+    /// `,`
+    ListSeparator,
+    /// `;`, which starts a new chapter (so `John 3:16; 4` is John 4, not John 3:4)
+    ChapterListSeparator,
+}
 
-static SEGMENT_SPLITTERS: Lazy<Regex> = Lazy::new(|| Regex::new("(,|;)").unwrap());
+/// Splits `1:1-4, 5` into numbers and separators (along with their byte ranges)
+fn tokenize_reference_segments(
+    segment_input: &str,
+) -> Result<Vec<(SegmentToken, Range<usize>)>, ReferenceError> {
+    let mut tokens = Vec::new();
+    let mut chars = segment_input.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        let token = match ch {
+            '0'..='9' => {
+                let mut end = start + 1;
+                while let Some((index, '0'..='9')) = chars.peek().copied() {
+                    chars.next();
+                    end = index + 1;
+                }
+                // this only fails when the number is too big
+                let number = segment_input[start..end]
+                    .parse()
+                    .map_err(|_| ReferenceError::MalformedSegment { span: start..end })?;
+                tokens.push((SegmentToken::Number(number), start..end));
+                continue;
+            }
+            ':' => SegmentToken::ChapterSeparator,
+            '-' | '–' => SegmentToken::RangeSeparator,
+            ',' => SegmentToken::ListSeparator,
+            ';' => SegmentToken::ChapterListSeparator,
+            ch if ch.is_whitespace() => continue,
+            _ => {
+                return Err(ReferenceError::MalformedSegment {
+                    span: start..start + ch.len_utf8(),
+                })
+            }
+        };
+        tokens.push((token, start..start + ch.len_utf8()));
+    }
+    Ok(tokens)
+}
 
-/// - This function is meant to parse the `1:1-4,5-7,2:2-3:4,6` in `Ephesians 1:1-4,5-7,2:2-3:4,6`
-/// - Don't pass it anything else please :)
 /**
-Passing `1` will result in
-```no_run
-[src/main.rs:27:5] parse_reference_segments("1") = [
-    ChapterVerse(
-        ChapterVerse {
-            chapter: 1,
-            verse: 1,
-        },
-    ),
-]
-```
-Passing `1:` will result in
-```no_run
-[src/main.rs:28:5] parse_reference_segments("1:") = [
-    ChapterVerse(
-        ChapterVerse {
-            chapter: 1,
-            verse: 1,
-        },
-    ),
+- This function is meant to parse the `1:1-4,5-7,2:2-3:4,6` in `Ephesians 1:1-4,5-7,2:2-3:4,6`
+- Each segment is returned with the byte range of `segment_input` that it came from
+- A segment with only verses (like the `5-7` and `6` above) belongs to the latest chapter
+- Numbers without a chapter before them are whole chapters, like `23` in `Psalm 23` or `1-3; 5`
+  in `Genesis 1-3; 5` (their last verse is resolved from `book`, and they must be in it)
+- A `;` starts a new chapter, so numbers after it are whole chapters too (Ex: the `4` in
+  `John 3:16; 4`)
+- Nothing at all is the whole book
+
+Passing `1:2,4` will result in
+```text
+[
+    (ChapterVerse(ChapterVerse { chapter: 1, verse: 2 }), 0..3),
+    (ChapterVerse(ChapterVerse { chapter: 1, verse: 4 }), 4..5),
 ]
 ```
 */
-fn parse_reference_segments(segment_input: &str) -> PassageSegments {
-    // swap weird hyphens with normal dash
-    let input = &segment_input.replace("–", "-");
-    // input now only contains the following characters: [\d,:;-]
-    let input = NON_SEGMENT_CHARACTERS.replace_all(&input, "").to_string();
+pub(crate) fn parse_reference_segments(
+    book: BibleBook,
+    segment_input: &str,
+) -> Result<Vec<(PassageSegment, Range<usize>)>, ReferenceError> {
+    use SegmentToken::*;
 
-    // removing trailing non-digits (leading shouldn't exist)
-    let input = TRAILING_NON_DIGITS.replace_all(&input, "").to_string();
+    let tokens = tokenize_reference_segments(segment_input)?;
+    if tokens.is_empty() {
+        return Ok(vec![(PassageSegment::full_book(book), 0..0)]);
+    }
 
-    // split at , or ; (because there is no uniform standard)
-    // now I only have ranges (or a single verse)
-    let ranges: Vec<&str> = SEGMENT_SPLITTERS.split(input.as_str()).collect();
-    // dbg!(&ranges);
+    // `3:16,`
+    if let Some((ListSeparator | ChapterListSeparator, span)) = tokens.last() {
+        return Err(ReferenceError::MalformedSegment { span: span.clone() });
+    }
 
     // ALWAYS UPDATE THE CHAPTER SO I CAN USE IT WHEN ONLY VERSES ARE PROVIDED
-    let mut chapter = 1;
-    let mut segments: Vec<PassageSegment> = Vec::new();
-    for range in ranges {
-        // if it is a range
-        if let Some((left, right)) = range.split_once("-") {
-            match (left.split_once(":"), right.split_once(":")) {
-                // `ch1:v1 - ch2:v2`
-                (Some((ch1, v1)), Some((ch2, v2))) => {
-                    chapter = ch2.parse().unwrap();
-                    segments.push(PassageSegment::ChapterRange(ChapterRange {
-                        start: ChapterVerse {
-                            chapter: ch1.parse().unwrap(),
-                            verse: v1.parse().unwrap(),
-                        },
-                        end: ChapterVerse {
-                            chapter,
-                            verse: v2.parse().unwrap(),
-                        },
-                    }));
-                }
-                // `ch1:v1 - v2`
-                (Some((ch1, v1)), None) => {
-                    chapter = ch1.parse().unwrap();
-                    segments.push(PassageSegment::ChapterVerseRange(ChapterVerseRange {
-                        chapter,
-                        verses: RangePair {
-                            start: v1.parse().unwrap(),
-                            end: right.parse().unwrap(),
-                        },
-                    }));
-                }
-                // `v1 - ch2:v2`
-                (None, Some((ch2, v2))) => {
-                    let start_chapter = chapter;
-                    chapter = ch2.parse().unwrap();
-                    segments.push(PassageSegment::ChapterRange(ChapterRange {
-                        start: ChapterVerse {
-                            chapter,
-                            verse: left.parse().unwrap(),
-                        },
-                        end: ChapterVerse {
-                            chapter,
-                            verse: v2.parse().unwrap(),
-                        },
-                    }));
-                }
-                // `v1 - v2`
-                (None, None) => {
-                    segments.push(PassageSegment::ChapterVerseRange(ChapterVerseRange {
-                        chapter,
-                        verses: RangePair {
-                            start: left.parse().unwrap(),
-                            end: right.parse().unwrap(),
-                        },
-                    }))
-                }
-            };
-        }
-        // else it is not a range, either `ch:v` or `v`
-        else {
-            // handle `ch:v`
-            if let Some((ch, v)) = range.split_once(":") {
-                chapter = ch.parse().unwrap();
-                segments.push(PassageSegment::ChapterVerse(ChapterVerse {
-                    chapter,
-                    verse: v.parse().unwrap(),
-                }))
+    // (it is `None` when lone numbers are chapters)
+    let mut chapter: Option<usize> = None;
+    let mut segments = Vec::new();
+    for group in
+        tokens.split_inclusive(|(token, _)| matches!(token, ListSeparator | ChapterListSeparator))
+    {
+        let (group, separator, new_chapter) = match group.split_last() {
+            Some(((ListSeparator, separator), group)) => (group, Some(separator), false),
+            Some(((ChapterListSeparator, separator), group)) => (group, Some(separator), true),
+            _ => (group, None, false),
+        };
+        let span = match (group.first(), group.last(), separator) {
+            (Some((_, first)), Some((_, last)), _) => first.start..last.end,
+            // `3:16,,17` (this group is only the second separator)
+            (_, _, Some(separator)) => {
+                return Err(ReferenceError::MalformedSegment {
+                    span: separator.clone(),
+                })
             }
-            // handle `v`
-            else {
-                let v = range.parse().unwrap();
-                segments.push(PassageSegment::ChapterVerse(ChapterVerse {
+            (_, _, None) => unreachable!("split groups are never empty"),
+        };
+        let group = group.iter().map(|(token, _)| *token).collect::<Vec<_>>();
+
+        // whole chapters that aren't in the book (Ex: `Jude 2`)
+        let full_chapters = |start_chapter: usize, end_chapter: usize| {
+            PassageSegment::full_chapters_of(book, start_chapter, end_chapter).ok_or_else(|| {
+                let chapter = if book.verse_count(start_chapter).is_none() {
+                    start_chapter
+                } else {
+                    end_chapter
+                };
+                ReferenceError::ChapterOutOfRange {
+                    book: book.get_name().to_string(),
                     chapter,
-                    verse: v,
-                }))
+                    chapter_count: book.chapter_count(),
+                    span: span.clone(),
+                }
+            })
+        };
+        let segment = match (group.as_slice(), chapter) {
+            // `ch`
+            ([Number(ch)], None) => full_chapters(*ch, *ch)?,
+            // `ch1 - ch2`
+            ([Number(ch1), RangeSeparator, Number(ch2)], None) => full_chapters(*ch1, *ch2)?,
+            // `ch1 - ch2:v2`
+            ([Number(ch1), RangeSeparator, Number(ch2), ChapterSeparator, Number(v2)], None) => {
+                PassageSegment::chapter_range(*ch1, 1, *ch2, *v2)
             }
-        }
+            // `v`
+            ([Number(v)], Some(ch)) => PassageSegment::chapter_verse(ch, *v),
+            // `ch:v`
+            ([Number(ch), ChapterSeparator, Number(v)], _) => {
+                PassageSegment::chapter_verse(*ch, *v)
+            }
+            // `ch:v1 - v2`
+            ([Number(ch), ChapterSeparator, Number(v1), RangeSeparator, Number(v2)], _) => {
+                PassageSegment::chapter_verse_range(*ch, *v1, *v2)
+            }
+            // `ch1:v1 - ch2:v2`
+            (
+                [Number(ch1), ChapterSeparator, Number(v1), RangeSeparator, Number(ch2), ChapterSeparator, Number(v2)],
+                _,
+            ) => PassageSegment::chapter_range(*ch1, *v1, *ch2, *v2),
+            // `v1 - v2`
+            ([Number(v1), RangeSeparator, Number(v2)], Some(ch)) => {
+                PassageSegment::chapter_verse_range(ch, *v1, *v2)
+            }
+            // `v1 - ch2:v2`
+            (
+                [Number(v1), RangeSeparator, Number(ch2), ChapterSeparator, Number(v2)],
+                Some(ch1),
+            ) => PassageSegment::chapter_range(ch1, *v1, *ch2, *v2),
+            _ => return Err(ReferenceError::MalformedSegment { span }),
+        };
+        // numbers after a `;` are chapters again
+        chapter =
+            (!segment.is_full_chapters() && !new_chapter).then(|| segment.get_ending_chapter());
+        segments.push((segment, span));
     }
-    PassageSegments(segments)
+    Ok(segments)
 }
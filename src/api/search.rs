@@ -0,0 +1,694 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
+
+use crate::bible_data::{bible_data::BibleData, verse::BibleVerse};
+
+use super::{bible_api::BibleAPI, passage::passage::Passage};
+
+/// Book ids where Matthew = 40
+const NEW_TESTAMENT_START: usize = 40;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Testament {
+    Old,
+    New,
+}
+
+impl Testament {
+    pub fn of_book(book: usize) -> Self {
+        if book < NEW_TESTAMENT_START {
+            Testament::Old
+        } else {
+            Testament::New
+        }
+    }
+}
+
+/// Where [`BibleAPI::search_in`] looks for matches
+#[derive(Clone)]
+pub enum SearchScope<'a> {
+    Bible,
+    Testament(Testament),
+    /// book id where Genesis = 1
+    Book(usize),
+    Passage(Passage<'a>),
+}
+
+impl SearchScope<'_> {
+    fn contains(&self, verse: &VerseKey) -> bool {
+        match self {
+            SearchScope::Bible => true,
+            SearchScope::Testament(testament) => Testament::of_book(verse.book) == *testament,
+            SearchScope::Book(book) => verse.book == *book,
+            SearchScope::Passage(passage) => {
+                passage.book.book_number() == verse.book
+                    && passage.segments.iter().any(|segment| {
+                        let position = (verse.chapter, verse.verse);
+                        (segment.get_starting_chapter(), segment.get_starting_verse()) <= position
+                            && position
+                                <= (segment.get_ending_chapter(), segment.get_ending_verse())
+                    })
+            }
+        }
+    }
+}
+
+/// A verse that matched a search
+pub struct SearchHit<'a> {
+    pub verse: BibleVerse<'a>,
+    /// - Higher is more relevant
+    /// - Verses with rarer words, and more of them, score higher
+    pub score: f64,
+    /// Byte ranges of the matched words (or phrases) in the verse content
+    pub highlights: Vec<Range<usize>>,
+}
+
+/// Splits text into lowercase words, along with their byte ranges
+fn tokenize(text: &str) -> Vec<(String, Range<usize>)> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (index, ch) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, ch.is_alphanumeric()) {
+            (None, true) => start = Some(index),
+            (Some(word_start), false) => {
+                tokens.push((text[word_start..index].to_lowercase(), word_start..index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct VerseKey {
+    book: usize,
+    chapter: usize,
+    verse: usize,
+}
+
+/// The verses a word is in, and the word positions in each (in verse order)
+type Postings = Vec<(u32, Vec<u32>)>;
+
+/**
+An inverted index of every word in a [`BibleData`]
+
+- Each verse is a document, and words are matched case-insensitively
+- Prefix queries use the order of the [`BTreeMap`]
+*/
+pub struct SearchIndex {
+    verses: Vec<VerseKey>,
+    /// the number of words in each verse (for relevance)
+    lengths: Vec<u32>,
+    average_length: f64,
+    postings: BTreeMap<String, Postings>,
+}
+
+impl SearchIndex {
+    pub fn build(bible: &BibleData) -> Self {
+        let mut verses = vec![];
+        let mut lengths = vec![];
+        let mut postings: HashMap<String, Postings> = HashMap::new();
+        for book in bible
+            .book_id_to_name
+            .keys()
+            .filter_map(|id| bible.get_book(*id))
+        {
+            for chapter in (1..=book.chapter_count()).filter_map(|ch| book.get_chapter(ch)) {
+                for verse in (1..=chapter.verse_count()).filter_map(|v| chapter.get_verse(v)) {
+                    let Some(content) = verse.get_content() else {
+                        continue;
+                    };
+                    let id = verses.len() as u32;
+                    verses.push(VerseKey {
+                        book: book.book_number(),
+                        chapter: verse.chapter_number(),
+                        verse: verse.verse_number(),
+                    });
+                    let tokens = tokenize(content);
+                    lengths.push(tokens.len() as u32);
+                    for (position, (word, _)) in tokens.into_iter().enumerate() {
+                        let list = postings.entry(word).or_default();
+                        match list.last_mut() {
+                            Some((last, positions)) if *last == id => {
+                                positions.push(position as u32)
+                            }
+                            _ => list.push((id, vec![position as u32])),
+                        }
+                    }
+                }
+            }
+        }
+        let average_length =
+            lengths.iter().map(|length| *length as f64).sum::<f64>() / lengths.len().max(1) as f64;
+        Self {
+            verses,
+            lengths,
+            average_length,
+            postings: postings.into_iter().collect(),
+        }
+    }
+
+    /// Every posting list of the words that start with `prefix`
+    fn prefixed<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a String, &'a Postings)> {
+        self.postings
+            .range(prefix.to_string()..)
+            .take_while(move |(word, _)| word.starts_with(prefix))
+    }
+
+    /// The verses (ids in ascending order) that match a query
+    fn matches(&self, query: &Query) -> Vec<u32> {
+        match query {
+            Query::Term(word) => self
+                .postings
+                .get(word)
+                .map(|postings| postings.iter().map(|(id, _)| *id).collect())
+                .unwrap_or_default(),
+            Query::Prefix(prefix) => {
+                let mut ids = self
+                    .prefixed(prefix)
+                    .flat_map(|(_, postings)| postings.iter().map(|(id, _)| *id))
+                    .collect::<Vec<_>>();
+                ids.sort_unstable();
+                ids.dedup();
+                ids
+            }
+            Query::Phrase(words) => self.phrase_matches(words),
+            Query::And(queries) => {
+                let (negative, positive): (Vec<_>, Vec<_>) = queries
+                    .iter()
+                    .partition(|query| matches!(query, Query::Not(_)));
+                let mut ids = match positive.split_first() {
+                    Some((first, rest)) => rest.iter().fold(self.matches(first), |ids, query| {
+                        intersect(&ids, &self.matches(query))
+                    }),
+                    None => (0..self.verses.len() as u32).collect(),
+                };
+                for query in negative {
+                    ids = difference(&ids, &self.matches(query.negated()));
+                }
+                ids
+            }
+            Query::Or(queries) => {
+                let mut ids = queries
+                    .iter()
+                    .flat_map(|query| self.matches(query))
+                    .collect::<Vec<_>>();
+                ids.sort_unstable();
+                ids.dedup();
+                ids
+            }
+            Query::Not(query) => {
+                let all = (0..self.verses.len() as u32).collect::<Vec<_>>();
+                difference(&all, &self.matches(query))
+            }
+        }
+    }
+
+    fn phrase_matches(&self, words: &[String]) -> Vec<u32> {
+        let Some(lists) = words
+            .iter()
+            .map(|word| self.postings.get(word))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return vec![];
+        };
+        let Some((first, rest)) = lists.split_first() else {
+            return vec![];
+        };
+        first
+            .iter()
+            .filter(|(id, starts)| {
+                // the positions of the rest of the words in this verse (if they're all in it)
+                let Some(rest) = rest
+                    .iter()
+                    .map(|list| positions(list, *id))
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    return false;
+                };
+                starts.iter().any(|start| {
+                    rest.iter()
+                        .enumerate()
+                        .all(|(offset, positions)| positions.contains(&(start + offset as u32 + 1)))
+                })
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// BM25, summed over every (positive) word of the query
+    fn scores(&self, query: &Query, ids: &[u32]) -> HashMap<u32, f64> {
+        const K1: f64 = 1.2;
+        const B: f64 = 0.75;
+        let total = self.verses.len() as f64;
+        let mut scores: HashMap<u32, f64> = ids.iter().map(|id| (*id, 0.0)).collect();
+        let mut add = |postings: &Postings| {
+            let frequency = postings.len() as f64;
+            let idf = (1.0 + (total - frequency + 0.5) / (frequency + 0.5)).ln();
+            for (id, positions) in postings {
+                if let Some(score) = scores.get_mut(id) {
+                    let tf = positions.len() as f64;
+                    let length = self.lengths[*id as usize] as f64 / self.average_length;
+                    *score += idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length));
+                }
+            }
+        };
+        for leaf in query.positive_leaves() {
+            match leaf {
+                Query::Term(word) => self.postings.get(word).into_iter().for_each(&mut add),
+                Query::Prefix(prefix) => self
+                    .prefixed(prefix)
+                    .for_each(|(_, postings)| add(postings)),
+                Query::Phrase(words) => words
+                    .iter()
+                    .filter_map(|word| self.postings.get(word))
+                    .for_each(&mut add),
+                _ => {}
+            }
+        }
+        scores
+    }
+}
+
+fn intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+    a.iter()
+        .filter(|id| b.binary_search(id).is_ok())
+        .copied()
+        .collect()
+}
+
+/// The positions of a word in the verse `id`
+fn positions(list: &Postings, id: u32) -> Option<&Vec<u32>> {
+    list.binary_search_by_key(&id, |(id, _)| *id)
+        .ok()
+        .map(|index| &list[index].1)
+}
+
+fn difference(a: &[u32], b: &[u32]) -> Vec<u32> {
+    a.iter()
+        .filter(|id| b.binary_search(id).is_err())
+        .copied()
+        .collect()
+}
+
+/**
+A parsed search query
+
+- `love world` matches verses with both words (`AND` is optional)
+- `"so loved"` matches the exact phrase
+- `belie*` matches words that start with `belie`
+- `faith OR hope`, `NOT works` or `-works`, and parentheses can be combined
+*/
+#[derive(Clone, Debug, PartialEq)]
+enum Query {
+    Term(String),
+    Prefix(String),
+    Phrase(Vec<String>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+}
+
+impl Query {
+    fn negated(&self) -> &Query {
+        match self {
+            Query::Not(query) => query,
+            query => query,
+        }
+    }
+
+    /// The words that make a verse match (rather than the ones that exclude it)
+    fn positive_leaves(&self) -> Vec<&Query> {
+        match self {
+            Query::Term(_) | Query::Prefix(_) | Query::Phrase(_) => vec![self],
+            Query::And(queries) | Query::Or(queries) => {
+                queries.iter().flat_map(Query::positive_leaves).collect()
+            }
+            Query::Not(_) => vec![],
+        }
+    }
+
+    /// The byte ranges of every positive word or phrase in `content`
+    fn highlights(&self, content: &str) -> Vec<Range<usize>> {
+        let tokens = tokenize(content);
+        let mut ranges = vec![];
+        for leaf in self.positive_leaves() {
+            match leaf {
+                Query::Term(word) => ranges.extend(
+                    tokens
+                        .iter()
+                        .filter(|(token, _)| token == word)
+                        .map(|(_, range)| range.clone()),
+                ),
+                Query::Prefix(prefix) => ranges.extend(
+                    tokens
+                        .iter()
+                        .filter(|(token, _)| token.starts_with(prefix.as_str()))
+                        .map(|(_, range)| range.clone()),
+                ),
+                Query::Phrase(words) => ranges.extend(
+                    tokens
+                        .windows(words.len())
+                        .filter(|window| {
+                            window
+                                .iter()
+                                .zip(words)
+                                .all(|((token, _), word)| token == word)
+                        })
+                        .map(|window| window[0].1.start..window[words.len() - 1].1.end),
+                ),
+                _ => {}
+            }
+        }
+        ranges.sort_by_key(|range| (range.start, range.end));
+        // merge overlapping ranges
+        let mut merged: Vec<Range<usize>> = vec![];
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Lexeme {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Word(String),
+    Phrase(String),
+}
+
+/// Unclosed quotes and parentheses are closed at the end, since queries are typed live
+fn lex_query(input: &str) -> Vec<Lexeme> {
+    let mut lexemes = vec![];
+    let mut chars = input.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        match ch {
+            '(' => lexemes.push(Lexeme::Open),
+            ')' => lexemes.push(Lexeme::Close),
+            '"' | '“' | '”' => {
+                let mut end = input.len();
+                for (index, ch) in chars.by_ref() {
+                    if matches!(ch, '"' | '“' | '”') {
+                        end = index;
+                        break;
+                    }
+                }
+                let phrase_start = start + ch.len_utf8();
+                lexemes.push(Lexeme::Phrase(input[phrase_start..end].to_string()));
+            }
+            '-' if chars.peek().is_some_and(|(_, next)| !next.is_whitespace()) => {
+                lexemes.push(Lexeme::Not)
+            }
+            ch if ch.is_whitespace() => {}
+            _ => {
+                let mut end = input.len();
+                while let Some((index, next)) = chars.peek().copied() {
+                    if next.is_whitespace() || matches!(next, '(' | ')' | '"' | '“' | '”') {
+                        end = index;
+                        break;
+                    }
+                    chars.next();
+                }
+                lexemes.push(match &input[start..end] {
+                    "AND" => Lexeme::And,
+                    "OR" => Lexeme::Or,
+                    "NOT" => Lexeme::Not,
+                    word => Lexeme::Word(word.to_string()),
+                });
+            }
+        }
+    }
+    lexemes
+}
+
+struct QueryParser {
+    lexemes: Vec<Lexeme>,
+    index: usize,
+}
+
+impl QueryParser {
+    fn parse(input: &str) -> Option<Query> {
+        let mut parser = Self {
+            lexemes: lex_query(input),
+            index: 0,
+        };
+        let mut queries = vec![];
+        while parser.index < parser.lexemes.len() {
+            // a stray `)` is skipped
+            match parser.parse_or() {
+                Some(query) => queries.push(query),
+                None => parser.index += 1,
+            }
+        }
+        simplify(queries, Query::And)
+    }
+
+    fn peek(&self) -> Option<&Lexeme> {
+        self.lexemes.get(self.index)
+    }
+
+    fn parse_or(&mut self) -> Option<Query> {
+        let mut queries = vec![];
+        queries.extend(self.parse_and());
+        while self.peek() == Some(&Lexeme::Or) {
+            self.index += 1;
+            queries.extend(self.parse_and());
+        }
+        simplify(queries, Query::Or)
+    }
+
+    fn parse_and(&mut self) -> Option<Query> {
+        let mut queries = vec![];
+        loop {
+            match self.peek() {
+                None | Some(Lexeme::Or) | Some(Lexeme::Close) => break,
+                Some(Lexeme::And) => self.index += 1,
+                _ => queries.extend(self.parse_unary()),
+            }
+        }
+        simplify(queries, Query::And)
+    }
+
+    fn parse_unary(&mut self) -> Option<Query> {
+        let lexeme = self.peek()?.clone();
+        self.index += 1;
+        match lexeme {
+            Lexeme::Not => Some(Query::Not(Box::new(self.parse_unary()?))),
+            Lexeme::Open => {
+                let query = self.parse_or();
+                if self.peek() == Some(&Lexeme::Close) {
+                    self.index += 1;
+                }
+                query
+            }
+            Lexeme::Word(word) => {
+                let (word, is_prefix) = match word.strip_suffix('*') {
+                    Some(prefix) => (prefix, true),
+                    None => (word.as_str(), false),
+                };
+                let mut words = tokenize(word)
+                    .into_iter()
+                    .map(|(word, _)| word)
+                    .collect::<Vec<_>>();
+                match (words.len(), is_prefix) {
+                    (0, _) => None,
+                    (1, true) => Some(Query::Prefix(words.remove(0))),
+                    (1, false) => Some(Query::Term(words.remove(0))),
+                    // `don't` is the phrase `don t`
+                    _ => Some(Query::Phrase(words)),
+                }
+            }
+            Lexeme::Phrase(phrase) => {
+                let mut words = tokenize(&phrase)
+                    .into_iter()
+                    .map(|(word, _)| word)
+                    .collect::<Vec<_>>();
+                match words.len() {
+                    0 => None,
+                    1 => Some(Query::Term(words.remove(0))),
+                    _ => Some(Query::Phrase(words)),
+                }
+            }
+            // `AND`, `OR` and `)` are handled by the callers
+            _ => None,
+        }
+    }
+}
+
+fn simplify(mut queries: Vec<Query>, combine: fn(Vec<Query>) -> Query) -> Option<Query> {
+    match queries.len() {
+        0 => None,
+        1 => queries.pop(),
+        _ => Some(combine(queries)),
+    }
+}
+
+impl BibleAPI {
+    /// Like [`Self::search_in`], but over the whole Bible
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_>> {
+        self.search_in(query, &SearchScope::Bible)
+    }
+
+    /**
+    Searches the content of the default translation, most relevant first
+
+    - `love world` matches verses with both words (`AND` is optional)
+    - `"so loved"` matches the exact phrase
+    - `belie*` matches words that start with `belie`
+    - `faith OR hope`, `NOT works` or `-works`, and parentheses can be combined
+    - Matching is case-insensitive, and unclosed quotes and parentheses are fine (so this can run
+      as the query is typed)
+    - The index is built by the first search
+    */
+    pub fn search_in(&self, query: &str, scope: &SearchScope) -> Vec<SearchHit<'_>> {
+        let Some(query) = QueryParser::parse(query) else {
+            return vec![];
+        };
+        let index = self
+            .search_index
+            .get_or_init(|| SearchIndex::build(&self.bible));
+        let ids = index
+            .matches(&query)
+            .into_iter()
+            .filter(|id| scope.contains(&index.verses[*id as usize]))
+            .collect::<Vec<_>>();
+        let scores = index.scores(&query, &ids);
+
+        let mut hits = ids
+            .into_iter()
+            .filter_map(|id| {
+                let key = index.verses[id as usize];
+                let verse = self.bible.get_verse(key.book, key.chapter, key.verse)?;
+                Some(SearchHit {
+                    verse,
+                    score: scores.get(&id).copied().unwrap_or_default(),
+                    highlights: query.highlights(verse.get_content().unwrap_or_default()),
+                })
+            })
+            .collect::<Vec<_>>();
+        // the ids are in canonical order, which breaks ties
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::formats::json::test_bible_data;
+
+    use super::*;
+
+    fn labels(hits: &[SearchHit]) -> Vec<String> {
+        hits.iter()
+            .map(|hit| {
+                format!(
+                    "{} {}:{}",
+                    hit.verse.get_book().get_name(),
+                    hit.verse.chapter_number(),
+                    hit.verse.verse_number()
+                )
+            })
+            .collect()
+    }
+
+    fn highlighted(hit: &SearchHit) -> Vec<String> {
+        let content = hit.verse.get_content().unwrap();
+        hit.highlights
+            .iter()
+            .map(|range| content[range.clone()].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parse_queries() {
+        let term = |word: &str| Query::Term(word.to_string());
+        assert_eq!(
+            QueryParser::parse("Love AND world"),
+            Some(Query::And(vec![term("love"), term("world")]))
+        );
+        assert_eq!(
+            QueryParser::parse("(faith OR hope) -works belie*"),
+            Some(Query::And(vec![
+                Query::Or(vec![term("faith"), term("hope")]),
+                Query::Not(Box::new(term("works"))),
+                Query::Prefix(String::from("belie")),
+            ]))
+        );
+        // as it is being typed
+        assert_eq!(
+            QueryParser::parse("\"so loved"),
+            Some(Query::Phrase(vec![
+                String::from("so"),
+                String::from("loved")
+            ]))
+        );
+        assert_eq!(QueryParser::parse("(faith OR"), Some(term("faith")));
+        assert_eq!(QueryParser::parse(" \"\" ) OR "), None);
+    }
+
+    #[test]
+    fn search_words_and_phrases() {
+        let api = BibleAPI::load(test_bible_data());
+        let hits = api.search("world");
+        assert_eq!(
+            labels(&hits),
+            vec!["John 3:17", "John 3:16", "Matthew 28:20", "Ephesians 1:4"]
+        );
+        assert_eq!(highlighted(&hits[0]), vec!["world", "world", "world"]);
+
+        let hits = api.search("\"loved the world\"");
+        assert_eq!(labels(&hits), vec!["John 3:16"]);
+        assert_eq!(highlighted(&hits[0]), vec!["loved the world"]);
+
+        assert_eq!(labels(&api.search("belie*")), vec!["John 3:16"]);
+        assert_eq!(labels(&api.search("GOD shepherd")), Vec::<String>::new());
+    }
+
+    #[test]
+    fn search_boolean_queries() {
+        let api = BibleAPI::load(test_bible_data());
+        assert_eq!(
+            labels(&api.search("world -condemn")),
+            vec!["John 3:16", "Matthew 28:20", "Ephesians 1:4"]
+        );
+        assert_eq!(
+            labels(&api.search("shepherd OR cup")),
+            vec!["Psalms 23:1", "Psalms 23:5"]
+        );
+        let hits = api.search("(grace OR faith) saved");
+        assert_eq!(labels(&hits), vec!["Ephesians 2:8"]);
+        assert_eq!(highlighted(&hits[0]), vec!["grace", "saved", "faith"]);
+    }
+
+    #[test]
+    fn search_scopes() {
+        let api = BibleAPI::load(test_bible_data());
+        let passage = api.parse_reference("John 3").unwrap();
+        assert_eq!(
+            labels(&api.search_in("world", &SearchScope::Passage(passage))),
+            vec!["John 3:17", "John 3:16"]
+        );
+        assert_eq!(
+            labels(&api.search_in("god", &SearchScope::Book(49))).len(),
+            5
+        );
+        assert!(api
+            .search_in("shepherd", &SearchScope::Testament(Testament::New))
+            .is_empty());
+        assert_eq!(
+            api.search_in("shepherd", &SearchScope::Testament(Testament::Old))
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::bible_data::{book::BibleBook, chapter::BibleChapter, verse::BibleVerse};
+
+use super::bible_api::BibleAPI;
+
+/// A completion for a partially typed reference
+#[derive(Clone, Copy)]
+pub enum Suggestion<'a> {
+    /// - Ex: `1 John` for `1 Jo`
+    Book(BibleBook<'a>),
+    /// - Ex: `Romans 8` for `Rom 8`
+    Chapter(BibleChapter<'a>),
+    /// - Ex: `Romans 8:28` for `Rom 8:2`
+    Verse(BibleVerse<'a>),
+}
+
+impl<'a> Suggestion<'a> {
+    /// This is what the input should be replaced with
+    pub fn label(&self) -> String {
+        match self {
+            Suggestion::Book(book) => book.get_name().to_string(),
+            Suggestion::Chapter(chapter) => format!(
+                "{} {}",
+                chapter.get_book().get_name(),
+                chapter.chapter_number()
+            ),
+            Suggestion::Verse(verse) => format!(
+                "{} {}:{}",
+                verse.get_book().get_name(),
+                verse.chapter_number(),
+                verse.verse_number()
+            ),
+        }
+    }
+
+    /// The content of the verse, or of the first verse of the chapter/book
+    pub fn preview(&self) -> Option<&'a str> {
+        match self {
+            Suggestion::Book(book) => book.get_verse(1, 1)?.get_content(),
+            Suggestion::Chapter(chapter) => chapter.get_verse(1)?.get_content(),
+            Suggestion::Verse(verse) => verse.get_content(),
+        }
+    }
+}
+
+/// - `book` is everything up until the numbers (it may only be part of a book name)
+/// - `separator` is used to tell `Rom` (still typing the book) apart from `Rom ` (now typing
+///   the chapter)
+static PARTIAL_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(?P<book>(?:[0-9]\s*)?[^0-9:]*?)(?P<separator>\s*)(?:(?P<chapter>[0-9]+)(?P<colon>:(?P<verse>[0-9]*))?)?$",
+    )
+    .unwrap()
+});
+
+impl BibleAPI {
+    /**
+    Suggests completions for a partially typed reference, ranked from best to worst
+
+    - `1 Jo` suggests books (names before abbreviations, then in canonical order)
+    - `Rom ` or `Rom 1` suggests the chapters of Romans (that start with `1`)
+    - `Eph 1:` or `Rom 8:2` suggests the verses of the chapter (that start with `2`)
+    */
+    pub fn suggest(&self, input: &str) -> Vec<Suggestion<'_>> {
+        let Some(captures) = PARTIAL_REFERENCE.captures(input) else {
+            return vec![];
+        };
+        let book_input = captures.name("book").map_or("", |m| m.as_str());
+        let still_typing_book =
+            captures["separator"].is_empty() && captures.name("chapter").is_none();
+        if still_typing_book {
+            return self.suggest_books(book_input);
+        }
+
+        let Some(book) = self
+            .bible
+            .get_book_id(book_input)
+            .and_then(|book_id| self.bible.get_book(book_id))
+        else {
+            // `Jo ` is not a book yet, but the trailing space shouldn't hide any books
+            return match captures.name("chapter") {
+                Some(_) => vec![],
+                None => self.suggest_books(book_input),
+            };
+        };
+        let chapter_input = captures.name("chapter").map_or("", |m| m.as_str());
+        let Some(verse_input) = captures.name("colon").map(|_| &captures["verse"]) else {
+            return (1..=book.chapter_count())
+                .filter(|chapter| chapter.to_string().starts_with(chapter_input))
+                .filter_map(|chapter| book.get_chapter(chapter))
+                .map(Suggestion::Chapter)
+                .collect();
+        };
+
+        let Some(chapter) = chapter_input
+            .parse()
+            .ok()
+            .and_then(|chapter| book.get_chapter(chapter))
+        else {
+            return vec![];
+        };
+        (1..=chapter.verse_count())
+            .filter(|verse| verse.to_string().starts_with(verse_input))
+            .filter_map(|verse| chapter.get_verse(verse))
+            .map(Suggestion::Verse)
+            .collect()
+    }
+
+    fn suggest_books(&self, input: &str) -> Vec<Suggestion<'_>> {
+        // so `1jo` still matches `1 John`
+        let normalize = |name: &str| name.to_lowercase().replace([' ', '.'], "");
+        let input = normalize(input);
+
+        let name_matches = self
+            .bible
+            .book_id_to_name
+            .iter()
+            .filter(|(_, name)| normalize(name).starts_with(&input))
+            .map(|(book_id, _)| *book_id);
+        let abbreviation_matches = self
+            .bible
+            .abbreviations_to_book_id
+            .iter()
+            .filter(|(abbreviation, _)| normalize(abbreviation).starts_with(&input))
+            .map(|(_, book_id)| *book_id)
+            .sorted();
+
+        name_matches
+            .chain(abbreviation_matches)
+            .unique()
+            .filter_map(|book_id| self.bible.get_book(book_id))
+            .map(Suggestion::Book)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::formats::json::test_bible_data;
+
+    use super::*;
+
+    fn labels(api: &BibleAPI, input: &str) -> Vec<String> {
+        api.suggest(input).iter().map(Suggestion::label).collect()
+    }
+
+    #[test]
+    fn suggest_books() {
+        let api = BibleAPI::load(test_bible_data());
+        assert_eq!(labels(&api, "1 Jo"), vec!["1 John"]);
+        assert_eq!(labels(&api, "1jo"), vec!["1 John"]);
+        assert_eq!(
+            labels(&api, "jo"),
+            vec!["Joshua", "Job", "Joel", "Jonah", "John"]
+        );
+        // `Judges` by name comes before `Jude` by its `jud` abbreviation
+        assert_eq!(labels(&api, "Judg"), vec!["Judges"]);
+        assert_eq!(labels(&api, "jud"), vec!["Judges", "Jude"]);
+        assert_eq!(labels(&api, "Jo "), labels(&api, "jo"));
+        assert_eq!(
+            api.suggest("Jude")[0].preview(),
+            Some("Jude, the servant of Jesus Christ, and brother of James, to them that are sanctified by God the Father, and preserved in Jesus Christ, and called:")
+        );
+    }
+
+    #[test]
+    fn suggest_chapters() {
+        let api = BibleAPI::load(test_bible_data());
+        assert_eq!(
+            labels(&api, "Rom 1"),
+            vec![
+                "Romans 1",
+                "Romans 10",
+                "Romans 11",
+                "Romans 12",
+                "Romans 13",
+                "Romans 14",
+                "Romans 15",
+                "Romans 16"
+            ]
+        );
+        assert_eq!(labels(&api, "Eph ").len(), 6);
+        assert_eq!(labels(&api, "Ps. 23"), vec!["Psalms 23"]);
+        assert_eq!(
+            api.suggest("Ps 23")[0].preview(),
+            Some("The LORD is my shepherd; I shall not want.")
+        );
+        assert!(labels(&api, "Jude 2").is_empty());
+    }
+
+    #[test]
+    fn suggest_verses() {
+        let api = BibleAPI::load(test_bible_data());
+        assert_eq!(labels(&api, "Eph 1:").len(), 23);
+        assert_eq!(
+            labels(&api, "Rom 8:2"),
+            vec![
+                "Romans 8:2",
+                "Romans 8:20",
+                "Romans 8:21",
+                "Romans 8:22",
+                "Romans 8:23",
+                "Romans 8:24",
+                "Romans 8:25",
+                "Romans 8:26",
+                "Romans 8:27",
+                "Romans 8:28",
+                "Romans 8:29"
+            ]
+        );
+        assert_eq!(
+            api.suggest("John 3:16")[0].preview(),
+            Some("For God so loved the world, that he gave his only begotten Son, that whosoever believeth in him should not perish, but have everlasting life.")
+        );
+        assert!(labels(&api, "Eph 7:").is_empty());
+        assert!(labels(&api, "Eph 0:").is_empty());
+        assert!(labels(&api, "Eph 1:24").is_empty());
+        assert!(labels(&api, "Nothing 1:1").is_empty());
+    }
+}
@@ -1,15 +1,18 @@
 use std::{
     collections::BTreeMap,
-    ops::{Deref, RangeInclusive},
+    ops::{Deref, Range, RangeInclusive},
     path::Path,
 };
 
+use itertools::Itertools;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::api::bible_api::ApiData;
 
-use super::{book::BibleBook, chapter::BibleChapter, verse::BibleVerse};
+use super::{
+    book::BibleBook, chapter::BibleChapter, formats::json::JSONTranslation, verse::BibleVerse,
+};
 
 /// map of abbreviations and actual name (all lowercase) to book id
 pub type AbbreviationsToBookId = BTreeMap<String, usize>;
@@ -34,19 +37,296 @@ pub type BibleContents = Vec<ChapterDataList>;
 
 //
 
-/// This is it's own struct so it will be easier to add things like cross-references later
-#[derive(Clone, Serialize)]
+/// How a paragraph (or line of poetry) is laid out
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParagraphStyle {
+    /// Ex: `\p` in USFM
+    Prose,
+    /// - The indentation level, starting at 1
+    /// - Ex: `\q2` in USFM is `Poetry(2)`
+    Poetry(u8),
+}
+
+/// - A new paragraph or line of poetry
+/// - It starts at `offset` (a byte offset into the verse content), so `0` is the start of the
+///   verse
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParagraphBreak {
+    pub offset: usize,
+    pub style: ParagraphStyle,
+}
+
+/// A footnote or cross reference note
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    /// byte offset into the verse content that the note is attached to
+    pub offset: usize,
+    /// - The reference the note is about, when it says
+    /// - Ex: `1:1` (from `\fr 1:1` in USFM)
+    pub origin: Option<String>,
+    /// - The text of the note
+    /// - For cross references, this is the referenced passages (Ex: `Jn 1:1; Heb 11:3`)
+    pub text: String,
+}
+
+/// A word of the verse content with lexical information
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaggedWord {
+    /// byte range of the word in the verse content
+    pub range: Range<usize>,
+    /// Ex: `H7225`
+    pub strong: Option<String>,
+    pub lemma: Option<String>,
+    /// Ex: `HR/Ncfsa`
+    pub morph: Option<String>,
+}
+
+/// How a range of the verse content is displayed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextStyle {
+    /// - Red letters
+    /// - Ex: `\wj` in USFM, `<q who="Jesus">` in OSIS
+    WordsOfJesus,
+    /// - The name of God, which is usually in small caps (like LORD)
+    /// - Ex: `\nd` in USFM, `<divineName>` in OSIS
+    DivineName,
+    /// Ex: `\sc` in USFM, `<hi type="small-caps">` in OSIS
+    SmallCaps,
+    /// Ex: `\qs` in USFM
+    Selah,
+}
+
+impl TextStyle {
+    /// Ex: `words-of-jesus`, which is what `{style}` is in templates
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextStyle::WordsOfJesus => "words-of-jesus",
+            TextStyle::DivineName => "divine-name",
+            TextStyle::SmallCaps => "small-caps",
+            TextStyle::Selah => "selah",
+        }
+    }
+
+    /// The style with this [`Self::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            TextStyle::WordsOfJesus,
+            TextStyle::DivineName,
+            TextStyle::SmallCaps,
+            TextStyle::Selah,
+        ]
+        .into_iter()
+        .find(|style| style.name() == name)
+    }
+}
+
+/// A range of the verse content with a [`TextStyle`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StyledText {
+    /// byte range in the verse content
+    pub range: Range<usize>,
+    pub style: TextStyle,
+}
+
+/// Everything about a verse besides its content, which most verses don't have any of
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VerseMarkup {
+    /// section headings that come right before this verse
+    pub(super) headings: Vec<String>,
+    pub(super) paragraphs: Vec<ParagraphBreak>,
+    pub(super) footnotes: Vec<Note>,
+    pub(super) cross_references: Vec<Note>,
+    pub(super) words: Vec<TaggedWord>,
+    pub(super) styles: Vec<StyledText>,
+}
+
+/// The markup of verses without any
+static NO_MARKUP: VerseMarkup = VerseMarkup {
+    headings: Vec::new(),
+    paragraphs: Vec::new(),
+    footnotes: Vec::new(),
+    cross_references: Vec::new(),
+    words: Vec::new(),
+    styles: Vec::new(),
+};
+
+impl VerseMarkup {
+    pub fn headings(&self) -> &[String] {
+        &self.headings
+    }
+
+    pub fn paragraphs(&self) -> &[ParagraphBreak] {
+        &self.paragraphs
+    }
+
+    pub fn footnotes(&self) -> &[Note] {
+        &self.footnotes
+    }
+
+    pub fn cross_references(&self) -> &[Note] {
+        &self.cross_references
+    }
+
+    pub fn words(&self) -> &[TaggedWord] {
+        &self.words
+    }
+
+    /// - The red letters, divine names, and so on of the content
+    /// - These can be nested (Ex: `LORD` in the words of Jesus)
+    pub fn styles(&self) -> &[StyledText] {
+        &self.styles
+    }
+
+    /// The verse starts a new paragraph (or line of poetry), or is after a heading
+    pub fn starts_paragraph(&self) -> bool {
+        !self.headings.is_empty()
+            || self
+                .paragraphs
+                .iter()
+                .any(|paragraph| paragraph.offset == 0)
+    }
+}
+
+/// How [`VerseData`] is serialized, with its markup next to its content
+#[derive(Clone, Serialize, Deserialize)]
+struct SerializedVerseData {
+    content: Option<String>,
+    headings: Vec<String>,
+    paragraphs: Vec<ParagraphBreak>,
+    footnotes: Vec<Note>,
+    cross_references: Vec<Note>,
+    words: Vec<TaggedWord>,
+    styles: Vec<StyledText>,
+}
+
+/**
+A verse while a translation is being parsed (or exported)
+
+- [`BibleData`] keeps the content of every verse in one buffer instead (see [`PackedVerse`])
+*/
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SerializedVerseData", into = "SerializedVerseData")]
 pub struct VerseData {
     pub(super) content: Option<String>,
+    /// boxed so that verses without markup are small
+    pub(super) markup: Option<Box<VerseMarkup>>,
+}
+
+impl From<SerializedVerseData> for VerseData {
+    fn from(verse: SerializedVerseData) -> Self {
+        let markup = VerseMarkup {
+            headings: verse.headings,
+            paragraphs: verse.paragraphs,
+            footnotes: verse.footnotes,
+            cross_references: verse.cross_references,
+            words: verse.words,
+            styles: verse.styles,
+        };
+        Self {
+            content: verse.content,
+            markup: (markup != VerseMarkup::default()).then(|| Box::new(markup)),
+        }
+    }
+}
+
+impl From<VerseData> for SerializedVerseData {
+    fn from(verse: VerseData) -> Self {
+        let markup = verse.markup.map(|markup| *markup).unwrap_or_default();
+        Self {
+            content: verse.content,
+            headings: markup.headings,
+            paragraphs: markup.paragraphs,
+            footnotes: markup.footnotes,
+            cross_references: markup.cross_references,
+            words: markup.words,
+            styles: markup.styles,
+        }
+    }
+}
+
+impl VerseData {
+    pub fn new(content: Option<String>) -> Self {
+        Self {
+            content,
+            markup: None,
+        }
+    }
+
+    pub fn content(&self) -> Option<&str> {
+        self.content.as_deref()
+    }
+
+    /// Everything besides the content (which is empty for most verses)
+    pub fn markup(&self) -> &VerseMarkup {
+        self.markup.as_deref().unwrap_or(&NO_MARKUP)
+    }
+
+    pub fn headings(&self) -> &[String] {
+        self.markup().headings()
+    }
+
+    pub fn paragraphs(&self) -> &[ParagraphBreak] {
+        self.markup().paragraphs()
+    }
+
+    pub fn footnotes(&self) -> &[Note] {
+        self.markup().footnotes()
+    }
+
+    pub fn cross_references(&self) -> &[Note] {
+        self.markup().cross_references()
+    }
+
+    pub fn words(&self) -> &[TaggedWord] {
+        self.markup().words()
+    }
+
+    /// See [`VerseMarkup::styles`]
+    pub fn styles(&self) -> &[StyledText] {
+        self.markup().styles()
+    }
+
+    /// See [`VerseMarkup::starts_paragraph`]
+    pub fn starts_paragraph(&self) -> bool {
+        self.markup().starts_paragraph()
+    }
+
+    /// The markup to add to
+    pub(super) fn markup_mut(&mut self) -> &mut VerseMarkup {
+        self.markup.get_or_insert_with(Box::default)
+    }
+}
+
+/**
+A verse of [`BibleData`], where its content is a range of the text of the whole translation
+(see [`BookDataList::content`])
+
+- This is 16 bytes (besides any markup), instead of a `String` for each verse
+*/
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PackedVerse {
+    /// [`Self::NO_CONTENT`] when the verse has no content
+    content: Range<u32>,
+    /// boxed so that verses without markup are small
+    markup: Option<Box<VerseMarkup>>,
+}
+
+impl PackedVerse {
+    /// The range of verses without content, which is never a range of the text
+    const NO_CONTENT: Range<u32> = u32::MAX..u32::MAX;
+
+    /// Everything besides the content (which is empty for most verses)
+    pub fn markup(&self) -> &VerseMarkup {
+        self.markup.as_deref().unwrap_or(&NO_MARKUP)
+    }
 }
-impl VerseData {}
 
 /// **NOTE: EVERYTHING IS INDEX 0**
 // #[derive(Debug)]
-#[derive(Clone, Serialize)]
-pub struct VerseDataList(pub(super) Vec<VerseData>);
+#[derive(Clone, Serialize, Deserialize)]
+pub struct VerseDataList(pub(super) Vec<PackedVerse>);
 impl Deref for VerseDataList {
-    type Target = Vec<VerseData>;
+    type Target = Vec<PackedVerse>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -55,7 +335,7 @@ impl Deref for VerseDataList {
 
 /// **NOTE: EVERYTHING IS INDEX 0**
 // #[derive(Debug)]
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ChapterDataList(pub(super) Vec<VerseDataList>);
 impl Deref for ChapterDataList {
     type Target = Vec<VerseDataList>;
@@ -67,17 +347,91 @@ impl Deref for ChapterDataList {
 
 /// **NOTE: EVERYTHING IS INDEX 0**
 // #[derive(Debug)]
-pub struct BookDataList(pub(super) Vec<ChapterDataList>);
+pub struct BookDataList {
+    pub(super) books: Vec<ChapterDataList>,
+    /// the content of every verse, one after another
+    pub(super) text: Box<str>,
+}
 impl Deref for BookDataList {
     type Target = Vec<ChapterDataList>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.books
+    }
+}
+
+impl BookDataList {
+    /**
+    Moves the content of every verse into one buffer, so each verse is only a range of it
+
+    - This is one allocation for the whole translation, instead of one for each verse
+    - Ex: `["In the beginning", "And the earth"]` is `"In the beginningAnd the earth"`, where the
+      verses are `0..16` and `16..29`
+    - Returns an error when there is too much text to index with `u32` (4 GiB)
+    */
+    pub(super) fn pack(
+        books: Vec<Vec<Vec<VerseData>>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let length: usize = books
+            .iter()
+            .flatten()
+            .flatten()
+            .filter_map(VerseData::content)
+            .map(str::len)
+            .sum();
+        if length >= u32::MAX as usize {
+            return Err(format!("the translation has too much text ({length} bytes)").into());
+        }
+        let mut text = String::with_capacity(length);
+        let books = books
+            .into_iter()
+            .map(|chapters| {
+                let chapters = chapters.into_iter().map(|verses| {
+                    let verses = verses.into_iter().map(|verse| {
+                        let content = match verse.content {
+                            // this fits, since the whole text does
+                            Some(content) => {
+                                let start = text.len() as u32;
+                                text.push_str(&content);
+                                start..text.len() as u32
+                            }
+                            None => PackedVerse::NO_CONTENT,
+                        };
+                        PackedVerse {
+                            content,
+                            markup: verse.markup,
+                        }
+                    });
+                    VerseDataList(verses.collect())
+                });
+                ChapterDataList(chapters.collect())
+            })
+            .collect();
+        Ok(Self {
+            books,
+            text: text.into_boxed_str(),
+        })
+    }
+
+    /// The content of `verse`, which is a verse of this translation
+    pub fn content(&self, verse: &PackedVerse) -> Option<&str> {
+        (verse.content != PackedVerse::NO_CONTENT)
+            .then(|| &self.text[verse.content.start as usize..verse.content.end as usize])
+    }
+
+    /// `verse` as it was before it was packed
+    pub(super) fn unpack(&self, verse: &PackedVerse) -> VerseData {
+        VerseData {
+            content: self.content(verse).map(str::to_string),
+            markup: verse.markup.clone(),
+        }
     }
 }
 
 pub struct BibleData {
     // pub data: &'a AppData,
+    /// the name, language, and abbreviation of this translation
+    pub translation: JSONTranslation,
     /// regex to match all book names or abbreviations that are part of this data set
     pub book_regex: Regex,
     /// map of abbreviations and actual name (all lowercase) to book id
@@ -96,7 +450,117 @@ pub struct BibleData {
     pub bible_contents: BookDataList,
 }
 
+/// The regex that matches every book name and abbreviation of a translation
+pub(super) fn book_regex(abbreviations_to_book_id: &AbbreviationsToBookId) -> Regex {
+    // longest names first, so that overlapping candidates (like `song` and `song of songs`)
+    // always resolve to the most specific book name
+    let books_pattern = abbreviations_to_book_id
+        .keys()
+        .sorted_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)))
+        .map(|key| regex::escape(key))
+        .join("|");
+    // I added the period so that people can use it in abbreviations
+    Regex::new(format!(r"\b((?i){books_pattern})\b\.?").as_str())
+        .expect("Failed to compile book_regex.")
+}
+
+/// A book from any source format, before it is indexed into [`BibleData`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BookData {
+    /// book id where Genesis = 1
+    pub id: usize,
+    /// the name of the book as it is displayed
+    pub name: String,
+    /// all abbreviations (any case), not necessarily including the book name
+    pub abbreviations: Vec<String>,
+    /// **NOTE: EVERYTHING IS INDEX 0**
+    pub chapters: Vec<Vec<VerseData>>,
+}
+
 impl BibleData {
+    /**
+    Indexes the books of a translation, which is what each format's
+    [`ParseBibleData::as_bible_data`](super::formats::parse::ParseBibleData::as_bible_data) does
+
+    - Books can be in any order, and missing books (like the Old Testament of a New Testament)
+      are left out
+    - Each book can only be in it once
+    - The content of every verse is moved into one buffer (see [`BookDataList::pack`])
+    */
+    pub fn from_books(
+        translation: JSONTranslation,
+        mut books: Vec<BookData>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut abbreviations_to_book_id = AbbreviationsToBookId::new();
+        let mut book_id_to_name = BookIdToName::new();
+        let mut reference_array = ReferenceArray::new();
+        let mut bible_contents: Vec<Vec<Vec<VerseData>>> = Vec::new();
+
+        books.sort_by_key(|book| book.id);
+        for book in books.into_iter() {
+            if book.id == 0 {
+                return Err(format!("{} has the book id 0 (Genesis is 1)", book.name).into());
+            }
+            // the contents are indexed by book id, so missing books are left empty
+            while bible_contents.len() < book.id - 1 {
+                reference_array.push(vec![]);
+                bible_contents.push(vec![]);
+            }
+            if bible_contents.len() != book.id - 1 {
+                return Err(format!(
+                    "{} (book id {}) is in the translation twice",
+                    book.name, book.id
+                )
+                .into());
+            }
+            abbreviations_to_book_id.insert(book.name.to_lowercase(), book.id);
+            for abbreviation in book.abbreviations.iter() {
+                abbreviations_to_book_id.insert(abbreviation.to_lowercase(), book.id);
+            }
+            reference_array.push(book.chapters.iter().map(Vec::len).collect());
+            bible_contents.push(book.chapters);
+            book_id_to_name.insert(book.id, book.name);
+        }
+
+        Ok(BibleData {
+            book_regex: book_regex(&abbreviations_to_book_id),
+            translation,
+            abbreviations_to_book_id,
+            book_id_to_name,
+            reference_array,
+            bible_contents: BookDataList::pack(bible_contents)?,
+        })
+    }
+
+    /// - The books of this translation, like they were before [`Self::from_books`]
+    /// - Abbreviations are lowercase
+    pub fn to_books(&self) -> Vec<BookData> {
+        self.book_id_to_name
+            .iter()
+            .map(|(id, name)| BookData {
+                id: *id,
+                name: name.clone(),
+                abbreviations: self
+                    .abbreviations_to_book_id
+                    .iter()
+                    .filter(|(abbreviation, book)| {
+                        *book == id && **abbreviation != name.to_lowercase()
+                    })
+                    .map(|(abbreviation, _)| abbreviation.clone())
+                    .collect(),
+                chapters: self.bible_contents[id - 1]
+                    .iter()
+                    .map(|verses| {
+                        verses
+                            .iter()
+                            .map(|verse| self.bible_contents.unpack(verse))
+                            .collect()
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
     pub fn get_book(&self, book: usize) -> Option<BibleBook> {
         let name = self.book_id_to_name.get(&book)?;
         let chapters = &self.bible_contents.get(book - 1)?;
@@ -116,9 +580,70 @@ impl BibleData {
         self.get_book(book)?.get_chapter(chapter)?.get_verse(verse)
     }
 
+    /// Number of chapters in a book, according to the [`ReferenceArray`]
+    pub fn chapter_count(&self, book: usize) -> Option<usize> {
+        Some(self.reference_array.get(book.checked_sub(1)?)?.len())
+    }
+
+    /// Number of verses in a chapter, according to the [`ReferenceArray`]
+    pub fn verse_count(&self, book: usize, chapter: usize) -> Option<usize> {
+        self.reference_array
+            .get(book.checked_sub(1)?)?
+            .get(chapter.checked_sub(1)?)
+            .copied()
+    }
+
     pub fn get_book_id(&self, book: &str) -> Option<usize> {
         self.abbreviations_to_book_id
             .get(book.to_lowercase().trim_end_matches("."))
             .cloned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::formats::json::{test_bible_data, test_json_bible};
+
+    use super::*;
+
+    #[test]
+    fn verses_share_one_buffer() {
+        let json = test_json_bible();
+        let expected: Vec<Option<String>> = json
+            .bible
+            .iter()
+            .flat_map(|book| book.content.iter().flatten().cloned())
+            .collect();
+        let bible = test_bible_data();
+        let contents = &bible.bible_contents;
+        let verses = || {
+            contents
+                .iter()
+                .flat_map(|chapters| chapters.iter())
+                .flat_map(|verses| verses.iter())
+        };
+        assert_eq!(
+            verses()
+                .map(|verse| contents.content(verse).map(str::to_string))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            contents.text.len(),
+            expected.iter().flatten().map(String::len).sum::<usize>()
+        );
+        assert_eq!(std::mem::size_of::<PackedVerse>(), 16);
+
+        // an empty verse isn't a missing one
+        let books = vec![vec![vec![
+            VerseData::new(Some(String::new())),
+            VerseData::new(None),
+            VerseData::new(Some(String::from("Jesus wept."))),
+        ]]];
+        let packed = BookDataList::pack(books).unwrap();
+        let verses = &packed[0][0];
+        assert_eq!(packed.content(&verses[0]), Some(""));
+        assert_eq!(packed.content(&verses[1]), None);
+        assert_eq!(packed.content(&verses[2]), Some("Jesus wept."));
+    }
+}
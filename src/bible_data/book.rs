@@ -5,6 +5,7 @@ use crate::api::bible_api::BibleAPI;
 use super::{
     bible_data::{BibleData, ChapterDataList, VerseDataList},
     chapter::BibleChapter,
+    formats::json::JSONTranslation,
     verse::BibleVerse,
 };
 
@@ -27,7 +28,7 @@ impl<'a> BibleBook<'a> {
     // when i add lifetime 'a to self, then it doesn't work
     // why? because &'a creates a new lifetime and isn't using the one it already has
     pub fn get_chapter(&self, chapter: usize) -> Option<BibleChapter<'a>> {
-        let verses: &'a VerseDataList = self.chapters.get(chapter - 1)?;
+        let verses: &'a VerseDataList = self.chapters.get(chapter.checked_sub(1)?)?;
         Some(BibleChapter {
             bible: self.bible,
             // api: self.api,
@@ -41,7 +42,28 @@ impl<'a> BibleBook<'a> {
         self.get_chapter(chapter)?.get_verse(verse)
     }
 
+    pub fn chapter_count(&self) -> usize {
+        self.chapters.len()
+    }
+
+    /// Number of verses in a chapter, according to the [`ReferenceArray`](super::bible_data::ReferenceArray)
+    pub fn verse_count(&self, chapter: usize) -> Option<usize> {
+        self.bible.verse_count(self.book, chapter)
+    }
+
     pub fn get_name(&self) -> &'a str {
         self.name
     }
+
+    /// The translation this book is from
+    pub fn get_translation(&self) -> &'a JSONTranslation {
+        &self.bible.translation
+    }
+
+    /// - The book after this one in canonical order
+    /// - Books that aren't in the translation are skipped (Ex: Exodus is followed by Numbers in a
+    ///   translation without Leviticus)
+    pub fn next_book(&self) -> Option<BibleBook<'a>> {
+        (self.book + 1..=self.bible.bible_contents.len()).find_map(|book| self.bible.get_book(book))
+    }
 }
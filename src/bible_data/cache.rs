@@ -0,0 +1,264 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    bible_data::{
+        book_regex, AbbreviationsToBookId, BibleData, BookDataList, BookIdToName, ChapterDataList,
+        ReferenceArray,
+    },
+    formats::json::JSONTranslation,
+};
+
+/// the first bytes of every cache file
+const MAGIC: &[u8; 8] = b"BIBLEAPI";
+
+/// This changes whenever what is cached changes shape, so old caches are rebuilt
+pub const CACHE_VERSION: u32 = 3;
+
+/// magic, version, source checksum, source length, payload checksum
+const HEADER_LENGTH: usize = 8 + 4 + 4 + 8 + 4;
+
+/// Everything in [`BibleData`] except `book_regex` (which is rebuilt from the abbreviations)
+#[derive(Serialize, Deserialize)]
+struct CachedBible<'a> {
+    translation: Cow<'a, JSONTranslation>,
+    abbreviations_to_book_id: Cow<'a, AbbreviationsToBookId>,
+    book_id_to_name: Cow<'a, BookIdToName>,
+    reference_array: Cow<'a, ReferenceArray>,
+    bible_contents: Cow<'a, [ChapterDataList]>,
+    /// the content of every verse, which the verses of `bible_contents` are ranges of
+    text: Cow<'a, str>,
+}
+
+/// - What a cache was made from, so it can tell when the source has changed
+/// - A folder (like a USFM translation) is every file in it, in order of their path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SourceFingerprint {
+    checksum: u32,
+    length: u64,
+}
+
+impl SourceFingerprint {
+    fn of(path: &Path) -> std::io::Result<Self> {
+        fn files(path: &Path, found: &mut Vec<PathBuf>) -> std::io::Result<()> {
+            if path.is_dir() {
+                for entry in std::fs::read_dir(path)? {
+                    files(&entry?.path(), found)?;
+                }
+            } else {
+                found.push(path.to_path_buf());
+            }
+            Ok(())
+        }
+
+        let mut found = vec![];
+        files(path, &mut found)?;
+        found.sort();
+        let mut hasher = crc32fast::Hasher::new();
+        let mut length = 0;
+        for file in found.iter() {
+            let contents = std::fs::read(file)?;
+            if path.is_dir() {
+                hasher.update(
+                    file.strip_prefix(path)
+                        .unwrap_or(file)
+                        .as_os_str()
+                        .as_encoded_bytes(),
+                );
+            }
+            hasher.update(&contents);
+            length += contents.len() as u64;
+        }
+        Ok(Self {
+            checksum: hasher.finalize(),
+            length,
+        })
+    }
+}
+
+impl BibleData {
+    /**
+    Writes this translation to `cache` in a compact binary format, so that it can be loaded in
+    milliseconds with [`Self::read_cache`]
+
+    - `source` is the file (or folder) that this was parsed from, which is checksummed so that the
+      cache is only used while the source hasn't changed
+    - The cache is written to a temporary file first, so a cache is never half written
+    */
+    pub fn write_cache(
+        &self,
+        source: &Path,
+        cache: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let fingerprint = SourceFingerprint::of(source)?;
+        let payload = bincode::serialize(&CachedBible {
+            translation: Cow::Borrowed(&self.translation),
+            abbreviations_to_book_id: Cow::Borrowed(&self.abbreviations_to_book_id),
+            book_id_to_name: Cow::Borrowed(&self.book_id_to_name),
+            reference_array: Cow::Borrowed(&self.reference_array),
+            bible_contents: Cow::Borrowed(&self.bible_contents[..]),
+            text: Cow::Borrowed(&self.bible_contents.text),
+        })?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH + payload.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&fingerprint.checksum.to_le_bytes());
+        bytes.extend_from_slice(&fingerprint.length.to_le_bytes());
+        bytes.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+
+        let temporary = cache.with_extension("tmp");
+        std::fs::write(&temporary, bytes)?;
+        std::fs::rename(temporary, cache)?;
+        Ok(())
+    }
+
+    /**
+    Loads a translation that was written with [`Self::write_cache`]
+
+    - Returns [`None`] when the cache is missing, from another [`CACHE_VERSION`], or when `source`
+      has changed since it was written
+    - Returns an error when the cache isn't one, or is corrupted
+    */
+    pub fn read_cache(
+        source: &Path,
+        cache: &Path,
+    ) -> Result<Option<BibleData>, Box<dyn std::error::Error>> {
+        let bytes = match std::fs::read(cache) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        if bytes.len() < HEADER_LENGTH || &bytes[..8] != MAGIC {
+            return Err(format!("{} isn't a Bible cache", cache.display()).into());
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        if u32_at(8) != CACHE_VERSION {
+            return Ok(None);
+        }
+        let fingerprint = SourceFingerprint {
+            checksum: u32_at(12),
+            length: u64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+        };
+        if fingerprint != SourceFingerprint::of(source)? {
+            return Ok(None);
+        }
+        let payload = &bytes[HEADER_LENGTH..];
+        if crc32fast::hash(payload) != u32_at(24) {
+            return Err(format!("{} is corrupted", cache.display()).into());
+        }
+
+        let cached: CachedBible = bincode::deserialize(payload)?;
+        let abbreviations_to_book_id = cached.abbreviations_to_book_id.into_owned();
+        Ok(Some(BibleData {
+            book_regex: book_regex(&abbreviations_to_book_id),
+            translation: cached.translation.into_owned(),
+            abbreviations_to_book_id,
+            book_id_to_name: cached.book_id_to_name.into_owned(),
+            reference_array: cached.reference_array.into_owned(),
+            // the verses are already packed, so the text is read into one buffer
+            bible_contents: BookDataList {
+                books: cached.bible_contents.into_owned(),
+                text: cached.text.into_owned().into_boxed_str(),
+            },
+        }))
+    }
+
+    /**
+    Loads `source` from `cache`, or parses it with `parse` (and writes the cache) when the cache
+    can't be used
+
+    - A cache that can't be written only makes the next start slower, so it isn't an error
+    */
+    pub fn load_cached(
+        source: &Path,
+        cache: &Path,
+        parse: impl FnOnce(&Path) -> Result<BibleData, Box<dyn std::error::Error>>,
+    ) -> Result<BibleData, Box<dyn std::error::Error>> {
+        if let Ok(Some(bible)) = Self::read_cache(source, cache) {
+            return Ok(bible);
+        }
+        let bible = parse(source)?;
+        let _ = bible.write_cache(source, cache);
+        Ok(bible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::formats::{
+        json::{test_json_bible, JSONBible},
+        parse::{assert_round_trip, ExportBibleData, ParseBibleData},
+    };
+
+    use super::*;
+
+    /// A copy of the KJV fixture in its own folder, since the tests change it
+    fn source(name: &str) -> (PathBuf, PathBuf) {
+        let folder = std::env::temp_dir().join(format!("bible_api_cache_{name}"));
+        std::fs::create_dir_all(&folder).unwrap();
+        let source = folder.join("kjv.json");
+        let bible = test_json_bible().as_bible_data().unwrap();
+        JSONBible::from_bible_data(&bible)
+            .write_file(&source)
+            .unwrap();
+        let cache = folder.join("kjv.cache");
+        let _ = std::fs::remove_file(&cache);
+        (source, cache)
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let (source, cache) = source("round_trip");
+        assert!(BibleData::read_cache(&source, &cache).unwrap().is_none());
+        let parsed = JSONBible::load_cached(&source, &cache).unwrap();
+        let cached = BibleData::read_cache(&source, &cache).unwrap().unwrap();
+        assert_round_trip(&parsed, &cached);
+        assert_eq!(cached.get_book_id("gen"), Some(1));
+        assert_eq!(
+            cached.get_verse(43, 3, 16).unwrap().get_content(),
+            parsed.get_verse(43, 3, 16).unwrap().get_content()
+        );
+    }
+
+    #[test]
+    fn cache_is_invalidated() {
+        let (source, cache) = source("invalidated");
+        let parsed = JSONBible::load_cached(&source, &cache).unwrap();
+
+        // the source changed
+        let mut json = JSONBible::from_bible_data(&parsed);
+        json.translation.name = String::from("Changed");
+        json.write_file(&source).unwrap();
+        assert!(BibleData::read_cache(&source, &cache).unwrap().is_none());
+        let reloaded = JSONBible::load_cached(&source, &cache).unwrap();
+        assert_eq!(reloaded.translation.name, "Changed");
+        assert!(BibleData::read_cache(&source, &cache).unwrap().is_some());
+
+        // another version of the format
+        let mut bytes = std::fs::read(&cache).unwrap();
+        bytes[8..12].copy_from_slice(&(CACHE_VERSION + 1).to_le_bytes());
+        std::fs::write(&cache, &bytes).unwrap();
+        assert!(BibleData::read_cache(&source, &cache).unwrap().is_none());
+
+        // corrupted
+        bytes[8..12].copy_from_slice(&CACHE_VERSION.to_le_bytes());
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        std::fs::write(&cache, &bytes).unwrap();
+        assert!(BibleData::read_cache(&source, &cache).is_err());
+        assert_eq!(
+            JSONBible::load_cached(&source, &cache)
+                .unwrap()
+                .translation
+                .name,
+            "Changed"
+        );
+        assert!(BibleData::read_cache(&source, &cache).unwrap().is_some());
+    }
+}
@@ -41,7 +41,10 @@ impl<'a> BibleChapter<'a> {
         // the early return from `verses.get()?` is for when the Bible verse does not exist
         // within the verse list, NOT for when the Bible verse's content has been deemed
         // not authentic to the original texts
-        let content = self.verses.get(verse - 1)?.content.as_deref();
+        let content = self
+            .bible
+            .bible_contents
+            .content(self.verses.get(verse.checked_sub(1)?)?);
         Some(BibleVerse {
             bible: self.bible,
             // api: self.api,
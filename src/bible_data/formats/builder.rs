@@ -0,0 +1,356 @@
+use crate::bible_data::bible_data::{
+    Note, ParagraphBreak, ParagraphStyle, StyledText, TaggedWord, TextStyle, VerseData,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum NoteKind {
+    Footnote,
+    CrossReference,
+}
+
+/// Collapses every run of whitespace into a space, and trims the ends
+pub(super) fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/**
+Builds the verses of a book from text that is read in order, which is how the markup formats
+(like USFM and OSIS) are parsed
+
+- Text only goes into a verse after [`Self::start_verse`], so introductions are left out
+- Headings and paragraph breaks are kept until the next verse (or text) that they belong to
+*/
+#[derive(Default)]
+pub(super) struct BookBuilder {
+    /// **NOTE: EVERYTHING IS INDEX 0**
+    chapters: Vec<Vec<VerseData>>,
+    chapter: usize,
+    /// the verse that text goes into (0 is outside of a verse)
+    verse: usize,
+    headings: Vec<String>,
+    paragraph: Option<ParagraphStyle>,
+    /// styles that have started but not ended, and where they started in the current verse
+    styles: Vec<(TextStyle, usize)>,
+}
+
+impl BookBuilder {
+    fn current_verse(&mut self) -> Option<&mut VerseData> {
+        self.chapters
+            .get_mut(self.chapter.checked_sub(1)?)?
+            .get_mut(self.verse.checked_sub(1)?)
+    }
+
+    pub(super) fn in_verse(&self) -> bool {
+        self.verse != 0
+    }
+
+    /// Appends text to the current verse, collapsing whitespace
+    pub(super) fn push_text(&mut self, text: &str) {
+        let mut paragraph = self.paragraph;
+        let Some(verse) = self.current_verse() else {
+            return;
+        };
+        let VerseData { content, markup } = verse;
+        let content = content.get_or_insert_with(String::new);
+        for ch in text.chars() {
+            if ch.is_whitespace() {
+                if !content.is_empty() && !content.ends_with(' ') {
+                    content.push(' ');
+                }
+                continue;
+            }
+            if let Some(style) = paragraph.take() {
+                markup
+                    .get_or_insert_with(Box::default)
+                    .paragraphs
+                    .push(ParagraphBreak {
+                        offset: content.len(),
+                        style,
+                    });
+            }
+            content.push(ch);
+        }
+        self.paragraph = paragraph;
+    }
+
+    pub(super) fn start_chapter(&mut self, chapter: usize) {
+        self.finish_verse();
+        self.chapter = chapter;
+        if self.chapters.len() < chapter {
+            self.chapters.resize_with(chapter, Vec::new);
+        }
+    }
+
+    /// - `end` is the last verse of a combined verse (like `17-18`), whose content is all in
+    ///   `start`
+    /// - The chapter is started if it isn't the current one
+    pub(super) fn start_verse(&mut self, chapter: usize, start: usize, end: usize) {
+        if chapter != self.chapter {
+            self.start_chapter(chapter);
+        }
+        self.finish_verse();
+        let Some(verses) = chapter
+            .checked_sub(1)
+            .and_then(|index| self.chapters.get_mut(index))
+        else {
+            return;
+        };
+        let end = end.max(start);
+        if verses.len() < end {
+            verses.resize_with(end, VerseData::default);
+        }
+        self.verse = start;
+        let headings = std::mem::take(&mut self.headings);
+        if headings.is_empty() {
+            return;
+        }
+        if let Some(verse) = self.current_verse() {
+            verse.markup_mut().headings.extend(headings);
+        }
+    }
+
+    pub(super) fn finish_verse(&mut self) {
+        // styles that go on to the next verse (like OSIS milestones) start over at its start
+        let styles = self.styles.clone();
+        for (style, _) in styles.iter().rev() {
+            self.end_style(*style);
+        }
+        self.styles = styles.into_iter().map(|(style, _)| (style, 0)).collect();
+        if let Some(verse) = self.current_verse() {
+            if let Some(content) = verse.content.as_mut() {
+                content.truncate(content.trim_end().len());
+            }
+            if verse.content() == Some("") {
+                verse.content = None;
+            }
+        }
+        self.verse = 0;
+    }
+
+    /// - A section heading for the next verse
+    /// - A heading at the very start of a verse (like some Psalm titles) is for that verse
+    pub(super) fn heading(&mut self, heading: &str) {
+        let heading = collapse_whitespace(heading);
+        if heading.is_empty() {
+            return;
+        }
+        match self.current_verse() {
+            Some(verse) if verse.content().unwrap_or_default().is_empty() => {
+                verse.markup_mut().headings.push(heading)
+            }
+            _ => self.headings.push(heading),
+        }
+    }
+
+    /// The next text starts a new paragraph (or line of poetry)
+    pub(super) fn paragraph(&mut self, style: ParagraphStyle) {
+        self.paragraph = Some(style);
+        // so that the lines of `<l>a</l><l>b</l>` aren't joined
+        self.push_text(" ");
+    }
+
+    /// The byte offset after the content so far, which notes are attached to
+    pub(super) fn content_end(&mut self) -> usize {
+        self.current_verse()
+            .and_then(|verse| verse.content())
+            .map_or(0, |content| content.trim_end().len())
+    }
+
+    /// Appends a word with lexical information
+    pub(super) fn push_word(
+        &mut self,
+        text: &str,
+        strong: Option<String>,
+        lemma: Option<String>,
+        morph: Option<String>,
+    ) {
+        let text = collapse_whitespace(text);
+        self.push_text(&text);
+        let end = self.content_end();
+        let word = TaggedWord {
+            range: end.saturating_sub(text.len())..end,
+            strong,
+            lemma,
+            morph,
+        };
+        if let Some(verse) = self.current_verse() {
+            verse.markup_mut().words.push(word);
+        }
+    }
+
+    /// - Tags the word that was just pushed as text, for formats where the tags come after it
+    /// - Ex: `beginning<WH7225>` in MySword
+    #[cfg(feature = "sqlite")]
+    pub(super) fn tag_previous_word(&mut self, strong: Option<String>, morph: Option<String>) {
+        let Some(verse) = self.current_verse() else {
+            return;
+        };
+        let content = verse.content().unwrap_or_default().trim_end();
+        let end = content
+            .trim_end_matches(|ch: char| !ch.is_alphanumeric())
+            .len();
+        let start = content[..end]
+            .rfind(|ch: char| !ch.is_alphanumeric())
+            .map_or(0, |index| {
+                index + content[index..].chars().next().unwrap().len_utf8()
+            });
+        if start == end {
+            return;
+        }
+        match verse.markup_mut().words.last_mut() {
+            Some(word) if word.range == (start..end) => {
+                // Ex: `<WH853><WH7225>` is `H853 H7225`
+                word.strong = match (word.strong.take(), strong) {
+                    (Some(previous), Some(strong)) => Some(format!("{previous} {strong}")),
+                    (previous, strong) => strong.or(previous),
+                };
+                word.morph = morph.or(word.morph.take());
+            }
+            _ => verse.markup_mut().words.push(TaggedWord {
+                range: start..end,
+                strong,
+                lemma: None,
+                morph,
+            }),
+        }
+    }
+
+    /// The next text has `style`, until [`Self::end_style`]
+    pub(super) fn start_style(&mut self, style: TextStyle) {
+        let start = self
+            .current_verse()
+            .and_then(|verse| verse.content())
+            .map_or(0, str::len);
+        self.styles.push((style, start));
+    }
+
+    /// - Ends the last `style` that was started
+    /// - Whitespace at the ends isn't part of the style
+    pub(super) fn end_style(&mut self, style: TextStyle) {
+        let Some(index) = self.styles.iter().rposition(|(open, _)| *open == style) else {
+            return;
+        };
+        let (_, start) = self.styles.remove(index);
+        let Some(verse) = self.current_verse() else {
+            return;
+        };
+        let VerseData { content, markup } = verse;
+        let content = content.as_deref().unwrap_or_default();
+        let end = content.trim_end().len();
+        let start = start.min(end);
+        let start = end - content[start..end].trim_start().len();
+        if start >= end {
+            return;
+        }
+        let styles = &mut markup.get_or_insert_with(Box::default).styles;
+        // a style that was closed and opened again (like at the end of a line in USFM) is 1 style
+        let previous = styles.iter_mut().find(|styled| {
+            styled.style == style
+                && styled.range.end <= start
+                && content[styled.range.end..start].trim().is_empty()
+        });
+        match previous {
+            Some(previous) => previous.range.end = end,
+            None => styles.push(StyledText {
+                range: start..end,
+                style,
+            }),
+        }
+    }
+
+    /// Adds a note to the current verse (the whitespace of `origin` and `text` is collapsed)
+    pub(super) fn push_note(&mut self, kind: NoteKind, offset: usize, origin: &str, text: &str) {
+        let origin = collapse_whitespace(origin);
+        let note = Note {
+            offset,
+            origin: (!origin.is_empty()).then_some(origin),
+            text: collapse_whitespace(text),
+        };
+        if let Some(verse) = self.current_verse() {
+            match kind {
+                NoteKind::Footnote => verse.markup_mut().footnotes.push(note),
+                NoteKind::CrossReference => verse.markup_mut().cross_references.push(note),
+            }
+        }
+    }
+
+    /// **NOTE: EVERYTHING IS INDEX 0**
+    pub(super) fn finish(mut self) -> Vec<Vec<VerseData>> {
+        self.finish_verse();
+        self.chapters
+    }
+}
+
+/// A piece of a verse, for writing it back out with its markup
+#[derive(Debug, PartialEq)]
+pub(super) enum Markup<'a> {
+    Text(&'a str),
+    Paragraph(ParagraphStyle),
+    WordStart(&'a TaggedWord),
+    WordEnd(&'a TaggedWord),
+    StyleStart(TextStyle),
+    StyleEnd(TextStyle),
+    Footnote(&'a Note),
+    CrossReference(&'a Note),
+}
+
+impl Markup<'_> {
+    /// The order of markup at the same offset, so that notes come after the word they are on
+    fn order(&self) -> u8 {
+        match self {
+            Markup::WordEnd(_) => 0,
+            Markup::StyleEnd(_) => 1,
+            Markup::Footnote(_) | Markup::CrossReference(_) => 2,
+            Markup::Paragraph(_) => 3,
+            Markup::StyleStart(_) => 4,
+            Markup::WordStart(_) => 5,
+            Markup::Text(_) => 6,
+        }
+    }
+}
+
+/// - The content of a verse split up by its paragraph breaks, words, styles, and notes
+/// - Ex: `In the beginning` with a footnote on `beginning` is
+///   `[Text("In the beginning"), Footnote(...)]`
+pub(super) fn verse_markup(verse: &VerseData) -> Vec<Markup<'_>> {
+    let content = verse.content().unwrap_or_default();
+    let mut markup = vec![];
+    for paragraph in verse.paragraphs().iter() {
+        markup.push((paragraph.offset, Markup::Paragraph(paragraph.style)));
+    }
+    for word in verse.words().iter() {
+        markup.push((word.range.start, Markup::WordStart(word)));
+        markup.push((word.range.end, Markup::WordEnd(word)));
+    }
+    // longer styles start first and end last, so that they are nested
+    let mut styles = verse.styles().iter().collect::<Vec<_>>();
+    styles.sort_by_key(|styled| (styled.range.start, std::cmp::Reverse(styled.range.end)));
+    for styled in styles.iter() {
+        markup.push((styled.range.start, Markup::StyleStart(styled.style)));
+    }
+    for styled in styles.iter().rev() {
+        markup.push((styled.range.end, Markup::StyleEnd(styled.style)));
+    }
+    for note in verse.footnotes().iter() {
+        markup.push((note.offset, Markup::Footnote(note)));
+    }
+    for note in verse.cross_references().iter() {
+        markup.push((note.offset, Markup::CrossReference(note)));
+    }
+    markup.sort_by_key(|(offset, markup)| (*offset, markup.order()));
+
+    let mut pieces = vec![];
+    let mut written = 0;
+    for (offset, markup) in markup {
+        let offset = offset.clamp(written, content.len());
+        if let Some(text) = content.get(written..offset).filter(|text| !text.is_empty()) {
+            pieces.push(Markup::Text(text));
+            written = offset;
+        }
+        pieces.push(markup);
+    }
+    if let Some(text) = content.get(written..).filter(|text| !text.is_empty()) {
+        pieces.push(Markup::Text(text));
+    }
+    pieces
+}
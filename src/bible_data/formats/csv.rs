@@ -0,0 +1,273 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bible_data::bible_data::{BibleData, BookData, VerseData};
+
+use super::{
+    json::JSONTranslation,
+    osis,
+    parse::{translation_from_path, ExportBibleData, ParseBibleData},
+    usfm,
+};
+
+/// The English book names, in order (so the book id is the index + 1)
+pub const BOOK_NAMES: [&str; 66] = [
+    "Genesis",
+    "Exodus",
+    "Leviticus",
+    "Numbers",
+    "Deuteronomy",
+    "Joshua",
+    "Judges",
+    "Ruth",
+    "1 Samuel",
+    "2 Samuel",
+    "1 Kings",
+    "2 Kings",
+    "1 Chronicles",
+    "2 Chronicles",
+    "Ezra",
+    "Nehemiah",
+    "Esther",
+    "Job",
+    "Psalms",
+    "Proverbs",
+    "Ecclesiastes",
+    "Song of Solomon",
+    "Isaiah",
+    "Jeremiah",
+    "Lamentations",
+    "Ezekiel",
+    "Daniel",
+    "Hosea",
+    "Joel",
+    "Amos",
+    "Obadiah",
+    "Jonah",
+    "Micah",
+    "Nahum",
+    "Habakkuk",
+    "Zephaniah",
+    "Haggai",
+    "Zechariah",
+    "Malachi",
+    "Matthew",
+    "Mark",
+    "Luke",
+    "John",
+    "Acts",
+    "Romans",
+    "1 Corinthians",
+    "2 Corinthians",
+    "Galatians",
+    "Ephesians",
+    "Philippians",
+    "Colossians",
+    "1 Thessalonians",
+    "2 Thessalonians",
+    "1 Timothy",
+    "2 Timothy",
+    "Titus",
+    "Philemon",
+    "Hebrews",
+    "James",
+    "1 Peter",
+    "2 Peter",
+    "1 John",
+    "2 John",
+    "3 John",
+    "Jude",
+    "Revelation",
+];
+
+/// - The book id (where Genesis = 1) of the `book` column
+/// - Ex: `43`, `John` or `JHN` are all 43
+fn csv_book_id(book: &str) -> Option<usize> {
+    let book = book.trim();
+    match book.parse::<usize>() {
+        Ok(id) => (1..=BOOK_NAMES.len()).contains(&id).then_some(id),
+        Err(_) => BOOK_NAMES
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(book))
+            .map(|index| index + 1)
+            .or_else(|| osis::book_id(book))
+            .or_else(|| usfm::book_id(book)),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CSVRow {
+    book: String,
+    chapter: usize,
+    verse: usize,
+    text: String,
+}
+
+/**
+A translation as a flat CSV with the header `book,chapter,verse,text`
+
+- `book` is the book id (where Genesis = 1), an English book name, or an OSIS or USFM book code,
+  and books outside of the 66 are skipped
+- Books are named in English, since a CSV only has the content
+- A verse with empty `text` has no content (like the second verse of a combined verse)
+- CSV has no translation metadata, so [`ParseBibleData::parse_file`] names the translation after
+  the file
+*/
+pub struct CSVBible {
+    pub translation: JSONTranslation,
+    pub books: Vec<BookData>,
+}
+
+impl CSVBible {
+    pub fn parse(
+        translation: JSONTranslation,
+        source: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut reader = csv::Reader::from_reader(source.as_bytes());
+        let mut books = BTreeMap::<usize, Vec<Vec<VerseData>>>::new();
+        for row in reader.deserialize() {
+            let row: CSVRow = row?;
+            let Some(id) = csv_book_id(&row.book) else {
+                continue;
+            };
+            if row.chapter == 0 || row.verse == 0 {
+                return Err(format!(
+                    "{} {}:{} isn't a verse (chapters and verses start at 1)",
+                    row.book, row.chapter, row.verse
+                )
+                .into());
+            }
+            let chapters = books.entry(id).or_default();
+            if chapters.len() < row.chapter {
+                chapters.resize_with(row.chapter, Vec::new);
+            }
+            let verses = &mut chapters[row.chapter - 1];
+            if verses.len() < row.verse {
+                verses.resize_with(row.verse, VerseData::default);
+            }
+            verses[row.verse - 1] = VerseData::new((!row.text.is_empty()).then_some(row.text));
+        }
+        let books = books
+            .into_iter()
+            .map(|(id, chapters)| BookData {
+                id,
+                name: BOOK_NAMES[id - 1].to_string(),
+                abbreviations: vec![
+                    osis::BOOK_CODES[id - 1].to_string(),
+                    usfm::BOOK_CODES[id - 1].to_string(),
+                ],
+                chapters,
+            })
+            .collect();
+        Ok(Self { translation, books })
+    }
+
+    /// - Every verse is a row, with the book id in the `book` column
+    /// - Only the content of each verse is written
+    pub fn to_csv(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        for book in self.books.iter() {
+            for (chapter, verses) in book.chapters.iter().enumerate() {
+                for (verse, data) in verses.iter().enumerate() {
+                    writer.serialize(CSVRow {
+                        book: book.id.to_string(),
+                        chapter: chapter + 1,
+                        verse: verse + 1,
+                        text: data.content().unwrap_or_default().to_string(),
+                    })?;
+                }
+            }
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+}
+
+impl ParseBibleData for CSVBible {
+    fn parse_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(translation_from_path(path), &contents)
+    }
+
+    fn as_bible_data(self) -> Result<BibleData, Box<dyn std::error::Error>> {
+        BibleData::from_books(self.translation, self.books)
+    }
+}
+
+impl ExportBibleData for CSVBible {
+    fn from_bible_data(bible: &BibleData) -> Self {
+        Self {
+            translation: bible.translation.clone(),
+            books: bible.to_books(),
+        }
+    }
+
+    fn write_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_csv()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::formats::json::test_bible_data;
+
+    use super::{super::parse::assert_round_trip, *};
+
+    const SOURCE: &str = r#"book,chapter,verse,text
+Genesis,1,1,"In the beginning God created the heaven and the earth."
+JHN,3,16,"For God so loved the world, that he gave his only begotten Son"
+43,3,17,
+Tobit,1,1,Tobit
+"#;
+
+    #[test]
+    fn parse_csv() {
+        let bible = CSVBible::parse(translation_from_path(Path::new("test.csv")), SOURCE)
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        assert_eq!(bible.translation.abbreviation, "TEST");
+        assert_eq!(bible.get_book(43).unwrap().get_name(), "John");
+        assert_eq!(bible.get_book_id("jhn"), Some(43));
+        assert_eq!(
+            bible.get_verse(43, 3, 16).unwrap().get_content(),
+            Some("For God so loved the world, that he gave his only begotten Son")
+        );
+        assert_eq!(bible.get_verse(43, 3, 17).unwrap().get_content(), None);
+        assert_eq!(bible.verse_count(43, 3), Some(17));
+        assert_eq!(bible.chapter_count(1), Some(1));
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let translation = translation_from_path(Path::new("test.csv"));
+        let before = CSVBible::parse(translation.clone(), SOURCE)
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        let csv = CSVBible::from_bible_data(&before).to_csv().unwrap();
+        let after = CSVBible::parse(translation, &csv)
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        assert_round_trip(&before, &after);
+    }
+
+    #[test]
+    fn json_to_csv() {
+        let json = test_bible_data();
+        let csv = CSVBible::from_bible_data(&json).to_csv().unwrap();
+        let bible = CSVBible::parse(json.translation.clone(), &csv)
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        assert_eq!(bible.get_book(19).unwrap().get_name(), "Psalms");
+        for (book, chapter, verse) in [(1, 1, 1), (19, 23, 1), (43, 3, 16), (65, 1, 25)] {
+            assert_eq!(
+                bible.get_verse(book, chapter, verse).unwrap().get_content(),
+                json.get_verse(book, chapter, verse).unwrap().get_content()
+            );
+        }
+    }
+}
@@ -1,26 +1,30 @@
 use std::path::Path;
 
-use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     api::bible_api::BibleAPI,
     bible_data::bible_data::{
-        AbbreviationsToBookId, BibleContents, BibleData, BookDataList, BookIdToName,
-        ChapterDataList, ReferenceArray, VerseData, VerseDataList,
+        BibleContents, BibleData, BookData, Note, ParagraphBreak, StyledText, VerseData,
     },
 };
 
-use super::parse::ParseBibleData;
+use super::{
+    super::versification::Versification,
+    parse::{ExportBibleData, ParseBibleData},
+};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct JSONTranslation {
     pub name: String,
     pub language: String,
     pub abbreviation: String,
+    /// how verses are numbered (the KJV numbering when it isn't specified)
+    #[serde(default)]
+    pub versification: Versification,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct JSONBook {
     /// book id where Genesis = 1
     pub id: usize,
@@ -29,6 +33,35 @@ pub struct JSONBook {
     /// all abbreviations (any case), not necessarily including the book name
     pub abbreviations: Vec<String>,
     pub content: Vec<Vec<Option<String>>>,
+    /// the markup of the verses that have any (most translations don't have any)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markup: Vec<JSONVerseMarkup>,
+}
+
+/// - Everything about a verse besides its content
+/// - The offsets are byte offsets into the content of the verse
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct JSONVerseMarkup {
+    pub chapter: usize,
+    pub verse: usize,
+    /// section headings that come right before this verse
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headings: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paragraphs: Vec<ParagraphBreak>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub styles: Vec<StyledText>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub footnotes: Vec<Note>,
+}
+
+impl JSONVerseMarkup {
+    fn is_empty(&self) -> bool {
+        self.headings.is_empty()
+            && self.paragraphs.is_empty()
+            && self.styles.is_empty()
+            && self.footnotes.is_empty()
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -44,71 +77,177 @@ impl ParseBibleData for JSONBible {
     }
 
     fn as_bible_data(self) -> Result<BibleData, Box<dyn std::error::Error>> {
-        let mut abbreviations_to_book_id = AbbreviationsToBookId::new();
-        let mut book_id_to_name = BookIdToName::new();
-        let mut reference_array = ReferenceArray::new();
-        let mut bible_contents: Vec<ChapterDataList> = Vec::new();
-
-        // let translation = self.translation;
-
+        let mut books = vec![];
         for book in self.bible.into_iter() {
-            // let mut book_contents: Vec<Vec<String>> = vec![];
-            let mut book_contents: Vec<VerseDataList> = vec![];
-            book_id_to_name.insert(book.id, book.book.clone());
-            abbreviations_to_book_id.insert(book.book.clone().to_lowercase(), book.id);
-            for abbreviation in book.abbreviations.iter().cloned() {
-                abbreviations_to_book_id.insert(abbreviation.to_lowercase(), book.id);
+            let mut chapters = book
+                .content
+                .into_iter()
+                .map(|verses| verses.into_iter().map(VerseData::new).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            for markup in book.markup.into_iter() {
+                let verse = markup
+                    .chapter
+                    .checked_sub(1)
+                    .and_then(|chapter| chapters.get_mut(chapter))
+                    .and_then(|verses| verses.get_mut(markup.verse.checked_sub(1)?))
+                    .ok_or_else(|| {
+                        format!(
+                            "{} {}:{} has markup, but isn't a verse",
+                            book.book, markup.chapter, markup.verse
+                        )
+                    })?
+                    .markup_mut();
+                verse.headings.extend(markup.headings);
+                verse.paragraphs.extend(markup.paragraphs);
+                verse.styles.extend(markup.styles);
+                verse.footnotes.extend(markup.footnotes);
             }
-            let mut chapter_array = Vec::new();
-            for (_, verses) in book.content.into_iter().enumerate() {
-                chapter_array.push(verses.len());
-                let verses = VerseDataList(
-                    verses
-                        .into_iter()
-                        .map(|content| VerseData { content })
-                        .collect::<Vec<_>>(),
-                );
-                book_contents.push(verses);
-            }
-            reference_array.push(chapter_array);
-            bible_contents.push(ChapterDataList(book_contents));
+            books.push(BookData {
+                id: book.id,
+                name: book.book,
+                abbreviations: book.abbreviations,
+                chapters,
+            });
         }
+        BibleData::from_books(self.translation, books)
+    }
+}
 
-        let books_pattern: String = join("|", abbreviations_to_book_id.keys());
-        // let books_pattern: String = abbreviations_to_book_id
-        //     .keys()
-        //     .map(|key| key.to_string())
-        //     .collect::<Vec<String>>()
-        //     .join("|");
-        // I added the period so that people can use it in abbreviations
-        let book_regex = Regex::new(format!(r"\b((?i){books_pattern})\b\.?").as_str())
-            .expect("Failed to compile book_regex.");
-
-        Ok(BibleData {
-            book_regex,
-            // translation,
-            abbreviations_to_book_id,
-            book_id_to_name,
-            reference_array,
-            bible_contents: BookDataList(bible_contents),
-        })
+impl ExportBibleData for JSONBible {
+    fn from_bible_data(bible: &BibleData) -> Self {
+        let books = bible
+            .to_books()
+            .into_iter()
+            .map(|book| JSONBook {
+                id: book.id,
+                book: book.name,
+                abbreviations: book.abbreviations,
+                content: book
+                    .chapters
+                    .iter()
+                    .map(|verses| {
+                        verses
+                            .iter()
+                            .map(|verse| verse.content().map(str::to_string))
+                            .collect()
+                    })
+                    .collect(),
+                markup: book
+                    .chapters
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(chapter, verses)| {
+                        verses
+                            .iter()
+                            .enumerate()
+                            .map(move |(verse, data)| JSONVerseMarkup {
+                                chapter: chapter + 1,
+                                verse: verse + 1,
+                                headings: data.headings().to_vec(),
+                                paragraphs: data.paragraphs().to_vec(),
+                                styles: data.styles().to_vec(),
+                                footnotes: data.footnotes().to_vec(),
+                            })
+                    })
+                    .filter(|markup| !markup.is_empty())
+                    .collect(),
+            })
+            .collect();
+        JSONBible {
+            translation: bible.translation.clone(),
+            bible: books,
+        }
+    }
+
+    /// Cross references and the words of each verse aren't written, since this schema doesn't have
+    /// them
+    fn write_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
     }
 }
 
-use std::fmt::Write;
-/// https://users.rust-lang.org/t/connecting-joining-string-slices-without-a-temp-vec/1811/6
-fn join<T>(sep: &str, iter: T) -> String
-where
-    T: IntoIterator,
-    T::Item: std::fmt::Display,
-{
-    let mut out = String::new();
-    let mut iter = iter.into_iter();
-    if let Some(fst) = iter.next() {
-        write!(&mut out, "{}", fst).unwrap();
-        for elt in iter {
-            write!(&mut out, "{}{}", sep, elt).unwrap();
+/// The (mostly placeholder) KJV fixture used by the tests
+#[cfg(test)]
+pub(crate) fn test_json_bible() -> JSONBible {
+    let path = Path::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/kjv.json"
+    ));
+    JSONBible::parse_file(path).unwrap()
+}
+
+#[cfg(test)]
+pub(crate) fn test_bible_data() -> BibleData {
+    test_json_bible().as_bible_data().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::parse::assert_round_trip, *};
+
+    #[test]
+    fn json_round_trip() {
+        let before = test_bible_data();
+        let path = std::env::temp_dir().join("bible_api_json_round_trip.json");
+        JSONBible::from_bible_data(&before)
+            .write_file(&path)
+            .unwrap();
+        let after = JSONBible::parse_file(&path)
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_round_trip(&before, &after);
+    }
+
+    #[test]
+    fn json_markup() {
+        let json: JSONBible = serde_json::from_str(
+            r#"{
+  "translation": { "name": "Test", "language": "English", "abbreviation": "TST" },
+  "bible": [
+    {
+      "id": 43,
+      "book": "John",
+      "abbreviations": ["jn"],
+      "content": [["In the beginning was the Word.", "He said, Follow me."]],
+      "markup": [
+        {
+          "chapter": 1,
+          "verse": 1,
+          "headings": ["The Word Became Flesh"],
+          "paragraphs": [{ "offset": 0, "style": "Prose" }]
+        },
+        {
+          "chapter": 1,
+          "verse": 2,
+          "paragraphs": [{ "offset": 9, "style": { "Poetry": 2 } }],
+          "styles": [{ "range": { "start": 9, "end": 18 }, "style": "WordsOfJesus" }],
+          "footnotes": [{ "offset": 18, "origin": "1:2", "text": "Or, Come after me" }]
         }
+      ]
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+        let bible = json.clone().as_bible_data().unwrap();
+        let verse = bible.get_verse(43, 1, 1).unwrap().get_data();
+        assert_eq!(verse.headings(), ["The Word Became Flesh"]);
+        assert!(verse.starts_paragraph());
+        let bible_verse = bible.get_verse(43, 1, 2).unwrap();
+        let verse = bible_verse.get_data();
+        assert!(!verse.starts_paragraph());
+        assert_eq!(
+            &bible_verse.get_content().unwrap()[verse.styles()[0].range.clone()],
+            "Follow me"
+        );
+        assert_eq!(verse.footnotes()[0].text, "Or, Come after me");
+        assert_eq!(JSONBible::from_bible_data(&bible).bible, json.bible);
+
+        let mut json = json;
+        json.bible[0].markup[0].verse = 3;
+        assert!(json.as_bible_data().is_err());
     }
-    out
 }
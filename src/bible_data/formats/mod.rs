@@ -1,2 +1,10 @@
+mod builder;
+pub mod csv;
 pub mod json;
+pub mod osis;
 pub mod parse;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod usfm;
+pub mod usx;
+pub mod zefania;
@@ -0,0 +1,821 @@
+use std::path::Path;
+
+use itertools::Itertools;
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::bible_data::{
+    bible_data::{BibleData, BookData, ParagraphStyle, TextStyle, VerseData},
+    versification::Versification,
+};
+
+use super::{
+    builder::{verse_markup, BookBuilder, Markup, NoteKind},
+    json::JSONTranslation,
+    parse::{ExportBibleData, ParseBibleData},
+};
+
+/// The OSIS book names, in order (so the book id is the index + 1)
+pub const BOOK_CODES: [&str; 66] = [
+    "Gen", "Exod", "Lev", "Num", "Deut", "Josh", "Judg", "Ruth", "1Sam", "2Sam", "1Kgs", "2Kgs",
+    "1Chr", "2Chr", "Ezra", "Neh", "Esth", "Job", "Ps", "Prov", "Eccl", "Song", "Isa", "Jer",
+    "Lam", "Ezek", "Dan", "Hos", "Joel", "Amos", "Obad", "Jonah", "Mic", "Nah", "Hab", "Zeph",
+    "Hag", "Zech", "Mal", "Matt", "Mark", "Luke", "John", "Acts", "Rom", "1Cor", "2Cor", "Gal",
+    "Eph", "Phil", "Col", "1Thess", "2Thess", "1Tim", "2Tim", "Titus", "Phlm", "Heb", "Jas",
+    "1Pet", "2Pet", "1John", "2John", "3John", "Jude", "Rev",
+];
+
+/// - The book id (where Genesis = 1) of an OSIS book name (any case)
+/// - Ex: `John` is 43
+pub fn book_id(code: &str) -> Option<usize> {
+    BOOK_CODES
+        .iter()
+        .position(|book| book.eq_ignore_ascii_case(code))
+        .map(|index| index + 1)
+}
+
+/// - Ex: `Gen.1.1` is `("Gen", 1, 1)`
+/// - A reference to a chapter (like `Gen.1`) has verse 0
+pub(crate) fn parse_osis_id(id: &str) -> Option<(&str, usize, usize)> {
+    // `Gen.1.1!a` marks part of a verse, and `KJV:Gen.1.1` has the work
+    let id = id.split('!').next()?;
+    let id = id.rsplit(':').next()?;
+    let mut parts = id.split('.');
+    let book = parts.next()?;
+    let chapter = parts.next()?.parse().ok()?;
+    let verse = parts.next().map_or(Some(0), |verse| verse.parse().ok())?;
+    Some((book, chapter, verse))
+}
+
+/**
+A translation in [OSIS](https://crosswire.org/osis/) XML, like the modules from CrossWire
+
+- Verses can be containers (`<verse osisID="Gen.1.1">...</verse>`) or milestones
+  (`<verse sID="Gen.1.1" osisID="Gen.1.1"/>...<verse eID="Gen.1.1"/>`), and chapters and books can
+  be either as well
+- Titles, paragraphs, poetry lines (`<l level="2">`), notes, `<w lemma="strong:H7225">`, and
+  styles (like `<q who="Jesus">` and `<divineName>`) are kept on each
+  [`VerseData`](crate::bible_data::bible_data::VerseData)
+- The translation comes from the `<work>` in the header
+- Books outside of the 66 (like the deuterocanon) are skipped
+*/
+pub struct OSISBible {
+    pub translation: JSONTranslation,
+    pub books: Vec<BookData>,
+}
+
+impl ParseBibleData for OSISBible {
+    fn parse_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    fn as_bible_data(self) -> Result<BibleData, Box<dyn std::error::Error>> {
+        BibleData::from_books(self.translation, self.books)
+    }
+}
+
+impl ExportBibleData for OSISBible {
+    fn from_bible_data(bible: &BibleData) -> Self {
+        Self {
+            translation: bible.translation.clone(),
+            books: bible.to_books(),
+        }
+    }
+
+    fn write_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_osis())?;
+        Ok(())
+    }
+}
+
+/// What text is being read into (besides verse content)
+enum Capture {
+    /// the text of a `<title>` in the header, or a book title
+    WorkTitle,
+    Language,
+    RefSystem,
+    BookTitle,
+    Heading,
+    Word {
+        strong: Option<String>,
+        lemma: Option<String>,
+        morph: Option<String>,
+    },
+    Note {
+        kind: NoteKind,
+        offset: usize,
+        origin: String,
+    },
+}
+
+/// Elements whose text isn't read
+const SKIPPED: [&[u8]; 3] = [b"rdg", b"figure", b"index"];
+
+#[derive(Default)]
+struct OSISParser {
+    abbreviation: Option<String>,
+    name: Option<String>,
+    language: Option<String>,
+    versification: Option<String>,
+    in_header: bool,
+    /// whether the first `<work>` of the header (the one that describes this text) has ended
+    read_work: bool,
+    books: Vec<BookData>,
+    /// the book id, name, and content of the current book
+    book: Option<(usize, Option<String>, BookBuilder)>,
+    /// `true` for each open `<div>` that is a book
+    divs: Vec<bool>,
+    /// the element (and its text) that is being captured
+    capture: Option<(Vec<u8>, usize, Capture, String)>,
+    depth: usize,
+    /// the depth of an element whose text is skipped
+    skip_depth: Option<usize>,
+    /// the style of each open `<q>`, `<divineName>`, `<hi>`, and `<seg>`
+    styles: Vec<Option<TextStyle>>,
+    /// the `sID` and style of each milestone that has started (Ex: `<q who="Jesus" sID="q1"/>`)
+    style_milestones: Vec<(String, TextStyle)>,
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+/// - Ex: `<divineName>` is [`TextStyle::DivineName`]
+/// - Quotes of anyone but Jesus aren't styled
+fn text_style(name: &[u8], element: &BytesStart) -> Option<TextStyle> {
+    let kind = attribute(element, "type");
+    match name {
+        b"q" if attribute(element, "who").as_deref() == Some("Jesus") => {
+            Some(TextStyle::WordsOfJesus)
+        }
+        b"divineName" => Some(TextStyle::DivineName),
+        b"hi" if kind.as_deref() == Some("small-caps") => Some(TextStyle::SmallCaps),
+        b"seg" if kind.as_deref() == Some("x-selah") => Some(TextStyle::Selah),
+        _ => None,
+    }
+}
+
+impl OSISParser {
+    fn start_book(&mut self, code: &str) {
+        self.finish_book();
+        self.book = book_id(code).map(|id| (id, None, BookBuilder::default()));
+    }
+
+    fn finish_book(&mut self) {
+        if let Some((id, name, builder)) = self.book.take() {
+            self.books.push(BookData {
+                id,
+                name: name.unwrap_or_else(|| BOOK_CODES[id - 1].to_string()),
+                abbreviations: vec![BOOK_CODES[id - 1].to_string()],
+                chapters: builder.finish(),
+            });
+        }
+    }
+
+    fn builder(&mut self) -> Option<&mut BookBuilder> {
+        self.book.as_mut().map(|(_, _, builder)| builder)
+    }
+
+    /// `osisID` can have several verses (like `Gen.1.1 Gen.1.2`) when they are combined
+    fn start_verse(&mut self, osis_id: &str) {
+        let verses = osis_id
+            .split_whitespace()
+            .filter_map(parse_osis_id)
+            .collect::<Vec<_>>();
+        let (Some(first), Some(last)) = (verses.first(), verses.last()) else {
+            return;
+        };
+        let book = self.book.as_ref().map(|(id, _, _)| *id);
+        if book != book_id(first.0) && book_id(first.0).is_some() {
+            // a verse outside of a book `<div>`
+            self.start_book(first.0);
+        }
+        let (chapter, start) = (first.1, first.2);
+        let end = if last.1 == chapter { last.2 } else { start };
+        if let Some(builder) = self.builder() {
+            builder.start_verse(chapter, start, end);
+        }
+    }
+
+    fn start(&mut self, element: &BytesStart, empty: bool) {
+        let name = element.local_name().as_ref().to_vec();
+        if !empty {
+            self.depth += 1;
+        }
+        if self.skip_depth.is_some() {
+            return;
+        }
+        if SKIPPED.contains(&name.as_slice()) && !empty {
+            self.skip_depth = Some(self.depth);
+            return;
+        }
+        // the text of the elements inside of a captured element is captured with it, except for
+        // notes (like a footnote in a heading)
+        if self.capture.is_some() {
+            let in_note = matches!(self.capture, Some((_, _, Capture::Note { .. }, _)));
+            if name == b"note" && !in_note && !empty {
+                self.skip_depth = Some(self.depth);
+            }
+            return;
+        }
+        let milestone_end = attribute(element, "eID").is_some();
+        match name.as_slice() {
+            b"header" => self.in_header = !empty,
+            b"osisText" => {
+                self.abbreviation = attribute(element, "osisIDWork");
+                self.language = attribute(element, "xml:lang");
+            }
+            b"work" if self.in_header && !self.read_work => {}
+            b"title" | b"language" | b"refSystem"
+                if self.in_header && !self.read_work && !empty =>
+            {
+                let capture = match name.as_slice() {
+                    b"title" => Capture::WorkTitle,
+                    b"language" => Capture::Language,
+                    _ => Capture::RefSystem,
+                };
+                self.capture = Some((name, self.depth, capture, String::new()));
+            }
+            _ if self.in_header => {}
+            b"div" => {
+                let is_book = attribute(element, "type").as_deref() == Some("book");
+                if is_book {
+                    if milestone_end {
+                        self.finish_book();
+                    } else if let Some(code) = attribute(element, "osisID") {
+                        self.start_book(&code);
+                    }
+                } else if attribute(element, "type").as_deref() == Some("paragraph")
+                    && !milestone_end
+                {
+                    self.paragraph(ParagraphStyle::Prose);
+                }
+                if !empty {
+                    self.divs.push(is_book);
+                }
+            }
+            b"chapter" => {
+                let chapter = attribute(element, "osisID")
+                    .and_then(|id| parse_osis_id(&id).map(|(_, chapter, _)| chapter));
+                if let Some(builder) = self.builder() {
+                    match chapter {
+                        Some(chapter) if !milestone_end => builder.start_chapter(chapter),
+                        _ => builder.finish_verse(),
+                    }
+                }
+            }
+            b"verse" => {
+                if milestone_end {
+                    if let Some(builder) = self.builder() {
+                        builder.finish_verse();
+                    }
+                } else if let Some(id) = attribute(element, "osisID") {
+                    self.start_verse(&id);
+                }
+            }
+            b"title" if !empty => {
+                let is_book_title = self.book.as_mut().is_some_and(|(_, name, builder)| {
+                    name.is_none() && !builder.in_verse() && {
+                        // `<title type="main" short="Genesis">`
+                        *name = attribute(element, "short");
+                        attribute(element, "type").as_deref() == Some("main")
+                    }
+                });
+                let capture = if is_book_title {
+                    Capture::BookTitle
+                } else {
+                    Capture::Heading
+                };
+                self.capture = Some((name, self.depth, capture, String::new()));
+            }
+            b"p" => self.paragraph(ParagraphStyle::Prose),
+            b"milestone"
+                if attribute(element, "type")
+                    .is_some_and(|kind| kind == "x-p" || kind == "paragraph") =>
+            {
+                self.paragraph(ParagraphStyle::Prose)
+            }
+            b"l" if !milestone_end => {
+                let level = attribute(element, "level")
+                    .and_then(|level| level.parse().ok())
+                    .unwrap_or(1);
+                self.paragraph(ParagraphStyle::Poetry(level));
+            }
+            b"w" if !empty => {
+                let lemmas = attribute(element, "lemma").unwrap_or_default();
+                let values = |prefix: &str| {
+                    let values = lemmas
+                        .split_whitespace()
+                        .filter_map(|lemma| lemma.strip_prefix(prefix))
+                        .map(|value| value.split_once(':').map_or(value, |(_, value)| value))
+                        .collect::<Vec<_>>();
+                    (!values.is_empty()).then(|| values.join(" "))
+                };
+                let capture = Capture::Word {
+                    strong: values("strong:"),
+                    lemma: values("lemma"),
+                    morph: attribute(element, "morph"),
+                };
+                self.capture = Some((name, self.depth, capture, String::new()));
+            }
+            b"q" | b"divineName" | b"hi" | b"seg" => {
+                let style = text_style(&name, element);
+                if !empty {
+                    self.styles.push(style);
+                    if let (Some(style), Some(builder)) = (style, self.builder()) {
+                        builder.start_style(style);
+                    }
+                } else if let (Some(style), Some(id)) = (style, attribute(element, "sID")) {
+                    self.style_milestones.push((id, style));
+                    if let Some(builder) = self.builder() {
+                        builder.start_style(style);
+                    }
+                } else if let Some(id) = attribute(element, "eID") {
+                    let Some(index) = self.style_milestones.iter().position(|(sid, _)| *sid == id)
+                    else {
+                        return;
+                    };
+                    let (_, style) = self.style_milestones.remove(index);
+                    if let Some(builder) = self.builder() {
+                        builder.end_style(style);
+                    }
+                }
+            }
+            b"note" if !empty => {
+                let kind = match attribute(element, "type").as_deref() {
+                    Some("crossReference") => NoteKind::CrossReference,
+                    _ => NoteKind::Footnote,
+                };
+                // `Gen.1.1` is `1:1`
+                let origin = attribute(element, "osisRef")
+                    .and_then(|id| {
+                        parse_osis_id(&id).map(|(_, chapter, verse)| format!("{chapter}:{verse}"))
+                    })
+                    .unwrap_or_default();
+                let offset = self.builder().map_or(0, |builder| builder.content_end());
+                let capture = Capture::Note {
+                    kind,
+                    offset,
+                    origin,
+                };
+                self.capture = Some((name, self.depth, capture, String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn paragraph(&mut self, style: ParagraphStyle) {
+        if let Some(builder) = self.builder() {
+            builder.paragraph(style);
+        }
+    }
+
+    fn end(&mut self, name: &[u8]) {
+        let depth = self.depth;
+        self.depth = self.depth.saturating_sub(1);
+        if let Some(skip_depth) = self.skip_depth {
+            if skip_depth == depth {
+                self.skip_depth = None;
+            }
+            return;
+        }
+        if self
+            .capture
+            .as_ref()
+            .is_some_and(|(element, capture_depth, _, _)| {
+                element == name && *capture_depth == depth
+            })
+        {
+            let (_, _, capture, text) = self.capture.take().unwrap();
+            return self.finish_capture(capture, text);
+        }
+        if self.capture.is_some() {
+            return;
+        }
+        match name {
+            b"header" => self.in_header = false,
+            b"work" if self.in_header => self.read_work = true,
+            b"div" => {
+                let is_book = self.divs.pop() == Some(true);
+                if is_book {
+                    self.finish_book();
+                }
+            }
+            b"verse" | b"chapter" => {
+                if let Some(builder) = self.builder() {
+                    builder.finish_verse();
+                }
+            }
+            b"q" | b"divineName" | b"hi" | b"seg" => {
+                if let (Some(Some(style)), Some(builder)) = (self.styles.pop(), self.builder()) {
+                    builder.end_style(style);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish_capture(&mut self, capture: Capture, text: String) {
+        let text = text.trim().to_string();
+        match capture {
+            Capture::WorkTitle => self.name = Some(text),
+            Capture::Language => self.language = Some(text),
+            Capture::RefSystem => self.versification = Some(text),
+            Capture::BookTitle => {
+                if let Some((_, name, _)) = self.book.as_mut() {
+                    name.get_or_insert(text);
+                }
+            }
+            Capture::Heading => {
+                if let Some(builder) = self.builder() {
+                    builder.heading(&text);
+                }
+            }
+            Capture::Word {
+                strong,
+                lemma,
+                morph,
+            } => {
+                if let Some(builder) = self.builder() {
+                    builder.push_word(&text, strong, lemma, morph);
+                }
+            }
+            Capture::Note {
+                kind,
+                offset,
+                origin,
+            } => {
+                if let Some(builder) = self.builder() {
+                    builder.push_note(kind, offset, &origin, &text);
+                }
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skip_depth.is_some() {
+            return;
+        }
+        if let Some((_, _, _, captured)) = self.capture.as_mut() {
+            return captured.push_str(text);
+        }
+        if let Some(builder) = self.builder() {
+            builder.push_text(text);
+        }
+    }
+}
+
+impl OSISBible {
+    pub fn parse(source: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut reader = Reader::from_str(source);
+        let mut parser = OSISParser::default();
+        loop {
+            match reader.read_event()? {
+                Event::Start(element) => parser.start(&element, false),
+                Event::Empty(element) => parser.start(&element, true),
+                Event::End(element) => parser.end(element.local_name().as_ref()),
+                Event::Text(text) => parser.text(&text.unescape()?),
+                Event::CData(text) => parser.text(&String::from_utf8_lossy(&text)),
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        parser.finish_book();
+
+        let abbreviation = parser.abbreviation.unwrap_or_default();
+        let translation = JSONTranslation {
+            name: parser.name.unwrap_or(abbreviation.clone()),
+            language: parser.language.unwrap_or_default(),
+            abbreviation,
+            // `Bible.KJV` is `KJV`
+            versification: parser
+                .versification
+                .map(|system| Versification::from(system.trim_start_matches("Bible.").to_string()))
+                .unwrap_or_default(),
+        };
+        Ok(Self {
+            translation,
+            books: parser.books,
+        })
+    }
+}
+
+/// - Ex: `<milestone type="x-p"/>In the beginning <w lemma="strong:H430">God</w>`
+/// - A note's `osisRef` is only written when its origin is a verse (like `1:1`)
+fn write_verse(osis: &mut String, code: &str, verse: &VerseData) {
+    for piece in verse_markup(verse) {
+        match piece {
+            Markup::Text(text) => osis.push_str(&escape(text)),
+            Markup::Paragraph(ParagraphStyle::Prose) => osis.push_str(r#"<milestone type="x-p"/>"#),
+            Markup::Paragraph(ParagraphStyle::Poetry(level)) => {
+                osis.push_str(&format!(r#"<l level="{level}"/>"#))
+            }
+            Markup::WordStart(word) => {
+                let lemmas = [("strong", &word.strong), ("lemma", &word.lemma)]
+                    .into_iter()
+                    .flat_map(|(prefix, values)| {
+                        values
+                            .iter()
+                            .flat_map(|values| values.split_whitespace())
+                            .map(move |value| format!("{prefix}:{value}"))
+                    })
+                    .join(" ");
+                osis.push_str("<w");
+                if !lemmas.is_empty() {
+                    osis.push_str(&format!(r#" lemma="{}""#, escape(&lemmas)));
+                }
+                if let Some(morph) = word.morph.as_ref() {
+                    osis.push_str(&format!(r#" morph="{}""#, escape(morph)));
+                }
+                osis.push('>');
+            }
+            Markup::WordEnd(_) => osis.push_str("</w>"),
+            Markup::StyleStart(style) => osis.push_str(match style {
+                TextStyle::WordsOfJesus => r#"<q who="Jesus" marker="">"#,
+                TextStyle::DivineName => "<divineName>",
+                TextStyle::SmallCaps => r#"<hi type="small-caps">"#,
+                TextStyle::Selah => r#"<seg type="x-selah">"#,
+            }),
+            Markup::StyleEnd(style) => osis.push_str(match style {
+                TextStyle::WordsOfJesus => "</q>",
+                TextStyle::DivineName => "</divineName>",
+                TextStyle::SmallCaps => "</hi>",
+                TextStyle::Selah => "</seg>",
+            }),
+            Markup::Footnote(note) | Markup::CrossReference(note) => {
+                osis.push_str("<note");
+                if matches!(piece, Markup::CrossReference(_)) {
+                    osis.push_str(r#" type="crossReference""#);
+                }
+                // `1:1` is `Gen.1.1`
+                if let Some((chapter, verse)) = note.origin.as_ref().and_then(|origin| {
+                    let (chapter, verse) = origin.split_once(':')?;
+                    Some((chapter.parse::<usize>().ok()?, verse.parse::<usize>().ok()?))
+                }) {
+                    osis.push_str(&format!(r#" osisRef="{code}.{chapter}.{verse}""#));
+                }
+                osis.push_str(&format!(">{}</note>", escape(&note.text)));
+            }
+        }
+    }
+}
+
+impl OSISBible {
+    /**
+    Writes this translation as OSIS, with container books, chapters and verses
+
+    - The translation is the `<work>` in the header
+    - Abbreviations aren't written, since OSIS books only have their name
+    - Books outside of the 66 are skipped, like when parsing
+    */
+    pub fn to_osis(&self) -> String {
+        let translation = &self.translation;
+        let mut osis = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<osis xmlns="http://www.bibletechnologies.net/2003/OSIS/namespace">
+<osisText osisIDWork="{abbreviation}" xml:lang="{language}">
+<header>
+<work osisWork="{abbreviation}">
+<title>{name}</title>
+<language>{language}</language>
+<refSystem>Bible.{versification}</refSystem>
+</work>
+</header>
+"#,
+            abbreviation = escape(&translation.abbreviation),
+            language = escape(&translation.language),
+            name = escape(&translation.name),
+            versification = escape(translation.versification.to_string()),
+        );
+        for book in self.books.iter() {
+            let Some(code) = BOOK_CODES.get(book.id - 1) else {
+                continue;
+            };
+            let name = escape(&book.name);
+            osis.push_str(&format!(
+                "<div type=\"book\" osisID=\"{code}\">\n<title type=\"main\" short=\"{name}\">{name}</title>\n"
+            ));
+            for (chapter, verses) in book.chapters.iter().enumerate() {
+                let chapter = chapter + 1;
+                osis.push_str(&format!("<chapter osisID=\"{code}.{chapter}\">\n"));
+                for (verse_number, verse) in verses.iter().enumerate() {
+                    for heading in verse.headings() {
+                        osis.push_str(&format!("<title>{}</title>\n", escape(heading)));
+                    }
+                    osis.push_str(&format!(
+                        "<verse osisID=\"{code}.{chapter}.{}\">",
+                        verse_number + 1
+                    ));
+                    write_verse(&mut osis, code, verse);
+                    osis.push_str("</verse>\n");
+                }
+                osis.push_str("</chapter>\n");
+            }
+            osis.push_str("</div>\n");
+        }
+        osis.push_str("</osisText>\n</osis>\n");
+        osis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::bible_data::{Note, ParagraphBreak, StyledText, TaggedWord};
+
+    use super::{super::parse::assert_round_trip, *};
+
+    const CONTAINERS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osis xmlns="http://www.bibletechnologies.net/2003/OSIS/namespace">
+<osisText osisIDWork="TST" xml:lang="en">
+<header>
+  <work osisWork="TST">
+    <title>Test Bible</title>
+    <language>English</language>
+    <refSystem>Bible.KJV</refSystem>
+  </work>
+  <work osisWork="strong"><title>Strong's</title></work>
+</header>
+<div type="book" osisID="Gen">
+  <title type="main" short="Genesis">THE FIRST BOOK OF MOSES</title>
+  <chapter osisID="Gen.1">
+    <title>The Creation</title>
+    <p>
+      <verse osisID="Gen.1.1"><w lemma="strong:H07225" morph="oshm:HR/Ncfsa">In the beginning</w> God created the heaven<note type="study" osisRef="Gen.1.1">Or, <catchWord>heavens</catchWord></note> and the earth.</verse>
+      <verse osisID="Gen.1.2 Gen.1.3">And the earth was without form &amp; void.</verse>
+    </p>
+  </chapter>
+</div>
+<div type="book" osisID="Ps">
+  <chapter osisID="Ps.23">
+    <verse osisID="Ps.23.1"><title type="psalm" canonical="true">A Psalm of David.</title>
+      <lg><l level="1">The <divineName>LORD</divineName> is my shepherd;</l><l level="2">I shall not want.</l></lg>
+    </verse>
+  </chapter>
+</div>
+<div type="book" osisID="Tob"><chapter osisID="Tob.1"><verse osisID="Tob.1.1">Tobit</verse></chapter></div>
+</osisText>
+</osis>"#;
+
+    const MILESTONES: &str = r#"<osis><osisText osisIDWork="MS">
+<div type="book" osisID="John">
+<chapter sID="John.3" osisID="John.3"/>
+<div type="paragraph" sID="p1"/>
+<verse sID="John.3.16" osisID="John.3.16"/><q who="Jesus" marker="" sID="q1"/>For God so loved the world<note type="crossReference" osisRef="John.3.16"><reference osisRef="Rom.5.8">Rom 5:8</reference></note>, that he gave his only begotten Son.<verse eID="John.3.16"/>
+<div type="paragraph" eID="p1"/>
+<verse sID="John.3.17" osisID="John.3.17"/>For God sent not his Son<q eID="q1" marker=""/><verse eID="John.3.17"/>
+<chapter eID="John.3"/>
+</div>
+</osisText></osis>"#;
+
+    #[test]
+    fn parse_osis_containers() {
+        let bible = OSISBible::parse(CONTAINERS).unwrap();
+        assert_eq!(bible.translation.name, "Test Bible");
+        assert_eq!(bible.translation.language, "English");
+        assert_eq!(bible.translation.abbreviation, "TST");
+        assert_eq!(bible.translation.versification, Versification::KJV);
+        assert_eq!(bible.books.len(), 2);
+
+        let genesis = &bible.books[0];
+        assert_eq!((genesis.id, genesis.name.as_str()), (1, "Genesis"));
+        let verse = &genesis.chapters[0][0];
+        assert_eq!(
+            verse.content(),
+            Some("In the beginning God created the heaven and the earth.")
+        );
+        assert_eq!(verse.headings(), ["The Creation"]);
+        assert_eq!(
+            verse.words(),
+            [TaggedWord {
+                range: 0..16,
+                strong: Some(String::from("H07225")),
+                lemma: None,
+                morph: Some(String::from("oshm:HR/Ncfsa")),
+            }]
+        );
+        assert_eq!(
+            verse.footnotes(),
+            [Note {
+                offset: 39,
+                origin: Some(String::from("1:1")),
+                text: String::from("Or, heavens"),
+            }]
+        );
+        assert_eq!(
+            genesis.chapters[0][1].content(),
+            Some("And the earth was without form & void.")
+        );
+        assert_eq!(genesis.chapters[0].len(), 3);
+        assert_eq!(genesis.chapters[0][2].content(), None);
+
+        let psalm = &bible.books[1].chapters[22][0];
+        assert_eq!(psalm.headings(), ["A Psalm of David."]);
+        assert_eq!(
+            psalm.content(),
+            Some("The LORD is my shepherd; I shall not want.")
+        );
+        assert_eq!(
+            psalm.paragraphs(),
+            [
+                ParagraphBreak {
+                    offset: 0,
+                    style: ParagraphStyle::Poetry(1)
+                },
+                ParagraphBreak {
+                    offset: 25,
+                    style: ParagraphStyle::Poetry(2)
+                }
+            ]
+        );
+        assert_eq!(
+            psalm.styles(),
+            [StyledText {
+                range: 4..8,
+                style: TextStyle::DivineName
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_osis_milestones() {
+        let bible = OSISBible::parse(MILESTONES)
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        assert_eq!(bible.translation.abbreviation, "MS");
+        assert_eq!(bible.get_book(43).unwrap().get_name(), "John");
+        let verse = bible.get_verse(43, 3, 16).unwrap();
+        assert_eq!(
+            verse.get_content(),
+            Some("For God so loved the world, that he gave his only begotten Son.")
+        );
+        let data = verse.get_data();
+        assert_eq!(data.paragraphs()[0].offset, 0);
+        assert_eq!(data.cross_references()[0].text, "Rom 5:8");
+        assert_eq!(data.cross_references()[0].offset, 26);
+        // the words of Jesus go on to the next verse
+        assert_eq!(
+            data.styles(),
+            [StyledText {
+                range: 0..63,
+                style: TextStyle::WordsOfJesus
+            }]
+        );
+        assert_eq!(
+            bible.get_verse(43, 3, 17).unwrap().get_data().styles(),
+            [StyledText {
+                range: 0..24,
+                style: TextStyle::WordsOfJesus
+            }]
+        );
+        assert_eq!(
+            bible.get_verse(43, 3, 17).unwrap().get_content(),
+            Some("For God sent not his Son")
+        );
+        assert!(bible
+            .get_verse(43, 3, 17)
+            .unwrap()
+            .get_data()
+            .paragraphs()
+            .is_empty());
+    }
+
+    #[test]
+    fn osis_round_trip() {
+        for source in [CONTAINERS, MILESTONES] {
+            let before = OSISBible::parse(source).unwrap().as_bible_data().unwrap();
+            let osis = OSISBible::from_bible_data(&before).to_osis();
+            let after = OSISBible::parse(&osis).unwrap().as_bible_data().unwrap();
+            assert_round_trip(&before, &after);
+        }
+    }
+
+    #[test]
+    fn osis_round_trip_skips_other_books() {
+        let before = OSISBible::parse(CONTAINERS)
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        let mut books = before.to_books();
+        books.push(BookData {
+            id: 67,
+            name: String::from("Tobit"),
+            abbreviations: vec![],
+            chapters: vec![vec![VerseData::new(Some(String::from("Tobit")))]],
+        });
+        let with_tobit = BibleData::from_books(before.translation.clone(), books).unwrap();
+        let osis = OSISBible::from_bible_data(&with_tobit).to_osis();
+        assert!(!osis.contains("Tobit"));
+        let after = OSISBible::parse(&osis).unwrap().as_bible_data().unwrap();
+        assert_round_trip(&before, &after);
+    }
+}
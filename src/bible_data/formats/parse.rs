@@ -1,8 +1,79 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::bible_data::bible_data::BibleData;
+use crate::bible_data::{bible_data::BibleData, versification::Versification};
+
+use super::json::JSONTranslation;
 
 pub trait ParseBibleData: Sized {
     fn parse_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>>;
     fn as_bible_data(self) -> Result<BibleData, Box<dyn std::error::Error>>;
+
+    /// - Loads `path` from the binary cache at `cache`, which is (re)written whenever `path` has
+    ///   changed
+    /// - See [`BibleData::load_cached`]
+    fn load_cached(path: &Path, cache: &Path) -> Result<BibleData, Box<dyn std::error::Error>> {
+        BibleData::load_cached(path, cache, |path| Self::parse_file(path)?.as_bible_data())
+    }
+}
+
+/// The other direction of [`ParseBibleData`], so that translations can be converted between formats
+pub trait ExportBibleData: Sized {
+    fn from_bible_data(bible: &BibleData) -> Self;
+    /// - Formats with a file for each book (like USFM) are written to a folder at `path`
+    fn write_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// - The contents of `path`, or of every file in it (and its folders) with one of `extensions`
+/// - Files are in order of their path
+pub(super) fn read_sources(path: &Path, extensions: &[&str]) -> std::io::Result<Vec<String>> {
+    fn find_files(
+        path: &Path,
+        extensions: &[&str],
+        files: &mut Vec<PathBuf>,
+    ) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                find_files(&path, extensions, files)?;
+            } else if path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| extensions.contains(&extension.to_lowercase().as_str()))
+            {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = vec![];
+    if path.is_dir() {
+        find_files(path, extensions, &mut files)?;
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+    files.iter().map(std::fs::read_to_string).collect()
+}
+
+/// For formats without translation metadata, the translation is named after the file (or folder)
+pub(super) fn translation_from_path(path: &Path) -> JSONTranslation {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    JSONTranslation {
+        abbreviation: name.to_uppercase(),
+        name,
+        language: String::new(),
+        versification: Versification::default(),
+    }
+}
+
+/// Checks that importing what was exported gives back the same translation
+#[cfg(test)]
+pub(crate) fn assert_round_trip(before: &BibleData, after: &BibleData) {
+    assert_eq!(before.translation, after.translation);
+    assert_eq!(before.reference_array, after.reference_array);
+    assert_eq!(before.to_books(), after.to_books());
 }
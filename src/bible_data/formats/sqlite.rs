@@ -0,0 +1,724 @@
+use std::{collections::BTreeMap, path::Path};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+
+use crate::bible_data::{
+    bible_data::{
+        BibleData, BookData, Note, ParagraphBreak, ParagraphStyle, StyledText, TaggedWord,
+        TextStyle, VerseData,
+    },
+    versification::Versification,
+};
+
+use super::{
+    builder::{BookBuilder, NoteKind},
+    csv::BOOK_NAMES,
+    json::JSONTranslation,
+    osis,
+    parse::{translation_from_path, ExportBibleData, ParseBibleData},
+    usfm,
+};
+
+/// The schema that [`SQLiteBible::write`] creates
+const SCHEMA: &str = "
+CREATE TABLE translation (
+    name TEXT NOT NULL,
+    language TEXT NOT NULL,
+    abbreviation TEXT NOT NULL,
+    versification TEXT NOT NULL
+);
+CREATE TABLE books (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    chapter_count INTEGER NOT NULL
+);
+CREATE TABLE book_abbreviations (
+    book_id INTEGER NOT NULL REFERENCES books(id),
+    abbreviation TEXT NOT NULL
+);
+CREATE TABLE verses (
+    id INTEGER PRIMARY KEY,
+    book_id INTEGER NOT NULL REFERENCES books(id),
+    chapter INTEGER NOT NULL,
+    verse INTEGER NOT NULL,
+    content TEXT,
+    UNIQUE (book_id, chapter, verse)
+);
+CREATE TABLE headings (
+    verse_id INTEGER NOT NULL REFERENCES verses(id),
+    text TEXT NOT NULL
+);
+CREATE TABLE paragraphs (
+    verse_id INTEGER NOT NULL REFERENCES verses(id),
+    byte_offset INTEGER NOT NULL,
+    -- NULL for prose
+    poetry_level INTEGER
+);
+CREATE TABLE notes (
+    verse_id INTEGER NOT NULL REFERENCES verses(id),
+    kind TEXT NOT NULL CHECK (kind IN ('footnote', 'cross_reference')),
+    byte_offset INTEGER NOT NULL,
+    origin TEXT,
+    text TEXT NOT NULL
+);
+CREATE TABLE words (
+    verse_id INTEGER NOT NULL REFERENCES verses(id),
+    range_start INTEGER NOT NULL,
+    range_end INTEGER NOT NULL,
+    strong TEXT,
+    lemma TEXT,
+    morph TEXT
+);
+CREATE TABLE styles (
+    verse_id INTEGER NOT NULL REFERENCES verses(id),
+    range_start INTEGER NOT NULL,
+    range_end INTEGER NOT NULL,
+    style TEXT NOT NULL CHECK (style IN ('words-of-jesus', 'divine-name', 'small-caps', 'selah'))
+);
+";
+
+/// The columns (book, chapter, verse, text) of the verse tables of other SQLite Bibles
+const VERSE_COLUMNS: [[&str; 4]; 3] = [
+    // MySword and e-Sword (`Bible`)
+    ["book", "chapter", "verse", "scripture"],
+    // scrollmapper (`KJV_verses`)
+    ["book_id", "chapter", "verse", "text"],
+    // older scrollmapper (`t_kjv`)
+    ["b", "c", "v", "t"],
+];
+
+/**
+A translation in a SQLite database
+
+- [`Self::read`] reads the schema that [`Self::write`] creates (with all of the markup of each
+  [`VerseData`]), or the verse table of other SQLite Bibles:
+  - MySword (and e-Sword) modules, with `Bible(Book, Chapter, Verse, Scripture)`, where titles
+    (`<TS>`), paragraphs (`<CM>`), notes (`<RF>`), red letters (`<FR>`) and Strong's numbers
+    (`<WH7225>`) are kept
+  - [scrollmapper](https://github.com/scrollmapper/bible_databases) databases, with
+    `KJV_verses(book_id, chapter, verse, text)` (or `t_kjv(b, c, v, t)`)
+- Books outside of the 66 are skipped
+- Books are named from a `books` table (like `KJV_books` or `key_english`) when there is one, and
+  otherwise in English
+*/
+pub struct SQLiteBible {
+    pub translation: JSONTranslation,
+    pub books: Vec<BookData>,
+}
+
+/// The names (in lowercase) of every table in the database
+fn tables(connection: &Connection) -> rusqlite::Result<Vec<String>> {
+    connection
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table'")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .map(|name| name.map(|name| name.to_lowercase()))
+        .collect()
+}
+
+/// The names (in lowercase) of the columns of `table`
+fn columns(connection: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    connection
+        .prepare(&format!("PRAGMA table_info(\"{table}\")"))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .map(|name| name.map(|name| name.to_lowercase()))
+        .collect()
+}
+
+fn english_book(id: usize) -> BookData {
+    BookData {
+        id,
+        name: BOOK_NAMES[id - 1].to_string(),
+        abbreviations: vec![
+            osis::BOOK_CODES[id - 1].to_string(),
+            usfm::BOOK_CODES[id - 1].to_string(),
+        ],
+        chapters: vec![],
+    }
+}
+
+fn note_kind(kind: &str) -> NoteKind {
+    match kind {
+        "cross_reference" => NoteKind::CrossReference,
+        _ => NoteKind::Footnote,
+    }
+}
+
+/// - Ex: `<WH7225>`, `<RF>` and `<Rf>`
+/// - The second letter is lowercase for closing tags
+static GBF_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<([A-Za-z]{2})([^>]*)>").unwrap());
+
+/// Ex: `\cf6`, `\par ` or `{`
+static RTF_CONTROL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\[a-z]+-?[0-9]* ?|[{}]").unwrap());
+
+/// Reads a verse of MySword's GBF markup (or e-Sword's RTF, which is left out) into `builder`
+fn push_scripture(builder: &mut BookBuilder, scripture: &str) {
+    let scripture = RTF_CONTROL.replace_all(scripture, "");
+    // the tag whose text is being captured (`TS` or `RF`) and its text
+    let mut capture: Option<(String, String)> = None;
+    let mut end = 0;
+    for tag in GBF_TAG.captures_iter(&scripture) {
+        let whole = tag.get(0).unwrap();
+        let text = &scripture[end..whole.start()];
+        end = whole.end();
+        match capture.as_mut() {
+            Some((_, captured)) => captured.push_str(text),
+            None => builder.push_text(text),
+        }
+        let name = &tag[1];
+        match name {
+            "TS" | "RF" => capture = Some((name.to_string(), String::new())),
+            "Ts" | "Rf" => {
+                if let Some((name, text)) = capture.take() {
+                    if name == "TS" {
+                        builder.heading(&text);
+                    } else {
+                        let offset = builder.content_end();
+                        builder.push_note(NoteKind::Footnote, offset, "", &text);
+                    }
+                }
+            }
+            "CM" => builder.paragraph(ParagraphStyle::Prose),
+            // red letters
+            "FR" if capture.is_none() => builder.start_style(TextStyle::WordsOfJesus),
+            "Fr" if capture.is_none() => builder.end_style(TextStyle::WordsOfJesus),
+            _ if capture.is_none() && name.starts_with('W') => {
+                let value = format!("{}{}", &name[1..], &tag[2]);
+                match &name[1..] {
+                    "H" | "G" => builder.tag_previous_word(Some(value), None),
+                    // `<WTHR/Ncfsa>` is a morphology code
+                    "T" => builder.tag_previous_word(None, Some(value[1..].to_string())),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    builder.push_text(&scripture[end..]);
+}
+
+impl SQLiteBible {
+    /// - Reads a translation from any of the schemas in [`SQLiteBible`]
+    /// - `translation` is used when the database doesn't have translation metadata
+    pub fn read(
+        connection: &Connection,
+        translation: JSONTranslation,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let tables = tables(connection)?;
+        if tables.iter().any(|table| table == "verses")
+            && tables.iter().any(|table| table == "books")
+        {
+            return Self::read_normalized(connection, translation);
+        }
+        for table in tables.iter() {
+            let columns = columns(connection, table)?;
+            if let Some(verse_columns) = VERSE_COLUMNS.iter().find(|verse_columns| {
+                verse_columns
+                    .iter()
+                    .all(|column| columns.iter().any(|name| name == column))
+            }) {
+                return Self::read_verses(connection, &tables, table, verse_columns, translation);
+            }
+        }
+        Err("the database doesn't have a table of verses".into())
+    }
+
+    fn read_normalized(
+        connection: &Connection,
+        translation: JSONTranslation,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let translation = connection
+            .query_row(
+                "SELECT name, language, abbreviation, versification FROM translation",
+                [],
+                |row| {
+                    Ok(JSONTranslation {
+                        name: row.get(0)?,
+                        language: row.get(1)?,
+                        abbreviation: row.get(2)?,
+                        versification: Versification::from(row.get::<_, String>(3)?),
+                    })
+                },
+            )
+            .optional()?
+            .unwrap_or(translation);
+
+        let mut books = BTreeMap::new();
+        let mut statement = connection.prepare("SELECT id, name, chapter_count FROM books")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: usize = row.get(0)?;
+            books.insert(
+                id,
+                BookData {
+                    id,
+                    name: row.get(1)?,
+                    abbreviations: vec![],
+                    chapters: vec![vec![]; row.get(2)?],
+                },
+            );
+        }
+        let mut statement = connection
+            .prepare("SELECT book_id, abbreviation FROM book_abbreviations ORDER BY rowid")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            if let Some(book) = books.get_mut(&row.get::<_, usize>(0)?) {
+                book.abbreviations.push(row.get(1)?);
+            }
+        }
+
+        // verse id to (book, chapter, verse), where the chapter and verse are index 0
+        let mut locations = BTreeMap::new();
+        let mut statement = connection.prepare(
+            "SELECT id, book_id, chapter, verse, content FROM verses ORDER BY book_id, chapter, verse",
+        )?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            let (book, chapter, verse): (usize, usize, usize) =
+                (row.get(1)?, row.get(2)?, row.get(3)?);
+            let Some(chapters) = books.get_mut(&book).map(|book| &mut book.chapters) else {
+                continue;
+            };
+            if chapter == 0 || verse == 0 {
+                return Err(format!("{book} {chapter}:{verse} isn't a verse").into());
+            }
+            if chapters.len() < chapter {
+                chapters.resize_with(chapter, Vec::new);
+            }
+            let verses = &mut chapters[chapter - 1];
+            if verses.len() < verse {
+                verses.resize_with(verse, VerseData::default);
+            }
+            verses[verse - 1].content = row.get(4)?;
+            locations.insert(row.get::<_, i64>(0)?, (book, chapter - 1, verse - 1));
+        }
+        fn verse_data<'a>(
+            books: &'a mut BTreeMap<usize, BookData>,
+            locations: &BTreeMap<i64, (usize, usize, usize)>,
+            id: i64,
+        ) -> Option<&'a mut VerseData> {
+            let (book, chapter, verse) = locations.get(&id)?;
+            books
+                .get_mut(book)
+                .map(|book| &mut book.chapters[*chapter][*verse])
+        }
+
+        let mut statement =
+            connection.prepare("SELECT verse_id, text FROM headings ORDER BY rowid")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            if let Some(verse) = verse_data(&mut books, &locations, row.get(0)?) {
+                verse.markup_mut().headings.push(row.get(1)?);
+            }
+        }
+        let mut statement = connection
+            .prepare("SELECT verse_id, byte_offset, poetry_level FROM paragraphs ORDER BY rowid")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            if let Some(verse) = verse_data(&mut books, &locations, row.get(0)?) {
+                verse.markup_mut().paragraphs.push(ParagraphBreak {
+                    offset: row.get(1)?,
+                    style: row
+                        .get::<_, Option<u8>>(2)?
+                        .map_or(ParagraphStyle::Prose, ParagraphStyle::Poetry),
+                });
+            }
+        }
+        let mut statement = connection.prepare(
+            "SELECT verse_id, kind, byte_offset, origin, text FROM notes ORDER BY rowid",
+        )?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            if let Some(verse) = verse_data(&mut books, &locations, row.get(0)?) {
+                let note = Note {
+                    offset: row.get(2)?,
+                    origin: row.get(3)?,
+                    text: row.get(4)?,
+                };
+                match note_kind(&row.get::<_, String>(1)?) {
+                    NoteKind::Footnote => verse.markup_mut().footnotes.push(note),
+                    NoteKind::CrossReference => verse.markup_mut().cross_references.push(note),
+                }
+            }
+        }
+        let mut statement = connection.prepare(
+            "SELECT verse_id, range_start, range_end, strong, lemma, morph FROM words ORDER BY rowid",
+        )?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            if let Some(verse) = verse_data(&mut books, &locations, row.get(0)?) {
+                verse.markup_mut().words.push(TaggedWord {
+                    range: row.get(1)?..row.get(2)?,
+                    strong: row.get(3)?,
+                    lemma: row.get(4)?,
+                    morph: row.get(5)?,
+                });
+            }
+        }
+        // databases from before styles were kept don't have this table
+        if tables(connection)?.iter().any(|table| table == "styles") {
+            let mut statement = connection.prepare(
+                "SELECT verse_id, range_start, range_end, style FROM styles ORDER BY rowid",
+            )?;
+            let mut rows = statement.query([])?;
+            while let Some(row) = rows.next()? {
+                let Some(style) = TextStyle::from_name(&row.get::<_, String>(3)?) else {
+                    continue;
+                };
+                if let Some(verse) = verse_data(&mut books, &locations, row.get(0)?) {
+                    verse.markup_mut().styles.push(StyledText {
+                        range: row.get(1)?..row.get(2)?,
+                        style,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            translation,
+            books: books.into_values().collect(),
+        })
+    }
+
+    /// Reads the verse table of a MySword, e-Sword or scrollmapper Bible
+    fn read_verses(
+        connection: &Connection,
+        tables: &[String],
+        table: &str,
+        [book, chapter, verse, text]: &[&str; 4],
+        translation: JSONTranslation,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut books = BTreeMap::<usize, (BookData, BookBuilder)>::new();
+        let mut statement = connection.prepare(&format!(
+            "SELECT {book}, {chapter}, {verse}, {text} FROM \"{table}\" ORDER BY {book}, {chapter}, {verse}"
+        ))?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: usize = row.get(0)?;
+            if !(1..=BOOK_NAMES.len()).contains(&id) {
+                continue;
+            }
+            let (chapter, verse): (usize, usize) = (row.get(1)?, row.get(2)?);
+            let (_, builder) = books
+                .entry(id)
+                .or_insert_with(|| (english_book(id), BookBuilder::default()));
+            builder.start_verse(chapter, verse, verse);
+            push_scripture(
+                builder,
+                &row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+            );
+        }
+
+        // Ex: `KJV_books(id, name)` for `KJV_verses`, or `key_english(b, n)` for `t_kjv`
+        let names_table = [
+            table
+                .strip_suffix("verses")
+                .map(|prefix| format!("{prefix}books")),
+            Some(String::from("key_english")),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|names| tables.contains(names));
+        if let Some(names_table) = names_table {
+            let columns = columns(connection, &names_table)?;
+            let [id, name] = if columns.iter().any(|column| column == "b") {
+                ["b", "n"]
+            } else {
+                ["id", "name"]
+            };
+            let mut statement =
+                connection.prepare(&format!("SELECT {id}, {name} FROM \"{names_table}\""))?;
+            let mut rows = statement.query([])?;
+            while let Some(row) = rows.next()? {
+                if let Some((book, _)) = books.get_mut(&row.get::<_, usize>(0)?) {
+                    let name: String = row.get(1)?;
+                    let english = std::mem::replace(&mut book.name, name);
+                    book.abbreviations.push(english);
+                }
+            }
+        }
+
+        let translation = Self::read_details(connection, tables)?.unwrap_or(translation);
+        let books = books
+            .into_values()
+            .map(|(book, builder)| BookData {
+                chapters: builder.finish(),
+                ..book
+            })
+            .collect();
+        Ok(Self { translation, books })
+    }
+
+    /// The translation from the `Details` table of a MySword or e-Sword module
+    fn read_details(
+        connection: &Connection,
+        tables: &[String],
+    ) -> Result<Option<JSONTranslation>, Box<dyn std::error::Error>> {
+        if !tables.iter().any(|table| table == "details") {
+            return Ok(None);
+        }
+        let columns = columns(connection, "details")?;
+        let mut statement = connection.prepare("SELECT * FROM Details")?;
+        let mut rows = statement.query([])?;
+        let Some(row) = rows.next()? else {
+            return Ok(None);
+        };
+        let value = |names: &[&str]| -> rusqlite::Result<Option<String>> {
+            match columns
+                .iter()
+                .position(|column| names.contains(&column.as_str()))
+            {
+                Some(index) => row.get(index),
+                None => Ok(None),
+            }
+        };
+        let abbreviation = value(&["abbreviation"])?.unwrap_or_default();
+        Ok(Some(JSONTranslation {
+            name: value(&["title", "description"])?.unwrap_or(abbreviation.clone()),
+            language: value(&["language"])?.unwrap_or_default(),
+            abbreviation,
+            versification: Versification::default(),
+        }))
+    }
+
+    /// Writes this translation (and all of its markup) into a new schema in `connection`
+    pub fn write(&self, connection: &Connection) -> rusqlite::Result<()> {
+        let transaction = connection.unchecked_transaction()?;
+        transaction.execute_batch(SCHEMA)?;
+        transaction.execute(
+            "INSERT INTO translation VALUES (?1, ?2, ?3, ?4)",
+            params![
+                self.translation.name,
+                self.translation.language,
+                self.translation.abbreviation,
+                self.translation.versification.to_string(),
+            ],
+        )?;
+        for book in self.books.iter() {
+            transaction.execute(
+                "INSERT INTO books VALUES (?1, ?2, ?3)",
+                params![book.id, book.name, book.chapters.len()],
+            )?;
+            for abbreviation in book.abbreviations.iter() {
+                transaction.execute(
+                    "INSERT INTO book_abbreviations VALUES (?1, ?2)",
+                    params![book.id, abbreviation],
+                )?;
+            }
+            for (chapter, verses) in book.chapters.iter().enumerate() {
+                for (verse, data) in verses.iter().enumerate() {
+                    transaction.execute(
+                        "INSERT INTO verses (book_id, chapter, verse, content) VALUES (?1, ?2, ?3, ?4)",
+                        params![book.id, chapter + 1, verse + 1, data.content()],
+                    )?;
+                    let id = transaction.last_insert_rowid();
+                    for heading in data.headings().iter() {
+                        transaction.execute(
+                            "INSERT INTO headings VALUES (?1, ?2)",
+                            params![id, heading],
+                        )?;
+                    }
+                    for paragraph in data.paragraphs().iter() {
+                        let level = match paragraph.style {
+                            ParagraphStyle::Prose => None,
+                            ParagraphStyle::Poetry(level) => Some(level),
+                        };
+                        transaction.execute(
+                            "INSERT INTO paragraphs VALUES (?1, ?2, ?3)",
+                            params![id, paragraph.offset, level],
+                        )?;
+                    }
+                    let notes = data
+                        .footnotes()
+                        .iter()
+                        .map(|note| ("footnote", note))
+                        .chain(
+                            data.cross_references()
+                                .iter()
+                                .map(|note| ("cross_reference", note)),
+                        );
+                    for (kind, note) in notes {
+                        transaction.execute(
+                            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, ?5)",
+                            params![id, kind, note.offset, note.origin, note.text],
+                        )?;
+                    }
+                    for word in data.words().iter() {
+                        transaction.execute(
+                            "INSERT INTO words VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                            params![
+                                id,
+                                word.range.start,
+                                word.range.end,
+                                word.strong,
+                                word.lemma,
+                                word.morph
+                            ],
+                        )?;
+                    }
+                    for styled in data.styles().iter() {
+                        transaction.execute(
+                            "INSERT INTO styles VALUES (?1, ?2, ?3, ?4)",
+                            params![
+                                id,
+                                styled.range.start,
+                                styled.range.end,
+                                styled.style.name()
+                            ],
+                        )?;
+                    }
+                }
+            }
+        }
+        transaction.commit()
+    }
+}
+
+impl ParseBibleData for SQLiteBible {
+    fn parse_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Self::read(&connection, translation_from_path(path))
+    }
+
+    fn as_bible_data(self) -> Result<BibleData, Box<dyn std::error::Error>> {
+        BibleData::from_books(self.translation, self.books)
+    }
+}
+
+impl ExportBibleData for SQLiteBible {
+    fn from_bible_data(bible: &BibleData) -> Self {
+        Self {
+            translation: bible.translation.clone(),
+            books: bible.to_books(),
+        }
+    }
+
+    /// `path` must not have a translation in it already
+    fn write_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(self.write(&Connection::open(path)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::formats::{
+        json::test_bible_data, parse::assert_round_trip, usfm::USFMBible,
+    };
+
+    use super::*;
+
+    fn round_trip(before: &BibleData) {
+        let connection = Connection::open_in_memory().unwrap();
+        SQLiteBible::from_bible_data(before)
+            .write(&connection)
+            .unwrap();
+        let after = SQLiteBible::read(&connection, translation_from_path(Path::new("other")))
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        assert_round_trip(before, &after);
+    }
+
+    #[test]
+    fn sqlite_round_trip() {
+        round_trip(&test_bible_data());
+        let usfm = r#"\id JHN
+\h John
+\c 1
+\s1 The Word Became Flesh
+\p
+\v 1 \w In|strong="G1722"\w* the beginning was the Word\f + \fr 1:1 \ft Or the Logos\f*.
+\q1
+\v 2 He was in the beginning with \nd God\nd*.\x - \xo 1:2 \xt Gen 1:1\x*
+"#;
+        let translation = translation_from_path(Path::new("test"));
+        round_trip(
+            &USFMBible::parse(translation, &[usfm])
+                .unwrap()
+                .as_bible_data()
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn read_mysword() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE Details (Title TEXT, Abbreviation TEXT, Language TEXT);
+                INSERT INTO Details VALUES ('King James Version', 'KJV', 'en');
+                CREATE TABLE Bible (Book INT, Chapter INT, Verse INT, Scripture TEXT);
+                INSERT INTO Bible VALUES (1, 1, 1, '<TS>The Creation<Ts>In the beginning<WH7225> God<WH430> created<WH1254><WTHVqp3ms> the heaven<RF>Or, heavens<Rf> and the earth.<CM>');
+                INSERT INTO Bible VALUES (1, 1, 2, 'And the earth was without form.');
+                INSERT INTO Bible VALUES (43, 3, 3, 'Jesus answered, <FR>Verily, verily<Fr>');
+                INSERT INTO Bible VALUES (70, 1, 1, 'Not one of the 66');",
+            )
+            .unwrap();
+        let bible = SQLiteBible::read(&connection, translation_from_path(Path::new("kjv")))
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        assert_eq!(bible.translation.name, "King James Version");
+        assert_eq!(bible.get_book(1).unwrap().get_name(), "Genesis");
+        let verse = bible.get_verse(1, 1, 1).unwrap();
+        assert_eq!(
+            verse.get_content(),
+            Some("In the beginning God created the heaven and the earth.")
+        );
+        let data = verse.get_data();
+        assert_eq!(data.headings(), ["The Creation"]);
+        assert_eq!(data.footnotes()[0].text, "Or, heavens");
+        assert_eq!(data.footnotes()[0].offset, 39);
+        assert_eq!(
+            data.words()
+                .iter()
+                .map(|word| (
+                    word.range.clone(),
+                    word.strong.as_deref(),
+                    word.morph.as_deref()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (7..16, Some("H7225"), None),
+                (17..20, Some("H430"), None),
+                (21..28, Some("H1254"), Some("HVqp3ms")),
+            ]
+        );
+        assert_eq!(
+            bible.get_verse(1, 1, 2).unwrap().get_data().paragraphs()[0].offset,
+            0
+        );
+        assert_eq!(
+            bible.get_verse(43, 3, 3).unwrap().get_data().styles(),
+            [StyledText {
+                range: 16..30,
+                style: TextStyle::WordsOfJesus
+            }]
+        );
+    }
+
+    #[test]
+    fn read_scrollmapper() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE KJV_books (id INTEGER, name TEXT);
+                INSERT INTO KJV_books VALUES (43, 'Jean');
+                CREATE TABLE KJV_verses (id INTEGER, book_id INTEGER, chapter INTEGER, verse INTEGER, text TEXT);
+                INSERT INTO KJV_verses VALUES (1, 43, 3, 16, 'For God so loved the world');",
+            )
+            .unwrap();
+        let bible = SQLiteBible::read(&connection, translation_from_path(Path::new("kjv")))
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        assert_eq!(bible.translation.abbreviation, "KJV");
+        assert_eq!(bible.get_book(43).unwrap().get_name(), "Jean");
+        assert_eq!(bible.get_book_id("john"), Some(43));
+        assert_eq!(
+            bible.get_verse(43, 3, 16).unwrap().get_content(),
+            Some("For God so loved the world")
+        );
+    }
+}
@@ -0,0 +1,890 @@
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::bible_data::bible_data::{BibleData, BookData, ParagraphStyle, TextStyle, VerseData};
+
+use super::{
+    builder::{verse_markup, BookBuilder, Markup, NoteKind},
+    json::JSONTranslation,
+    parse::{read_sources, translation_from_path, ExportBibleData, ParseBibleData},
+};
+
+/// The USFM book codes, in order (so the book id is the index + 1)
+pub const BOOK_CODES: [&str; 66] = [
+    "GEN", "EXO", "LEV", "NUM", "DEU", "JOS", "JDG", "RUT", "1SA", "2SA", "1KI", "2KI", "1CH",
+    "2CH", "EZR", "NEH", "EST", "JOB", "PSA", "PRO", "ECC", "SNG", "ISA", "JER", "LAM", "EZK",
+    "DAN", "HOS", "JOL", "AMO", "OBA", "JON", "MIC", "NAM", "HAB", "ZEP", "HAG", "ZEC", "MAL",
+    "MAT", "MRK", "LUK", "JHN", "ACT", "ROM", "1CO", "2CO", "GAL", "EPH", "PHP", "COL", "1TH",
+    "2TH", "1TI", "2TI", "TIT", "PHM", "HEB", "JAS", "1PE", "2PE", "1JN", "2JN", "3JN", "JUD",
+    "REV",
+];
+
+/// - The book id (where Genesis = 1) of a USFM book code (any case)
+/// - Ex: `JHN` is 43
+pub fn book_id(code: &str) -> Option<usize> {
+    BOOK_CODES
+        .iter()
+        .position(|book| book.eq_ignore_ascii_case(code))
+        .map(|index| index + 1)
+}
+
+/**
+A translation in [USFM](https://ubsicap.github.io/usfm/), with a file for each book
+
+- Headings (`\s`), paragraphs and poetry (`\p`, `\q1`, ...), footnotes (`\f`), cross references
+  (`\x`) and words with Strong's numbers (`\w ...|strong="..."\w*`) are kept on each
+  [`VerseData`]
+- Books outside of the 66 (like the deuterocanon) are skipped
+- USFM has no translation metadata, so [`ParseBibleData::parse_file`] names the translation after
+  the file (or folder), and `translation` can be changed before calling
+  [`ParseBibleData::as_bible_data`]
+*/
+pub struct USFMBible {
+    pub translation: JSONTranslation,
+    pub books: Vec<BookData>,
+}
+
+impl USFMBible {
+    /// Parses each USFM book in `sources`
+    pub fn parse(
+        translation: JSONTranslation,
+        sources: &[&str],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut books = vec![];
+        for source in sources {
+            books.extend(parse_book(source)?);
+        }
+        Ok(Self { translation, books })
+    }
+}
+
+impl ParseBibleData for USFMBible {
+    /// `path` is either a USFM file or a folder of them (`.usfm`, `.sfm` or `.ptx`)
+    fn parse_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let sources = read_sources(path, &["usfm", "sfm", "ptx"])?;
+        Self::parse(
+            translation_from_path(path),
+            &sources.iter().map(String::as_str).collect::<Vec<_>>(),
+        )
+    }
+
+    fn as_bible_data(self) -> Result<BibleData, Box<dyn std::error::Error>> {
+        BibleData::from_books(self.translation, self.books)
+    }
+}
+
+impl ExportBibleData for USFMBible {
+    fn from_bible_data(bible: &BibleData) -> Self {
+        Self {
+            translation: bible.translation.clone(),
+            books: bible.to_books(),
+        }
+    }
+
+    /// - Writes a folder with a file for each book (Ex: `43-JHN.usfm`)
+    /// - Books outside of the 66 are skipped, like when parsing
+    fn write_file(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(path)?;
+        for book in self.books.iter() {
+            let (Some(code), Some(usfm)) = (BOOK_CODES.get(book.id - 1), write_book(book)) else {
+                continue;
+            };
+            std::fs::write(path.join(format!("{:02}-{code}.usfm", book.id)), usfm)?;
+        }
+        Ok(())
+    }
+}
+
+/// - Ex: `\v`, `\q1`, `\+w`, `\w*` or `\qt-s`
+/// - A lone `\*` ends a milestone
+static MARKER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\\\+?([A-Za-z]+[0-9]*(?:-[se])?)?(\*)?").unwrap());
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    /// `name` is empty for `\*`
+    Marker {
+        name: &'a str,
+        end: bool,
+    },
+    Text(&'a str),
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut last = 0;
+    for captures in MARKER.captures_iter(source) {
+        let marker = captures.get(0).unwrap();
+        if marker.start() > last {
+            tokens.push(Token::Text(&source[last..marker.start()]));
+        }
+        let name = captures.get(1).map_or("", |name| name.as_str());
+        let end = captures.get(2).is_some();
+        tokens.push(Token::Marker { name, end });
+        last = marker.end();
+        // the space after an opening marker is part of the marker
+        if !end && source[last..].starts_with([' ', '\t', '\r', '\n']) {
+            last += 1;
+        }
+    }
+    if last < source.len() {
+        tokens.push(Token::Text(&source[last..]));
+    }
+    tokens
+}
+
+/// `q2` is `Poetry(2)`, and markers that don't start a paragraph are [`None`]
+pub(super) fn paragraph_style(marker: &str) -> Option<ParagraphStyle> {
+    let level = marker
+        .trim_start_matches(char::is_alphabetic)
+        .parse()
+        .unwrap_or(1);
+    match marker.trim_end_matches(|ch: char| ch.is_ascii_digit()) {
+        "p" | "m" | "po" | "pr" | "cls" | "pmo" | "pm" | "pmc" | "pmr" | "pi" | "mi" | "pc"
+        | "ph" | "li" | "lim" => Some(ParagraphStyle::Prose),
+        "q" | "qr" | "qc" | "qm" => Some(ParagraphStyle::Poetry(level)),
+        _ => None,
+    }
+}
+
+/// Character markers that style their text, and [`None`] for the rest
+pub(super) fn text_style(marker: &str) -> Option<TextStyle> {
+    match marker {
+        "wj" => Some(TextStyle::WordsOfJesus),
+        "nd" => Some(TextStyle::DivineName),
+        "sc" => Some(TextStyle::SmallCaps),
+        "qs" => Some(TextStyle::Selah),
+        _ => None,
+    }
+}
+
+fn text_style_marker(style: TextStyle) -> &'static str {
+    match style {
+        TextStyle::WordsOfJesus => "wj",
+        TextStyle::DivineName => "nd",
+        TextStyle::SmallCaps => "sc",
+        TextStyle::Selah => "qs",
+    }
+}
+
+/// Markers whose content is the rest of the line (or the content of a `<para>` in USX)
+pub(super) enum LineMarker {
+    Id,
+    Name,
+    ShortName,
+    LongName,
+    Abbreviation,
+    Title,
+    Heading,
+    /// Ex: `\r` (the parallel passages under a heading) or `\rem`
+    Ignored,
+}
+
+pub(super) fn line_marker(marker: &str) -> Option<LineMarker> {
+    Some(match marker {
+        "id" => LineMarker::Id,
+        "h" => LineMarker::Name,
+        "toc1" => LineMarker::LongName,
+        "toc2" => LineMarker::ShortName,
+        "toc3" => LineMarker::Abbreviation,
+        "mt" | "mt1" => LineMarker::Title,
+        "s" | "s1" | "s2" | "s3" | "s4" | "ms" | "ms1" | "ms2" | "ms3" | "d" | "qa" => {
+            LineMarker::Heading
+        }
+        "ide" | "rem" | "sts" | "usfm" | "cl" | "r" | "mr" | "sr" | "mt2" | "mt3" | "mt4"
+        | "mte" | "cd" | "sp" => LineMarker::Ignored,
+        _ => return None,
+    })
+}
+
+struct NoteBuilder {
+    kind: NoteKind,
+    offset: usize,
+    /// the caller (like `+`) hasn't been read yet
+    needs_caller: bool,
+    /// text is going into the origin (`\fr` or `\xo`) rather than the note text
+    in_origin: bool,
+    origin: String,
+    text: String,
+}
+
+/// What the next text is for
+enum Expecting {
+    Content,
+    Chapter,
+    Verse,
+    Line(LineMarker),
+}
+
+/// The name and abbreviations of a book, from `\id`, `\h`, `\toc1`, ...
+#[derive(Default)]
+pub(super) struct BookNames {
+    code: Option<String>,
+    name: Option<String>,
+    short_name: Option<String>,
+    long_name: Option<String>,
+    abbreviation: Option<String>,
+    title: Option<String>,
+}
+
+impl BookNames {
+    /// Headings aren't part of the name, so they are handled by the caller
+    pub(super) fn set(&mut self, marker: &LineMarker, line: &str) {
+        let line = line.trim().to_string();
+        match marker {
+            LineMarker::Id => self.code = line.split_whitespace().next().map(str::to_string),
+            LineMarker::Name => self.name = Some(line),
+            LineMarker::ShortName => self.short_name = Some(line),
+            LineMarker::LongName => self.long_name = Some(line),
+            LineMarker::Abbreviation => self.abbreviation = Some(line),
+            LineMarker::Title => {
+                self.title.get_or_insert(line);
+            }
+            LineMarker::Heading | LineMarker::Ignored => {}
+        }
+    }
+
+    /// Returns [`None`] for books outside of the 66
+    pub(super) fn into_book(
+        self,
+        chapters: Vec<Vec<VerseData>>,
+    ) -> Result<Option<BookData>, String> {
+        let code = self
+            .code
+            .ok_or("book is missing its book code (like `\\id GEN`)")?;
+        let Some(id) = book_id(&code) else {
+            return Ok(None);
+        };
+        let name = self
+            .name
+            .clone()
+            .or(self.short_name.clone())
+            .or(self.long_name.clone())
+            .or(self.title.clone())
+            .unwrap_or(code.clone());
+        let abbreviations = [
+            self.abbreviation,
+            self.short_name,
+            self.long_name,
+            Some(code),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|abbreviation| abbreviation.trim_end_matches('.').to_string())
+        .filter(|abbreviation| !abbreviation.is_empty() && *abbreviation != name)
+        .collect();
+        Ok(Some(BookData {
+            id,
+            name,
+            abbreviations,
+            chapters,
+        }))
+    }
+}
+
+#[derive(Default)]
+struct BookParser {
+    names: BookNames,
+    builder: BookBuilder,
+    chapter: usize,
+    /// the text of the `\w` that is being read
+    word: Option<String>,
+    note: Option<NoteBuilder>,
+    in_milestone: bool,
+}
+
+impl BookParser {
+    fn start_chapter(&mut self, text: &str) {
+        let Some(chapter) = leading_number(text) else {
+            return;
+        };
+        self.chapter = chapter;
+        self.builder.start_chapter(chapter);
+    }
+
+    /// Ex: `16`, `17-18` (a combined verse) or `4a`
+    fn start_verse(&mut self, text: &str) {
+        self.builder.finish_verse();
+        let Some(start) = leading_number(text) else {
+            return;
+        };
+        let end = text
+            .trim_start()
+            .split_once(['-', '–'])
+            .and_then(|(_, end)| leading_number(end))
+            .unwrap_or(start);
+        if self.chapter != 0 {
+            self.builder.start_verse(self.chapter, start, end);
+        }
+    }
+
+    fn finish_word(&mut self) {
+        let Some(word) = self.word.take() else {
+            return;
+        };
+        let (text, attributes) = word.split_once('|').unwrap_or((&word, ""));
+        let (mut strong, mut lemma, mut morph) = (None, None, None);
+        for (key, value) in parse_attributes(attributes) {
+            match key {
+                "strong" | "x-strong" => strong = Some(value.to_string()),
+                "lemma" | "x-lemma" => lemma = Some(value.to_string()),
+                "x-morph" | "morph" => morph = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        self.builder.push_word(text, strong, lemma, morph);
+    }
+
+    fn finish_note(&mut self) {
+        if let Some(note) = self.note.take() {
+            self.builder
+                .push_note(note.kind, note.offset, &note.origin, &note.text);
+        }
+    }
+
+    fn note_text(&mut self, mut text: &str) {
+        let Some(note) = self.note.as_mut() else {
+            return;
+        };
+        if note.needs_caller {
+            let trimmed = text.trim_start();
+            if trimmed.is_empty() {
+                return;
+            }
+            note.needs_caller = false;
+            text = trimmed
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, rest)| rest);
+        }
+        if note.in_origin {
+            note.origin.push_str(text);
+        } else {
+            note.text.push_str(text);
+            note.text.push(' ');
+        }
+    }
+
+    fn marker(&mut self, name: &str, end: bool) -> Expecting {
+        if name.is_empty() || name.ends_with("-s") || name.ends_with("-e") {
+            // milestones (like `\qt-s |who="Jesus"\*`) only have attributes
+            self.in_milestone = !name.is_empty();
+            return Expecting::Content;
+        }
+        if let Some(note) = self.note.as_mut() {
+            match name {
+                "f" | "fe" | "ef" | "x" | "ex" if end => self.finish_note(),
+                "fr" | "xo" => note.in_origin = !end,
+                _ if !end => note.in_origin = false,
+                _ => {}
+            }
+            return Expecting::Content;
+        }
+        if end {
+            if name == "w" {
+                self.finish_word();
+            }
+            if let Some(style) = text_style(name) {
+                self.builder.end_style(style);
+            }
+            return Expecting::Content;
+        }
+        if let Some(style) = text_style(name) {
+            self.builder.start_style(style);
+            return Expecting::Content;
+        }
+        match name {
+            "c" => return Expecting::Chapter,
+            "v" => return Expecting::Verse,
+            "w" => self.word = Some(String::new()),
+            "f" | "fe" | "ef" | "x" | "ex" => {
+                self.note = Some(NoteBuilder {
+                    kind: if name.starts_with('f') || name == "ef" {
+                        NoteKind::Footnote
+                    } else {
+                        NoteKind::CrossReference
+                    },
+                    offset: self.builder.content_end(),
+                    needs_caller: true,
+                    in_origin: false,
+                    origin: String::new(),
+                    text: String::new(),
+                })
+            }
+            _ => {
+                if let Some(marker) = line_marker(name) {
+                    return Expecting::Line(marker);
+                }
+                if let Some(style) = paragraph_style(name) {
+                    self.builder.paragraph(style);
+                }
+            }
+        }
+        Expecting::Content
+    }
+
+    fn line(&mut self, marker: LineMarker, line: &str) {
+        match marker {
+            LineMarker::Heading => self.builder.heading(line),
+            marker => self.names.set(&marker, line),
+        }
+    }
+
+    fn text(&mut self, expecting: Expecting, text: &str) {
+        if self.in_milestone {
+            return;
+        }
+        if self.note.is_some() {
+            return self.note_text(text);
+        }
+        if let Some(word) = self.word.as_mut() {
+            return word.push_str(text);
+        }
+        let rest = match expecting {
+            Expecting::Content => text,
+            Expecting::Chapter => {
+                self.start_chapter(text);
+                skip_number(text)
+            }
+            Expecting::Verse => {
+                self.start_verse(text);
+                skip_number(text)
+            }
+            Expecting::Line(marker) => {
+                let (line, rest) = text.split_once('\n').unwrap_or((text, ""));
+                self.line(marker, line);
+                rest
+            }
+        };
+        self.builder.push_text(rest);
+    }
+
+    fn finish(self) -> Result<Option<BookData>, String> {
+        self.names.into_book(self.builder.finish())
+    }
+}
+
+pub(super) fn leading_number(text: &str) -> Option<usize> {
+    let text = text.trim_start();
+    let end = text
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(text.len());
+    text[..end].parse().ok()
+}
+
+/// The text after a chapter or verse number (like `17-18` or `4a`)
+fn skip_number(text: &str) -> &str {
+    let text = text.trim_start();
+    text.find(char::is_whitespace)
+        .map_or("", |index| &text[index..])
+}
+
+/// - `strong="H7225" x-morph="He,R:Ncfsa"` is `[("strong", "H7225"), ("x-morph", "He,R:Ncfsa")]`
+/// - A value on its own is the lemma
+fn parse_attributes(attributes: &str) -> Vec<(&str, &str)> {
+    static ATTRIBUTE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"([A-Za-z][A-Za-z0-9-]*)\s*=\s*"([^"]*)""#).unwrap());
+    let attributes = attributes.trim();
+    if !attributes.is_empty() && !attributes.contains('=') {
+        return vec![("lemma", attributes)];
+    }
+    ATTRIBUTE
+        .captures_iter(attributes)
+        .map(|captures| {
+            let (_, [key, value]) = captures.extract();
+            (key, value)
+        })
+        .collect()
+}
+
+/// - Parses 1 book of USFM
+/// - Returns [`None`] for books outside of the 66
+pub fn parse_book(source: &str) -> Result<Option<BookData>, String> {
+    let mut parser = BookParser::default();
+    let mut expecting = Expecting::Content;
+    for token in tokenize(source) {
+        match token {
+            Token::Marker { name, end } => {
+                // a marker with nothing after it (like `\c` at the end of the file)
+                if let Expecting::Line(marker) = expecting {
+                    parser.line(marker, "");
+                }
+                expecting = parser.marker(name, end);
+            }
+            Token::Text(text) => {
+                parser.text(expecting, text);
+                expecting = Expecting::Content;
+            }
+        }
+    }
+    parser.finish()
+}
+
+fn paragraph_marker(style: ParagraphStyle) -> String {
+    match style {
+        ParagraphStyle::Prose => String::from("\\p"),
+        ParagraphStyle::Poetry(level) => format!("\\q{level}"),
+    }
+}
+
+/// Ex: `\v 1 \w In|strong="G1722"\w* the beginning was the Word\f + \fr 1:1 \ft Or the Logos\f*`
+fn write_verse(usfm: &mut String, verse_number: usize, verse: &VerseData) {
+    let mut markup = verse_markup(verse).into_iter().peekable();
+    // a paragraph at the start of the verse goes before it
+    if let Some(Markup::Paragraph(style)) =
+        markup.next_if(|piece| matches!(piece, Markup::Paragraph(_)))
+    {
+        usfm.push_str(&paragraph_marker(style));
+        usfm.push('\n');
+    }
+    usfm.push_str(&format!("\\v {verse_number}"));
+    if verse.content().is_some() {
+        usfm.push(' ');
+    }
+    // character styles can't go past the end of a paragraph, so they are closed and opened again
+    let mut styles: Vec<TextStyle> = vec![];
+    // Ex: `\+nd` inside of `\wj`
+    let nested = |styles: &[TextStyle]| if styles.is_empty() { "" } else { "+" };
+    for piece in markup {
+        match piece {
+            Markup::Text(text) => usfm.push_str(text),
+            Markup::Paragraph(style) => {
+                usfm.truncate(usfm.trim_end().len());
+                for (index, style) in styles.iter().enumerate().rev() {
+                    let plus = nested(&styles[..index]);
+                    usfm.push_str(&format!("\\{plus}{}*", text_style_marker(*style)));
+                }
+                usfm.push('\n');
+                usfm.push_str(&paragraph_marker(style));
+                usfm.push(' ');
+                for (index, style) in styles.iter().enumerate() {
+                    let plus = nested(&styles[..index]);
+                    usfm.push_str(&format!("\\{plus}{} ", text_style_marker(*style)));
+                }
+            }
+            Markup::StyleStart(style) => {
+                let plus = nested(&styles);
+                usfm.push_str(&format!("\\{plus}{} ", text_style_marker(style)));
+                styles.push(style);
+            }
+            Markup::StyleEnd(style) => {
+                styles.pop();
+                let plus = nested(&styles);
+                usfm.push_str(&format!("\\{plus}{}*", text_style_marker(style)));
+            }
+            Markup::WordStart(_) => usfm.push_str(&format!("\\{}w ", nested(&styles))),
+            Markup::WordEnd(word) => {
+                let attributes = [
+                    ("strong", &word.strong),
+                    ("lemma", &word.lemma),
+                    ("x-morph", &word.morph),
+                ]
+                .into_iter()
+                .filter_map(|(name, value)| Some(format!("{name}=\"{}\"", value.as_ref()?)))
+                .collect::<Vec<_>>();
+                if !attributes.is_empty() {
+                    usfm.push('|');
+                    usfm.push_str(&attributes.join(" "));
+                }
+                usfm.push_str(&format!("\\{}w*", nested(&styles)));
+            }
+            Markup::Footnote(note) => {
+                usfm.push_str("\\f + ");
+                if let Some(origin) = note.origin.as_ref() {
+                    usfm.push_str(&format!("\\fr {origin} "));
+                }
+                usfm.push_str(&format!("\\ft {}\\f*", note.text));
+            }
+            Markup::CrossReference(note) => {
+                usfm.push_str("\\x - ");
+                if let Some(origin) = note.origin.as_ref() {
+                    usfm.push_str(&format!("\\xo {origin} "));
+                }
+                usfm.push_str(&format!("\\xt {}\\x*", note.text));
+            }
+        }
+    }
+    usfm.push('\n');
+}
+
+/**
+Writes a book as USFM
+
+- The book code is the `\id`, and the name is the `\h`
+- USFM only has room for 3 other abbreviations (`\toc3`, `\toc2` and `\toc1`), so the rest are
+  left out
+- Returns [`None`] for books outside of the 66, which have no book code
+*/
+pub fn write_book(book: &BookData) -> Option<String> {
+    let code = BOOK_CODES.get(book.id - 1)?;
+    let mut usfm = format!("\\id {code}\n\\h {}\n", book.name);
+    let mut abbreviations = book
+        .abbreviations
+        .iter()
+        .filter(|abbreviation| !abbreviation.eq_ignore_ascii_case(code));
+    for marker in ["toc3", "toc2", "toc1"] {
+        if let Some(abbreviation) = abbreviations.next() {
+            usfm.push_str(&format!("\\{marker} {abbreviation}\n"));
+        }
+    }
+    for (chapter, verses) in book.chapters.iter().enumerate() {
+        usfm.push_str(&format!("\\c {}\n", chapter + 1));
+        for (verse_number, verse) in verses.iter().enumerate() {
+            for heading in verse.headings() {
+                usfm.push_str(&format!("\\s1 {heading}\n"));
+            }
+            write_verse(&mut usfm, verse_number + 1, verse);
+        }
+    }
+    Some(usfm)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::{
+        bible_data::{Note, ParagraphBreak, TaggedWord},
+        versification::Versification,
+    };
+
+    use super::{super::parse::assert_round_trip, *};
+
+    const JOHN: &str = r#"\id JHN English: Test Bible
+\h John
+\toc1 The Gospel According to John
+\toc2 John
+\toc3 Jn
+\mt1 John
+\c 1
+\s1 The Word Became Flesh
+\p
+\v 1 \w In|strong="G1722"\w* the beginning was the Word\f + \fr 1:1 \ft Or \fq the Logos\f*, and the Word was with God.\x - \xo 1:1 \xt Gen 1:1\x*
+\v 2 He was in the beginning with \nd God\nd*.
+\c 3
+\p
+\v 16 For God so loved the world,
+\q1 that he gave
+\q2 his only Son,
+\v 17-18 For God did not send his Son into the world \wj to condemn\wj* the world.
+"#;
+
+    #[test]
+    fn parse_usfm_book() {
+        let john = parse_book(JOHN).unwrap().unwrap();
+        assert_eq!(john.id, 43);
+        assert_eq!(john.name, "John");
+        assert_eq!(
+            john.abbreviations,
+            vec!["Jn", "The Gospel According to John", "JHN"]
+        );
+        assert_eq!(john.chapters.len(), 3);
+        assert_eq!(john.chapters[1].len(), 0);
+        assert_eq!(john.chapters[2].len(), 18);
+
+        let verse = &john.chapters[0][0];
+        assert_eq!(
+            verse.content(),
+            Some("In the beginning was the Word, and the Word was with God.")
+        );
+        assert_eq!(verse.headings(), ["The Word Became Flesh"]);
+        assert_eq!(
+            verse.paragraphs(),
+            [ParagraphBreak {
+                offset: 0,
+                style: ParagraphStyle::Prose
+            }]
+        );
+        assert_eq!(
+            verse.footnotes(),
+            [Note {
+                offset: 29,
+                origin: Some(String::from("1:1")),
+                text: String::from("Or the Logos"),
+            }]
+        );
+        assert_eq!(verse.cross_references()[0].text, "Gen 1:1");
+        assert_eq!(verse.cross_references()[0].offset, 57);
+        assert_eq!(
+            verse.words(),
+            [TaggedWord {
+                range: 0..2,
+                strong: Some(String::from("G1722")),
+                lemma: None,
+                morph: None,
+            }]
+        );
+        assert!(john.chapters[0][1].headings().is_empty());
+    }
+
+    #[test]
+    fn parse_usfm_poetry_and_combined_verses() {
+        let john = parse_book(JOHN).unwrap().unwrap();
+        let verse = &john.chapters[2][15];
+        assert_eq!(
+            verse.content(),
+            Some("For God so loved the world, that he gave his only Son,")
+        );
+        assert_eq!(
+            verse
+                .paragraphs()
+                .iter()
+                .map(|paragraph| (paragraph.offset, paragraph.style))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, ParagraphStyle::Prose),
+                (28, ParagraphStyle::Poetry(1)),
+                (41, ParagraphStyle::Poetry(2)),
+            ]
+        );
+        assert_eq!(
+            john.chapters[2][16].content(),
+            Some("For God did not send his Son into the world to condemn the world.")
+        );
+        assert_eq!(john.chapters[2][17].content(), None);
+    }
+
+    #[test]
+    fn parse_usfm_styles() {
+        let john = parse_book(JOHN).unwrap().unwrap();
+        fn styled(verse: &VerseData) -> Vec<(&str, TextStyle)> {
+            verse
+                .styles()
+                .iter()
+                .map(|styled| {
+                    (
+                        &verse.content().unwrap()[styled.range.clone()],
+                        styled.style,
+                    )
+                })
+                .collect()
+        }
+        assert_eq!(
+            styled(&john.chapters[0][1]),
+            vec![("God", TextStyle::DivineName)]
+        );
+        assert_eq!(
+            styled(&john.chapters[2][16]),
+            vec![("to condemn", TextStyle::WordsOfJesus)]
+        );
+
+        // nested, and across a line of poetry
+        let psalm = parse_book(
+            "\\id PSA\n\\c 3\n\\q1\n\\v 4 \\wj I cried to the \\+nd Lord\\+nd*\n\\q2 and he answered\\wj* \\qs Selah\\qs*\n",
+        )
+        .unwrap()
+        .unwrap();
+        let verse = &psalm.chapters[2][3];
+        assert_eq!(
+            verse.content(),
+            Some("I cried to the Lord and he answered Selah")
+        );
+        assert_eq!(
+            styled(verse),
+            vec![
+                ("Lord", TextStyle::DivineName),
+                (
+                    "I cried to the Lord and he answered",
+                    TextStyle::WordsOfJesus
+                ),
+                ("Selah", TextStyle::Selah),
+            ]
+        );
+        let usfm = write_book(&psalm).unwrap();
+        assert!(usfm.contains("\\v 4 \\wj I cried to the \\+nd Lord\\+nd*\\wj*\n\\q2 \\wj and he answered\\wj* \\qs Selah\\qs*"));
+        assert_eq!(parse_book(&usfm).unwrap().unwrap().chapters, psalm.chapters);
+    }
+
+    #[test]
+    fn usfm_round_trip() {
+        let translation = translation_from_path(Path::new("test"));
+        let before = USFMBible::parse(translation.clone(), &[JOHN])
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        let usfm = USFMBible::from_bible_data(&before)
+            .books
+            .iter()
+            .filter_map(write_book)
+            .collect::<Vec<_>>();
+        let after = USFMBible::parse(
+            translation,
+            &usfm.iter().map(String::as_str).collect::<Vec<_>>(),
+        )
+        .unwrap()
+        .as_bible_data()
+        .unwrap();
+        assert_round_trip(&before, &after);
+    }
+
+    #[test]
+    fn usfm_round_trip_skips_other_books() {
+        let translation = translation_from_path(Path::new("test"));
+        let before = USFMBible::parse(translation.clone(), &[JOHN])
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        let mut books = before.to_books();
+        books.push(BookData {
+            id: 67,
+            name: String::from("Tobit"),
+            abbreviations: vec![],
+            chapters: vec![vec![VerseData::new(Some(String::from("Tobit")))]],
+        });
+        let with_tobit = BibleData::from_books(translation, books).unwrap();
+
+        let path = std::env::temp_dir().join("bible_api_usfm_other_books");
+        USFMBible::from_bible_data(&with_tobit)
+            .write_file(&path)
+            .unwrap();
+        let after = USFMBible::parse_file(&path)
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        std::fs::remove_dir_all(&path).unwrap();
+        assert_eq!(before.to_books(), after.to_books());
+    }
+
+    #[test]
+    fn usfm_as_bible_data() {
+        let translation = JSONTranslation {
+            name: String::from("Test Bible"),
+            language: String::from("English"),
+            abbreviation: String::from("TB"),
+            versification: Versification::default(),
+        };
+        let bible = USFMBible::parse(translation, &[JOHN, "\\id TOB\n\\c 1\n\\v 1 Tobit"])
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        assert!(bible.get_book(1).is_none());
+        assert_eq!(bible.get_book_id("jn"), Some(43));
+        assert_eq!(bible.get_book_id("jhn"), Some(43));
+        let verse = bible.get_verse(43, 1, 1).unwrap();
+        assert_eq!(verse.get_data().footnotes()[0].text, "Or the Logos");
+        assert_eq!(
+            bible.get_verse(43, 3, 16).unwrap().get_content(),
+            Some("For God so loved the world, that he gave his only Son,")
+        );
+    }
+
+    #[test]
+    fn usfm_duplicate_books() {
+        let translation = translation_from_path(Path::new("test"));
+        let error = USFMBible::parse(
+            translation,
+            &[
+                JOHN,
+                "\\id JHN\n\\c 1\n\\v 1 In the beginning",
+                "\\id ACT\n\\c 1\n\\v 1 In the first book",
+            ],
+        )
+        .unwrap()
+        .as_bible_data()
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "JHN (book id 43) is in the translation twice"
+        );
+    }
+}
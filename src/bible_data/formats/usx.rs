@@ -0,0 +1,427 @@
+use std::path::Path;
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::bible_data::{
+    bible_data::{BibleData, BookData},
+    versification::Versification,
+};
+
+use super::{
+    builder::{BookBuilder, NoteKind},
+    json::JSONTranslation,
+    parse::{read_sources, translation_from_path, ParseBibleData},
+    usfm::{leading_number, line_marker, paragraph_style, text_style, BookNames, LineMarker},
+};
+
+/**
+A translation in [USX](https://ubsicap.github.io/usx/) (the XML form of USFM), with a file for
+each book, like the bundles from the Digital Bible Library
+
+- Books are identified by their USFM code (`<book code="GEN">`), and the styles are the USFM
+  markers, so this keeps the same things as [`USFMBible`](super::usfm::USFMBible)
+- Verses and chapters can be milestones (USX 3, with `eid`) or just the start (USX 2)
+- [`ParseBibleData::parse_file`] reads the translation from the `metadata.xml` of a bundle, and
+  otherwise names it after the file (or folder)
+*/
+pub struct USXBible {
+    pub translation: JSONTranslation,
+    pub books: Vec<BookData>,
+}
+
+impl USXBible {
+    /// Parses each USX book in `sources`
+    pub fn parse(
+        translation: JSONTranslation,
+        sources: &[&str],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut books = vec![];
+        for source in sources {
+            books.extend(parse_book(source)?);
+        }
+        Ok(Self { translation, books })
+    }
+}
+
+impl ParseBibleData for USXBible {
+    /// `path` is either a USX file or a folder of them (like a Digital Bible Library bundle)
+    fn parse_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let sources = read_sources(path, &["usx"])?;
+        let metadata = path.join("metadata.xml");
+        let translation = match metadata.is_file() {
+            true => parse_metadata(&std::fs::read_to_string(metadata)?)?,
+            false => translation_from_path(path),
+        };
+        Self::parse(
+            translation,
+            &sources.iter().map(String::as_str).collect::<Vec<_>>(),
+        )
+    }
+
+    fn as_bible_data(self) -> Result<BibleData, Box<dyn std::error::Error>> {
+        BibleData::from_books(self.translation, self.books)
+    }
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+/// The name, abbreviation and language in the `metadata.xml` of a Digital Bible Library bundle
+fn parse_metadata(source: &str) -> Result<JSONTranslation, Box<dyn std::error::Error>> {
+    let mut reader = Reader::from_str(source);
+    let mut path: Vec<Vec<u8>> = vec![];
+    let (mut name, mut abbreviation, mut language) = (None, None, None);
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => path.push(element.local_name().as_ref().to_vec()),
+            Event::End(_) => {
+                path.pop();
+            }
+            Event::Text(text) => {
+                let text = text.unescape()?.trim().to_string();
+                let field = match path.as_slice() {
+                    [.., parent, field] if parent == b"identification" => field.as_slice(),
+                    [.., parent, field] if parent == b"language" => match field.as_slice() {
+                        b"name" => b"language",
+                        _ => continue,
+                    },
+                    _ => continue,
+                };
+                match field {
+                    b"name" => name.get_or_insert(text),
+                    b"abbreviation" => abbreviation.get_or_insert(text),
+                    b"language" => language.get_or_insert(text),
+                    _ => continue,
+                };
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let abbreviation: String = abbreviation.unwrap_or_default();
+    Ok(JSONTranslation {
+        name: name.unwrap_or(abbreviation.clone()),
+        language: language.unwrap_or_default(),
+        abbreviation,
+        versification: Versification::default(),
+    })
+}
+
+/// What text is being read into (besides verse content)
+enum Capture {
+    /// the text of a `<para>` like `\h` or `\s1`
+    Line(LineMarker),
+    Word {
+        strong: Option<String>,
+        lemma: Option<String>,
+        morph: Option<String>,
+    },
+    Note {
+        kind: NoteKind,
+        offset: usize,
+        origin: String,
+    },
+}
+
+#[derive(Default)]
+struct USXParser {
+    names: BookNames,
+    builder: BookBuilder,
+    chapter: usize,
+    /// the element (and its depth) that is being captured, with its text
+    capture: Option<(Vec<u8>, usize, Capture, String)>,
+    /// the styles of the open `<char>`s
+    chars: Vec<String>,
+    depth: usize,
+    /// the depth of an element whose text is skipped
+    skip_depth: Option<usize>,
+}
+
+impl USXParser {
+    fn start(&mut self, element: &BytesStart, empty: bool) {
+        let name = element.local_name().as_ref().to_vec();
+        if !empty {
+            self.depth += 1;
+        }
+        if self.skip_depth.is_some() {
+            return;
+        }
+        let style = attribute(element, "style").unwrap_or_default();
+        if name == b"char" && !empty {
+            self.chars.push(style.clone());
+        }
+        let in_note = matches!(self.capture, Some((_, _, Capture::Note { .. }, _)));
+        let skip = match name.as_slice() {
+            // the text of `<book>` is a description
+            b"book" | b"figure" | b"sidebar" => true,
+            b"note" => self.capture.is_some() && !in_note,
+            _ => false,
+        };
+        if name == b"book" {
+            self.names.set(
+                &LineMarker::Id,
+                &attribute(element, "code").unwrap_or_default(),
+            );
+        }
+        if skip && !empty {
+            self.skip_depth = Some(self.depth);
+            return;
+        }
+        if self.capture.is_some() {
+            return;
+        }
+        match name.as_slice() {
+            b"chapter" if attribute(element, "eid").is_some() => self.builder.finish_verse(),
+            b"chapter" => {
+                if let Some(chapter) = attribute(element, "number").and_then(|n| leading_number(&n))
+                {
+                    self.chapter = chapter;
+                    self.builder.start_chapter(chapter);
+                }
+            }
+            b"verse" if attribute(element, "eid").is_some() => self.builder.finish_verse(),
+            b"verse" => {
+                let number = attribute(element, "number").unwrap_or_default();
+                self.builder.finish_verse();
+                if let (Some(start), true) = (leading_number(&number), self.chapter != 0) {
+                    let end = number
+                        .split_once(['-', '–'])
+                        .and_then(|(_, end)| leading_number(end))
+                        .unwrap_or(start);
+                    self.builder.start_verse(self.chapter, start, end);
+                }
+            }
+            b"para" => {
+                if let Some(marker) = line_marker(&style) {
+                    if !empty {
+                        self.capture =
+                            Some((name, self.depth, Capture::Line(marker), String::new()));
+                    }
+                } else if let Some(style) = paragraph_style(&style) {
+                    self.builder.paragraph(style);
+                }
+            }
+            b"char" if text_style(&style).is_some() && !empty => {
+                self.builder.start_style(text_style(&style).unwrap());
+            }
+            b"char" if style == "w" && !empty => {
+                let capture = Capture::Word {
+                    strong: attribute(element, "strong"),
+                    lemma: attribute(element, "lemma"),
+                    morph: attribute(element, "x-morph"),
+                };
+                self.capture = Some((name, self.depth, capture, String::new()));
+            }
+            b"note" if !empty => {
+                let kind = match style.as_str() {
+                    "x" | "ex" => NoteKind::CrossReference,
+                    _ => NoteKind::Footnote,
+                };
+                let capture = Capture::Note {
+                    kind,
+                    offset: self.builder.content_end(),
+                    origin: String::new(),
+                };
+                self.capture = Some((name, self.depth, capture, String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &[u8]) {
+        let depth = self.depth;
+        self.depth = self.depth.saturating_sub(1);
+        let style = if name == b"char" {
+            self.chars.pop()
+        } else {
+            None
+        };
+        if let Some(skip_depth) = self.skip_depth {
+            if skip_depth == depth {
+                self.skip_depth = None;
+            }
+            return;
+        }
+        if let Some(style) = style.as_deref().and_then(text_style) {
+            if self.capture.is_none() {
+                self.builder.end_style(style);
+            }
+        }
+        let is_captured = self
+            .capture
+            .as_ref()
+            .is_some_and(|(element, capture_depth, _, _)| {
+                element == name && *capture_depth == depth
+            });
+        if !is_captured {
+            return;
+        }
+        let (_, _, capture, text) = self.capture.take().unwrap();
+        match capture {
+            Capture::Line(LineMarker::Heading) => self.builder.heading(&text),
+            Capture::Line(marker) => self.names.set(&marker, &text),
+            Capture::Word {
+                strong,
+                lemma,
+                morph,
+            } => self.builder.push_word(&text, strong, lemma, morph),
+            Capture::Note {
+                kind,
+                offset,
+                origin,
+            } => self.builder.push_note(kind, offset, &origin, &text),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skip_depth.is_some() {
+            return;
+        }
+        let in_origin = self
+            .chars
+            .last()
+            .is_some_and(|style| style == "fr" || style == "xo");
+        match self.capture.as_mut() {
+            Some((_, _, Capture::Note { origin, .. }, _)) if in_origin => origin.push_str(text),
+            Some((_, _, Capture::Note { .. }, captured)) => {
+                captured.push_str(text);
+                captured.push(' ');
+            }
+            Some((_, _, _, captured)) => captured.push_str(text),
+            None => self.builder.push_text(text),
+        }
+    }
+}
+
+/// - Parses 1 book of USX
+/// - Returns [`None`] for books outside of the 66
+pub fn parse_book(source: &str) -> Result<Option<BookData>, Box<dyn std::error::Error>> {
+    let mut reader = Reader::from_str(source);
+    let mut parser = USXParser::default();
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => parser.start(&element, false),
+            Event::Empty(element) => parser.start(&element, true),
+            Event::End(element) => parser.end(element.local_name().as_ref()),
+            Event::Text(text) => parser.text(&text.unescape()?),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(parser.names.into_book(parser.builder.finish())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::bible_data::{Note, ParagraphStyle, StyledText, TaggedWord, TextStyle};
+
+    use super::*;
+
+    const JOHN: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<usx version="3.0">
+  <book code="JHN" style="id">Test Bible</book>
+  <para style="h">John</para>
+  <para style="toc3">Jn</para>
+  <chapter number="1" style="c" sid="JHN 1" />
+  <para style="s1">The Word Became Flesh</para>
+  <para style="p"><verse number="1" style="v" sid="JHN 1:1" /><char style="w" strong="G1722">In</char> the beginning was the Word<note caller="+" style="f"><char style="fr" closed="false">1:1 </char><char style="ft" closed="false">Or the Logos</char></note>, and the Word was with God.<verse eid="JHN 1:1" /></para>
+  <para style="q1"><verse number="2-3" style="v" sid="JHN 1:2-3" />He was in the beginning<note caller="-" style="x"><char style="xo">1:2 </char><char style="xt">Gen 1:1</char></note></para>
+  <para style="q2">with <char style="nd">God</char>.<verse eid="JHN 1:2-3" /></para>
+  <chapter eid="JHN 1" />
+</usx>"#;
+
+    const METADATA: &str = r#"<DBLMetadata id="1" revision="1">
+  <identification>
+    <name>Test Bible</name>
+    <nameLocal>Test Bible</nameLocal>
+    <abbreviation>TB</abbreviation>
+  </identification>
+  <language>
+    <iso>eng</iso>
+    <name>English</name>
+  </language>
+</DBLMetadata>"#;
+
+    #[test]
+    fn parse_usx_book() {
+        let john = parse_book(JOHN).unwrap().unwrap();
+        assert_eq!(john.id, 43);
+        assert_eq!(john.name, "John");
+        assert_eq!(john.abbreviations, vec!["Jn", "JHN"]);
+        assert_eq!(john.chapters[0].len(), 3);
+
+        let verse = &john.chapters[0][0];
+        assert_eq!(
+            verse.content(),
+            Some("In the beginning was the Word, and the Word was with God.")
+        );
+        assert_eq!(verse.headings(), ["The Word Became Flesh"]);
+        assert_eq!(
+            verse.words(),
+            [TaggedWord {
+                range: 0..2,
+                strong: Some(String::from("G1722")),
+                lemma: None,
+                morph: None,
+            }]
+        );
+        assert_eq!(
+            verse.footnotes(),
+            [Note {
+                offset: 29,
+                origin: Some(String::from("1:1")),
+                text: String::from("Or the Logos"),
+            }]
+        );
+
+        let verse = &john.chapters[0][1];
+        assert_eq!(verse.content(), Some("He was in the beginning with God."));
+        assert_eq!(verse.cross_references()[0].text, "Gen 1:1");
+        assert_eq!(
+            verse
+                .paragraphs()
+                .iter()
+                .map(|paragraph| (paragraph.offset, paragraph.style))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, ParagraphStyle::Poetry(1)),
+                (24, ParagraphStyle::Poetry(2))
+            ]
+        );
+        assert_eq!(
+            verse.styles(),
+            [StyledText {
+                range: 29..32,
+                style: TextStyle::DivineName
+            }]
+        );
+        assert_eq!(john.chapters[0][2].content(), None);
+    }
+
+    #[test]
+    fn parse_usx_metadata() {
+        let translation = parse_metadata(METADATA).unwrap();
+        assert_eq!(translation.name, "Test Bible");
+        assert_eq!(translation.abbreviation, "TB");
+        assert_eq!(translation.language, "English");
+
+        let bible = USXBible::parse(translation, &[JOHN])
+            .unwrap()
+            .as_bible_data()
+            .unwrap();
+        assert_eq!(bible.get_book_id("jn"), Some(43));
+        assert_eq!(
+            bible.get_verse(43, 1, 2).unwrap().get_content(),
+            Some("He was in the beginning with God.")
+        );
+    }
+}
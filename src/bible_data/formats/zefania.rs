@@ -0,0 +1,440 @@
+use std::path::Path;
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::bible_data::{
+    bible_data::{BibleData, BookData, ParagraphStyle, TextStyle},
+    versification::Versification,
+};
+
+use super::{
+    builder::{BookBuilder, NoteKind},
+    json::JSONTranslation,
+    osis,
+    parse::ParseBibleData,
+    usfm,
+};
+
+/**
+A translation in [Zefania XML](https://sourceforge.net/projects/zefania-sharp/)
+
+- Books are numbered by `bnumber`, where Genesis = 1 (and numbers after 66, like the
+  deuterocanon, are skipped), or by an OSIS or USFM book code in `bsname` when there is no number
+- Captions, line breaks, notes, cross references and `<gr str="7225">` are kept on each
+  [`VerseData`](crate::bible_data::bible_data::VerseData), where Strong's numbers get an `H` or
+  `G` for the testament (so `7225` in Genesis is `H7225`)
+- The translation comes from `<INFORMATION>`
+*/
+pub struct ZefaniaBible {
+    pub translation: JSONTranslation,
+    pub books: Vec<BookData>,
+}
+
+impl ParseBibleData for ZefaniaBible {
+    fn parse_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    fn as_bible_data(self) -> Result<BibleData, Box<dyn std::error::Error>> {
+        BibleData::from_books(self.translation, self.books)
+    }
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+/// - The book id of a `<BIBLEBOOK>`
+/// - Ex: `bnumber="1"`, or `bsname="Gen"` when there is no number
+fn zefania_book_id(element: &BytesStart) -> Option<usize> {
+    match attribute(element, "bnumber") {
+        Some(number) => number
+            .trim()
+            .parse()
+            .ok()
+            .filter(|id| (1..=66).contains(id)),
+        None => {
+            let code = attribute(element, "bsname")?;
+            osis::book_id(&code).or_else(|| usfm::book_id(&code))
+        }
+    }
+}
+
+/// What text is being read into (besides verse content)
+enum Capture {
+    Title,
+    Identifier,
+    Language,
+    Caption,
+    Word {
+        strong: Option<String>,
+        morph: Option<String>,
+    },
+    Note {
+        kind: NoteKind,
+        offset: usize,
+    },
+}
+
+#[derive(Default)]
+struct ZefaniaParser {
+    bible_name: Option<String>,
+    name: Option<String>,
+    abbreviation: Option<String>,
+    language: Option<String>,
+    books: Vec<BookData>,
+    /// the book id, name, abbreviation, and content of the current book
+    book: Option<(usize, String, Option<String>, BookBuilder)>,
+    chapter: usize,
+    /// the element that is being captured, with its text
+    capture: Option<(Vec<u8>, Capture, String)>,
+    depth: usize,
+    /// the depth of an element whose text is skipped
+    skip_depth: Option<usize>,
+    /// the style of each open `<STYLE>`
+    styles: Vec<Option<TextStyle>>,
+}
+
+/// - Ex: `<STYLE fs="divineName">` is [`TextStyle::DivineName`]
+/// - Red text (like `<STYLE css="color:#ff0000">`) is the words of Jesus
+fn text_style(element: &BytesStart) -> Option<TextStyle> {
+    let css = attribute(element, "css")
+        .unwrap_or_default()
+        .to_lowercase()
+        .replace(' ', "");
+    if ["color:red", "color:#ff0000", "color:#f00"]
+        .iter()
+        .any(|red| css.contains(red))
+    {
+        return Some(TextStyle::WordsOfJesus);
+    }
+    match attribute(element, "fs")?.as_str() {
+        "divineName" => Some(TextStyle::DivineName),
+        "small-caps" => Some(TextStyle::SmallCaps),
+        _ => None,
+    }
+}
+
+impl ZefaniaParser {
+    fn builder(&mut self) -> Option<&mut BookBuilder> {
+        self.book.as_mut().map(|(_, _, _, builder)| builder)
+    }
+
+    fn finish_book(&mut self) {
+        if let Some((id, name, abbreviation, builder)) = self.book.take() {
+            self.books.push(BookData {
+                id,
+                name,
+                abbreviations: abbreviation.into_iter().collect(),
+                chapters: builder.finish(),
+            });
+        }
+    }
+
+    fn start(&mut self, element: &BytesStart, empty: bool) {
+        let name = element.local_name().as_ref().to_ascii_uppercase();
+        if !empty {
+            self.depth += 1;
+        }
+        if self.skip_depth.is_some() {
+            return;
+        }
+        if self.capture.is_some() {
+            return;
+        }
+        let capture = match name.as_slice() {
+            b"XMLBIBLE" => {
+                self.bible_name = attribute(element, "biblename");
+                None
+            }
+            b"TITLE" => Some(Capture::Title),
+            b"IDENTIFIER" => Some(Capture::Identifier),
+            b"LANGUAGE" => Some(Capture::Language),
+            b"BIBLEBOOK" => {
+                self.finish_book();
+                self.chapter = 0;
+                self.book = zefania_book_id(element).map(|id| {
+                    let abbreviation = attribute(element, "bsname");
+                    let name = attribute(element, "bname")
+                        .or(abbreviation.clone())
+                        .unwrap_or_else(|| osis::BOOK_CODES[id - 1].to_string());
+                    (id, name, abbreviation, BookBuilder::default())
+                });
+                None
+            }
+            b"CHAPTER" => {
+                let chapter = attribute(element, "cnumber").and_then(|n| n.trim().parse().ok());
+                if let (Some(chapter), Some(builder)) = (chapter, self.builder()) {
+                    builder.start_chapter(chapter);
+                }
+                self.chapter = chapter.unwrap_or(0);
+                None
+            }
+            b"VERS" => {
+                let number = attribute(element, "vnumber").unwrap_or_default();
+                let start = usfm::leading_number(&number);
+                let end = number
+                    .split_once(['-', '–'])
+                    .and_then(|(_, end)| usfm::leading_number(end));
+                let chapter = self.chapter;
+                if let (Some(start), Some(builder)) = (start, self.builder()) {
+                    builder.start_verse(chapter, start, end.unwrap_or(start));
+                }
+                None
+            }
+            b"CAPTION" => Some(Capture::Caption),
+            b"BR" => {
+                if let Some(builder) = self.builder() {
+                    builder.paragraph(ParagraphStyle::Prose);
+                }
+                None
+            }
+            b"GR" => {
+                // `str` is only the number, so the testament decides the language
+                let prefix = match self.book.as_ref().map_or(0, |book| book.0) {
+                    ..=39 => "H",
+                    _ => "G",
+                };
+                Some(Capture::Word {
+                    strong: attribute(element, "str").map(|strong| match strong.parse::<usize>() {
+                        Ok(number) => format!("{prefix}{number}"),
+                        Err(_) => strong,
+                    }),
+                    morph: attribute(element, "rmac"),
+                })
+            }
+            b"NOTE" | b"XREF" => {
+                let kind = match name.as_slice() {
+                    b"XREF" => NoteKind::CrossReference,
+                    _ => NoteKind::Footnote,
+                };
+                let offset = self.builder().map_or(0, |builder| builder.content_end());
+                // a cross reference can be just an attribute, like `<XREF mscope="43;1;1"/>`
+                if let (true, Some(builder)) = (empty, self.builder()) {
+                    let scope = attribute(element, "fscope").or(attribute(element, "mscope"));
+                    builder.push_note(kind, offset, "", &scope.unwrap_or_default());
+                }
+                Some(Capture::Note { kind, offset })
+            }
+            b"STYLE" if !empty => {
+                let style = text_style(element);
+                self.styles.push(style);
+                if let (Some(style), Some(builder)) = (style, self.builder()) {
+                    builder.start_style(style);
+                }
+                None
+            }
+            b"PROLOG" | b"REMARK" | b"MEDIA" => {
+                if !empty {
+                    self.skip_depth = Some(self.depth);
+                }
+                None
+            }
+            _ => None,
+        };
+        if let (Some(capture), false) = (capture, empty) {
+            self.capture = Some((name, capture, String::new()));
+        }
+    }
+
+    fn end(&mut self, name: &[u8]) {
+        let depth = self.depth;
+        self.depth = self.depth.saturating_sub(1);
+        if let Some(skip_depth) = self.skip_depth {
+            if skip_depth == depth {
+                self.skip_depth = None;
+            }
+            return;
+        }
+        let name = name.to_ascii_uppercase();
+        if self
+            .capture
+            .as_ref()
+            .is_some_and(|(element, _, _)| *element == name)
+        {
+            let (_, capture, text) = self.capture.take().unwrap();
+            return self.finish_capture(capture, text);
+        }
+        if self.capture.is_some() {
+            return;
+        }
+        match name.as_slice() {
+            b"BIBLEBOOK" => self.finish_book(),
+            b"VERS" | b"CHAPTER" => {
+                if let Some(builder) = self.builder() {
+                    builder.finish_verse();
+                }
+            }
+            b"STYLE" => {
+                if let (Some(Some(style)), Some(builder)) = (self.styles.pop(), self.builder()) {
+                    builder.end_style(style);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish_capture(&mut self, capture: Capture, text: String) {
+        let text = text.trim().to_string();
+        match capture {
+            Capture::Title => {
+                self.name.get_or_insert(text);
+            }
+            Capture::Identifier => {
+                self.abbreviation.get_or_insert(text);
+            }
+            Capture::Language => {
+                self.language.get_or_insert(text);
+            }
+            Capture::Caption => {
+                if let Some(builder) = self.builder() {
+                    builder.heading(&text);
+                }
+            }
+            Capture::Word { strong, morph } => {
+                if let Some(builder) = self.builder() {
+                    builder.push_word(&text, strong, None, morph);
+                }
+            }
+            Capture::Note { kind, offset } => {
+                if let Some(builder) = self.builder() {
+                    builder.push_note(kind, offset, "", &text);
+                }
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skip_depth.is_some() {
+            return;
+        }
+        if let Some((_, _, captured)) = self.capture.as_mut() {
+            return captured.push_str(text);
+        }
+        if let Some(builder) = self.builder() {
+            builder.push_text(text);
+        }
+    }
+}
+
+impl ZefaniaBible {
+    pub fn parse(source: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut reader = Reader::from_str(source);
+        let mut parser = ZefaniaParser::default();
+        loop {
+            match reader.read_event()? {
+                Event::Start(element) => parser.start(&element, false),
+                Event::Empty(element) => parser.start(&element, true),
+                Event::End(element) => parser.end(element.local_name().as_ref()),
+                Event::Text(text) => parser.text(&text.unescape()?),
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        parser.finish_book();
+
+        let abbreviation = parser
+            .abbreviation
+            .or(parser.bible_name.clone())
+            .unwrap_or_default();
+        let translation = JSONTranslation {
+            name: parser
+                .name
+                .or(parser.bible_name)
+                .unwrap_or(abbreviation.clone()),
+            language: parser.language.unwrap_or_default(),
+            abbreviation,
+            versification: Versification::default(),
+        };
+        Ok(Self {
+            translation,
+            books: parser.books,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bible_data::bible_data::{StyledText, TaggedWord};
+
+    use super::*;
+
+    const ZEFANIA: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<XMLBIBLE xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" biblename="Test Bible">
+  <INFORMATION>
+    <title>Test Bible</title>
+    <identifier>TB</identifier>
+    <language>ENG</language>
+  </INFORMATION>
+  <BIBLEBOOK bnumber="1" bname="Genesis" bsname="Gen">
+    <CHAPTER cnumber="1">
+      <CAPTION>The Creation</CAPTION>
+      <VERS vnumber="1">In the <gr str="7225" rmac="HNcfsa">beginning</gr> God created the heaven and the earth.<NOTE type="x-studynote">Or, heavens</NOTE></VERS>
+      <VERS vnumber="2">And the earth was without form.<BR art="x-p"/>And the Spirit of God moved.</VERS>
+    </CHAPTER>
+  </BIBLEBOOK>
+  <BIBLEBOOK bsname="John">
+    <CHAPTER cnumber="3">
+      <VERS vnumber="16"><STYLE css="color:#FF0000">For God so loved the <gr str="2889">world</gr>.</STYLE><XREF mscope="45;5;8"/></VERS>
+    </CHAPTER>
+  </BIBLEBOOK>
+  <BIBLEBOOK bnumber="67" bname="Tobit"><CHAPTER cnumber="1"><VERS vnumber="1">Tobit</VERS></CHAPTER></BIBLEBOOK>
+</XMLBIBLE>"#;
+
+    #[test]
+    fn parse_zefania() {
+        let zefania = ZefaniaBible::parse(ZEFANIA).unwrap();
+        assert_eq!(zefania.translation.name, "Test Bible");
+        assert_eq!(zefania.translation.abbreviation, "TB");
+        assert_eq!(zefania.translation.language, "ENG");
+        assert_eq!(zefania.books.len(), 2);
+        assert_eq!(zefania.books[1].name, "John");
+
+        let bible = zefania.as_bible_data().unwrap();
+        assert_eq!(bible.get_book_id("gen"), Some(1));
+        let verse = bible.get_verse(1, 1, 1).unwrap();
+        assert_eq!(
+            verse.get_content(),
+            Some("In the beginning God created the heaven and the earth.")
+        );
+        let data = verse.get_data();
+        assert_eq!(data.headings(), ["The Creation"]);
+        assert_eq!(data.footnotes()[0].text, "Or, heavens");
+        assert_eq!(data.footnotes()[0].offset, 54);
+        assert_eq!(
+            data.words(),
+            [TaggedWord {
+                range: 7..16,
+                strong: Some(String::from("H7225")),
+                lemma: None,
+                morph: Some(String::from("HNcfsa")),
+            }]
+        );
+        assert_eq!(
+            bible.get_verse(1, 1, 2).unwrap().get_content(),
+            Some("And the earth was without form. And the Spirit of God moved.")
+        );
+
+        let verse = bible.get_verse(43, 3, 16).unwrap();
+        assert_eq!(verse.get_content(), Some("For God so loved the world."));
+        let data = verse.get_data();
+        assert_eq!(data.words()[0].strong.as_deref(), Some("G2889"));
+        assert_eq!(data.cross_references()[0].text, "45;5;8");
+        assert_eq!(
+            data.styles(),
+            [StyledText {
+                range: 0..27,
+                style: TextStyle::WordsOfJesus
+            }]
+        );
+    }
+}
@@ -2,5 +2,7 @@ pub mod formats;
 
 pub mod bible_data;
 pub mod book;
+pub mod cache;
 pub mod chapter;
 pub mod verse;
+pub mod versification;
@@ -3,12 +3,22 @@ use serde::{Deserialize, Serialize};
 use crate::{
     api::{
         bible_api::{Api, ApiData, BibleAPI},
-        passage::segments::{ChapterVerse, PassageSegment},
+        passage::{
+            passage::Passage,
+            segments::{ChapterVerse, PassageSegment, PassageSegments},
+        },
+    },
+    related_media::{
+        interlinear::{align_words, AlignedWord},
+        related_media::{RelatedMedia, RelatedMediaProximity},
     },
-    related_media::related_media::{RelatedMedia, RelatedMediaProximity},
 };
 
-use super::{bible_data::BibleData, book::BibleBook, chapter::BibleChapter};
+use super::{
+    bible_data::{BibleData, Note, ParagraphBreak, StyledText, VerseMarkup},
+    book::BibleBook,
+    chapter::BibleChapter,
+};
 
 #[derive(Clone, Copy, Serialize)]
 pub struct BibleVerse<'a> {
@@ -63,6 +73,36 @@ impl<'a> BibleVerse<'a> {
         self.content
     }
 
+    /// Everything else about this verse, like headings, paragraphs, and footnotes
+    pub fn get_data(&self) -> &'a VerseMarkup {
+        self.bible.bible_contents[self.book - 1][self.chapter - 1][self.verse - 1].markup()
+    }
+
+    /// The section headings that come right before this verse
+    pub fn get_headings(&self) -> &'a [String] {
+        self.get_data().headings()
+    }
+
+    /// The paragraph breaks and lines of poetry in this verse (by byte offset)
+    pub fn get_paragraphs(&self) -> &'a [ParagraphBreak] {
+        self.get_data().paragraphs()
+    }
+
+    /// The red letters, divine names, and so on in this verse (by byte range)
+    pub fn get_styles(&self) -> &'a [StyledText] {
+        self.get_data().styles()
+    }
+
+    /// The footnotes of this verse, in order (by byte offset)
+    pub fn get_footnotes(&self) -> &'a [Note] {
+        self.get_data().footnotes()
+    }
+
+    /// This verse starts a new paragraph (or line of poetry), or is after a heading
+    pub fn starts_paragraph(&self) -> bool {
+        self.get_data().starts_paragraph()
+    }
+
     // pub fn get_related_media(&self) -> Option<Vec<RelatedMediaProximity>> {
     //     self.api.related_media.get_related_media(
     //         self.book,
@@ -74,14 +114,94 @@ impl<'a> BibleVerse<'a> {
     // }
 }
 
-impl Api<'_, BibleVerse<'_>> {
+impl<'a> Api<'a, BibleVerse<'_>> {
+    /// This verse as a passage
+    fn as_passage(&self) -> Passage<'_> {
+        Passage {
+            book: self.get_book(),
+            segments: PassageSegments(vec![PassageSegment::chapter_verse(
+                self.chapter,
+                self.verse,
+            )]),
+        }
+    }
+
+    /// The passages this verse references, most relevant first (see
+    /// [`ApiData::get_cross_references`])
+    pub fn get_cross_references(&self, min_votes: Option<i64>) -> Vec<Passage<'a>> {
+        self.api.get_cross_references(&self.as_passage(), min_votes)
+    }
+
+    /// The verses that reference this verse, most relevant first
+    pub fn get_incoming_cross_references(&self, min_votes: Option<i64>) -> Vec<Passage<'a>> {
+        self.api
+            .get_incoming_cross_references(&self.as_passage(), min_votes)
+    }
+
+    /**
+    - The original language words of this verse, with the words of the translation they are
+      aligned to and their entries in the lexicon
+    - Interlinear data uses the versification of the default translation, so the verse is mapped to
+      it first
+    */
+    pub fn get_interlinear_words(&self) -> Vec<AlignedWord<'a>> {
+        let api = self.api;
+        api.versifications
+            .map_verse(
+                self.book,
+                ChapterVerse {
+                    chapter: self.chapter,
+                    verse: self.verse,
+                },
+                &self.bible.translation.versification,
+                &api.bible.translation.versification,
+            )
+            .into_iter()
+            .filter_map(|verse| {
+                let interlinear =
+                    api.interlinear
+                        .get_verse(self.book, verse.chapter, verse.verse)?;
+                let content = interlinear
+                    .translation
+                    .as_deref()
+                    .and_then(|translation| api.get_translation(translation))
+                    .and_then(|bible| bible.get_verse(self.book, verse.chapter, verse.verse))
+                    .and_then(|verse| verse.get_content());
+                Some(align_words(interlinear, content, &api.lexicon))
+            })
+            .flatten()
+            .collect()
+    }
+
+    /**
+    - The related media about this verse, including media about a passage around it (see
+      [`RelatedMediaBook::get_all_passage_media`](crate::related_media::related_media::RelatedMediaBook::get_all_passage_media))
+    - Related media references use the versification of the default translation, so a verse
+      from another translation is mapped to it first
+    */
     pub fn get_related_media(&self) -> Option<Vec<RelatedMediaProximity>> {
-        self.api.related_media.get_related_media(
+        let verses = self.api.versifications.map_verse(
             self.book,
-            PassageSegment::ChapterVerse(ChapterVerse {
+            ChapterVerse {
                 chapter: self.chapter,
                 verse: self.verse,
-            }),
-        )
+            },
+            &self.bible.translation.versification,
+            &self.api.bible.translation.versification,
+        );
+        let related_media = verses
+            .into_iter()
+            .filter_map(|verse| {
+                self.api
+                    .related_media
+                    .get_all_related_media(self.book, PassageSegment::ChapterVerse(verse))
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        if related_media.is_empty() {
+            None
+        } else {
+            Some(related_media)
+        }
     }
 }
@@ -0,0 +1,434 @@
+use std::{collections::BTreeMap, fmt::Display, path::Path};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::api::passage::segments::ChapterVerse;
+
+/**
+A verse numbering scheme
+
+- Translations number some verses differently, like Malachi 4:1-6 in the KJV being Malachi
+  3:19-24 in the Hebrew Bible, or Psalm titles being verse 1 in the Hebrew Bible
+- Unknown names are kept as [`Versification::Other`]
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Versification {
+    /// The KJV and most English translations
+    #[default]
+    KJV,
+    /// The Hebrew (Masoretic) Old Testament and the Greek New Testament
+    Original,
+    /// The Latin Vulgate
+    Vulgate,
+    /// The Greek Septuagint
+    LXX,
+    Other(String),
+}
+
+impl From<String> for Versification {
+    fn from(name: String) -> Self {
+        match name.to_uppercase().as_str() {
+            "KJV" | "ENGLISH" => Versification::KJV,
+            "ORIGINAL" | "HEBREW" | "MT" => Versification::Original,
+            "VULGATE" | "VULG" => Versification::Vulgate,
+            "LXX" | "SEPTUAGINT" => Versification::LXX,
+            _ => Versification::Other(name),
+        }
+    }
+}
+
+impl From<Versification> for String {
+    fn from(versification: Versification) -> Self {
+        versification.to_string()
+    }
+}
+
+impl Display for Versification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Versification::KJV => write!(f, "KJV"),
+            Versification::Original => write!(f, "Original"),
+            Versification::Vulgate => write!(f, "Vulgate"),
+            Versification::LXX => write!(f, "LXX"),
+            Versification::Other(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/**
+A mapping table as it is stored in a data file
+
+```text
+{
+    "from": "KJV",
+    "to": "Original",
+    "mappings": [
+        { "book": 39, "from": "4:1-6", "to": "3:19-24" },
+        { "book": 64, "from": "1:14", "to": "1:14-15" },
+        { "book": 19, "from": null, "to": "51:1-2" }
+    ]
+}
+```
+
+- Ranges of the same length are mapped verse by verse
+- A single verse can map to a range (and the other way around)
+- A `null` side means those verses don't exist in that scheme (like a Psalm title)
+- Verses that aren't in any mapping keep their numbers
+- No tables come with this crate, so a complete one (like the CrossWire or Copenhagen Alliance
+  mappings) has to be converted to this format
+*/
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VersificationFile {
+    pub from: Versification,
+    pub to: Versification,
+    pub mappings: Vec<VersificationMapping>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VersificationMapping {
+    /// book id where Genesis = 1
+    pub book: usize,
+    /// - Ex: `4:1-6` or `1:14`
+    pub from: Option<String>,
+    /// - Ex: `3:19-24` or `1:14-15`
+    pub to: Option<String>,
+}
+
+/// `chapter:verse` or `chapter:start_verse-end_verse`
+static VERSE_RANGE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*([0-9]+):([0-9]+)(?:\s*[-–]\s*([0-9]+))?\s*$").unwrap());
+
+fn parse_verse_range(range: &str) -> Result<Vec<ChapterVerse>, String> {
+    let captures = VERSE_RANGE
+        .captures(range)
+        .ok_or_else(|| format!("'{}' is not a verse or range like `3:19-24`.", range))?;
+    let number = |index: usize| captures.get(index).map(|m| m.as_str().parse::<usize>());
+    let chapter = number(1).unwrap().map_err(|e| e.to_string())?;
+    let start = number(2).unwrap().map_err(|e| e.to_string())?;
+    let end = number(3)
+        .transpose()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(start);
+    if start > end {
+        return Err(format!("'{}' is backwards.", range));
+    }
+    Ok((start..=end)
+        .map(|verse| ChapterVerse { chapter, verse })
+        .collect())
+}
+
+type VerseMap = BTreeMap<(usize, ChapterVerse), Vec<ChapterVerse>>;
+
+/// A mapping table between 2 versifications, which works in both directions
+#[derive(Clone, Debug)]
+pub struct VersificationMap {
+    pub from: Versification,
+    pub to: Versification,
+    /// (book, verse in `from`) to verses in `to`
+    forward: VerseMap,
+    /// (book, verse in `to`) to verses in `from`
+    backward: VerseMap,
+}
+
+impl VersificationMap {
+    pub fn parse_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        let file: VersificationFile = serde_json::from_str(&contents)?;
+        Ok(Self::from_file(file)?)
+    }
+
+    pub fn from_file(file: VersificationFile) -> Result<Self, String> {
+        let mut forward = VerseMap::new();
+        let mut backward = VerseMap::new();
+        for mapping in file.mappings.iter() {
+            let parse = |range: &Option<String>| match range {
+                Some(range) => parse_verse_range(range),
+                None => Ok(vec![]),
+            };
+            let (from, to) = (parse(&mapping.from)?, parse(&mapping.to)?);
+            let book = mapping.book;
+            let add = |map: &mut VerseMap, key: ChapterVerse, value: &[ChapterVerse]| {
+                map.entry((book, key)).or_default().extend_from_slice(value);
+            };
+            match (from.len(), to.len()) {
+                (from_len, to_len) if from_len == to_len => {
+                    for (from, to) in from.iter().zip(to.iter()) {
+                        add(&mut forward, *from, &[*to]);
+                        add(&mut backward, *to, &[*from]);
+                    }
+                }
+                (0, _) | (_, 0) | (1, _) | (_, 1) => {
+                    for verse in from.iter() {
+                        add(&mut forward, *verse, &to);
+                    }
+                    for verse in to.iter() {
+                        add(&mut backward, *verse, &from);
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "{:?} and {:?} (book {}) must be the same length, or 1 verse.",
+                        mapping.from, mapping.to, book
+                    ))
+                }
+            }
+        }
+        Ok(Self {
+            from: file.from,
+            to: file.to,
+            forward,
+            backward,
+        })
+    }
+
+    /// - Maps a verse from `self.from` to `self.to`
+    /// - Returns [`None`] when the verse isn't in this table (so it keeps its number)
+    pub fn map_forward(&self, book: usize, verse: ChapterVerse) -> Option<&[ChapterVerse]> {
+        self.forward.get(&(book, verse)).map(Vec::as_slice)
+    }
+
+    /// - Maps a verse from `self.to` to `self.from`
+    /// - Returns [`None`] when the verse isn't in this table (so it keeps its number)
+    pub fn map_backward(&self, book: usize, verse: ChapterVerse) -> Option<&[ChapterVerse]> {
+        self.backward.get(&(book, verse)).map(Vec::as_slice)
+    }
+}
+
+/// Every loaded [`VersificationMap`]
+#[derive(Clone, Debug, Default)]
+pub struct Versifications(Vec<VersificationMap>);
+
+impl Versifications {
+    pub fn add(&mut self, map: VersificationMap) {
+        self.0.push(map);
+    }
+
+    /// Maps a verse in 1 step (with a table in either direction)
+    fn map_directly(
+        &self,
+        book: usize,
+        verse: ChapterVerse,
+        from: &Versification,
+        to: &Versification,
+    ) -> Option<Vec<ChapterVerse>> {
+        self.0.iter().find_map(|map| {
+            if &map.from == from && &map.to == to {
+                Some(
+                    map.map_forward(book, verse)
+                        .map_or(vec![verse], <[_]>::to_vec),
+                )
+            } else if &map.from == to && &map.to == from {
+                Some(
+                    map.map_backward(book, verse)
+                        .map_or(vec![verse], <[_]>::to_vec),
+                )
+            } else {
+                None
+            }
+        })
+    }
+
+    /**
+    Maps a verse from one versification to another
+
+    - Returns every verse it corresponds to, which is empty when it doesn't exist in `to` (like
+      a Psalm title)
+    - When there is no table between them, this goes through a versification that both have a
+      table with (usually the KJV)
+    - When there is no way between them, the verse keeps its number
+    */
+    pub fn map_verse(
+        &self,
+        book: usize,
+        verse: ChapterVerse,
+        from: &Versification,
+        to: &Versification,
+    ) -> Vec<ChapterVerse> {
+        if from == to {
+            return vec![verse];
+        }
+        if let Some(verses) = self.map_directly(book, verse, from, to) {
+            return verses;
+        }
+        let intermediates = self
+            .0
+            .iter()
+            .flat_map(|map| [&map.from, &map.to])
+            .filter(|scheme| *scheme != from && *scheme != to);
+        for intermediate in intermediates {
+            let Some(first) = self.map_directly(book, verse, from, intermediate) else {
+                continue;
+            };
+            let second = first
+                .iter()
+                .map(|verse| self.map_directly(book, *verse, intermediate, to))
+                .collect::<Option<Vec<_>>>();
+            if let Some(second) = second {
+                let mut verses = second.concat();
+                verses.dedup();
+                return verses;
+            }
+        }
+        vec![verse]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{
+            bible_api::BibleAPI,
+            passage::{formatting_template::PassageFormatterBuilder, parallel::ParallelLayout},
+        },
+        bible_data::formats::{
+            json::{test_bible_data, test_json_bible},
+            parse::ParseBibleData,
+        },
+    };
+
+    use super::*;
+
+    fn verse(chapter: usize, verse: usize) -> ChapterVerse {
+        ChapterVerse { chapter, verse }
+    }
+
+    fn test_versifications() -> Versifications {
+        let mut versifications = Versifications::default();
+        for file in ["kjv-original.json", "kjv-lxx.json"] {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures/versification")
+                .join(file);
+            versifications.add(VersificationMap::parse_file(&path).unwrap());
+        }
+        versifications
+    }
+
+    #[test]
+    fn map_between_versifications() {
+        let versifications = test_versifications();
+        let (kjv, original, lxx) = (
+            Versification::KJV,
+            Versification::Original,
+            Versification::LXX,
+        );
+        // Malachi 4:5 is Malachi 3:23 in the Hebrew Bible
+        assert_eq!(
+            versifications.map_verse(39, verse(4, 5), &kjv, &original),
+            vec![verse(3, 23)]
+        );
+        assert_eq!(
+            versifications.map_verse(39, verse(3, 24), &original, &kjv),
+            vec![verse(4, 6)]
+        );
+        // 3 John 1:14 is split in two
+        assert_eq!(
+            versifications.map_verse(64, verse(1, 14), &kjv, &original),
+            vec![verse(1, 14), verse(1, 15)]
+        );
+        assert_eq!(
+            versifications.map_verse(64, verse(1, 15), &original, &kjv),
+            vec![verse(1, 14)]
+        );
+        // the title of Psalm 51 isn't a verse in the KJV
+        assert_eq!(
+            versifications.map_verse(19, verse(51, 1), &original, &kjv),
+            vec![]
+        );
+        assert_eq!(
+            versifications.map_verse(19, verse(51, 1), &kjv, &original),
+            vec![verse(51, 3)]
+        );
+        // unmapped verses keep their numbers
+        assert_eq!(
+            versifications.map_verse(43, verse(3, 16), &kjv, &original),
+            vec![verse(3, 16)]
+        );
+        // through the KJV
+        assert_eq!(
+            versifications.map_verse(19, verse(51, 3), &original, &lxx),
+            vec![verse(50, 3)]
+        );
+        assert_eq!(
+            versifications.map_verse(19, verse(10, 1), &kjv, &lxx),
+            vec![verse(9, 22)]
+        );
+    }
+
+    /// The KJV fixture, but Malachi 4 is at the end of Malachi 3 (like the Hebrew Bible)
+    fn hebrew_api() -> BibleAPI {
+        let mut api = BibleAPI::load(test_bible_data());
+        let mut hebrew = test_json_bible();
+        hebrew.translation.abbreviation = String::from("HEB");
+        hebrew.translation.versification = Versification::Original;
+        let malachi = hebrew.bible.iter_mut().find(|book| book.id == 39).unwrap();
+        let chapter_4 = malachi.content.pop().unwrap();
+        malachi.content[2].extend(chapter_4);
+        api.add_translation(hebrew.as_bible_data().unwrap());
+        api.add_versification(
+            VersificationMap::parse_file(
+                &Path::new(env!("CARGO_MANIFEST_DIR"))
+                    .join("tests/fixtures/versification/kjv-original.json"),
+            )
+            .unwrap(),
+        );
+        api
+    }
+
+    #[test]
+    fn passages_line_up_across_versifications() {
+        let api = hebrew_api();
+        let formatter = PassageFormatterBuilder::new()
+            .verse("{content}")
+            .text("{segments}")
+            .build();
+        let kjv = api.parse_reference("Malachi 4:5-6").unwrap();
+        let hebrew = api.parse_reference_in("HEB", "Malachi 4:5-6").unwrap();
+        assert_eq!(hebrew.label(), "Malachi 3:23-24");
+        assert_eq!(hebrew.format(&formatter), kjv.format(&formatter));
+        assert!(hebrew
+            .format(&formatter)
+            .starts_with("Behold, I will send you Elijah the prophet"));
+        assert_eq!(
+            api.parse_reference_in("HEB", "Malachi 4").unwrap().label(),
+            "Malachi 3:19-24"
+        );
+
+        let translations = api.get_translations(&["KJV", "HEB"]).unwrap();
+        let table = kjv.format_parallel_with(
+            &translations,
+            api.get_versifications(),
+            &PassageFormatterBuilder::new()
+                .verse("{translation} {content:HEB}")
+                .build(),
+            ParallelLayout::MarkdownTable,
+        );
+        assert!(table.contains("| 4:6 | KJV And he shall turn the heart of the fathers"));
+        assert!(table.contains(" | HEB And he shall turn the heart of the fathers"));
+    }
+
+    #[test]
+    fn parse_versification_names() {
+        let versification: Versification = serde_json::from_str("\"hebrew\"").unwrap();
+        assert_eq!(versification, Versification::Original);
+        let versification: Versification = serde_json::from_str("\"Synodal\"").unwrap();
+        assert_eq!(versification, Versification::Other(String::from("Synodal")));
+        assert_eq!(
+            serde_json::to_string(&Versification::LXX).unwrap(),
+            "\"LXX\""
+        );
+
+        let file = VersificationFile {
+            from: Versification::KJV,
+            to: Versification::Original,
+            mappings: vec![VersificationMapping {
+                book: 39,
+                from: Some(String::from("4:1-6")),
+                to: Some(String::from("3:19-20")),
+            }],
+        };
+        assert!(VersificationMap::from_file(file).is_err());
+    }
+}
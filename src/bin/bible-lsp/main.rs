@@ -0,0 +1,37 @@
+//! `bible-lsp <path/to/bible.json> [path/to/bible.cache]`
+//!
+//! A language server (over stdio) for the Bible references found in any kind of document
+//!
+//! With a cache path, the translation is loaded from (and kept in) a binary cache, which makes
+//! starting up much faster
+
+use std::{error::Error, path::Path};
+
+use bible_api::{
+    api::bible_api::BibleAPI,
+    bible_data::formats::{json::JSONBible, parse::ParseBibleData},
+};
+use lsp_server::Connection;
+use server::Server;
+
+mod position;
+mod server;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        return Err("Usage: bible-lsp <path/to/bible.json> [path/to/bible.cache]".into());
+    };
+    let data = match args.next() {
+        Some(cache) => JSONBible::load_cached(Path::new(&path), Path::new(&cache))?,
+        None => JSONBible::parse_file(Path::new(&path))?.as_bible_data()?,
+    };
+    let api = BibleAPI::load(data);
+
+    let (connection, io_threads) = Connection::stdio();
+    connection.initialize(serde_json::to_value(Server::capabilities())?)?;
+    // the connection must be dropped (when the server is done) before joining the io threads
+    Server::new(&api, connection).run()?;
+    io_threads.join()?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use bible_api::api::bible_api::{LinePosition, LineRange};
+use lsp_types::{Position, Range};
+
+/// [`LinePosition`]s count characters, but LSP positions count UTF-16 code units (by default),
+/// so this converts between them using the lines of a document
+pub struct LineIndex<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            lines: text.split('\n').collect(),
+        }
+    }
+
+    fn line(&self, line: u32) -> &'a str {
+        self.lines.get(line as usize).copied().unwrap_or("")
+    }
+
+    pub fn to_lsp_position(&self, position: LinePosition) -> Position {
+        let character = self
+            .line(position.line)
+            .chars()
+            .take(position.character as usize)
+            .map(char::len_utf16)
+            .sum::<usize>();
+        Position::new(position.line, character as u32)
+    }
+
+    pub fn to_lsp_range(&self, range: LineRange) -> Range {
+        Range::new(
+            self.to_lsp_position(range.start),
+            self.to_lsp_position(range.end),
+        )
+    }
+
+    /// The text of a range that starts and ends on the same line
+    pub fn slice(&self, range: LineRange) -> &'a str {
+        let line = self.line(range.start.line);
+        let byte_index = |character: u32| {
+            line.char_indices()
+                .nth(character as usize)
+                .map_or(line.len(), |(index, _)| index)
+        };
+        &line[byte_index(range.start.character)..byte_index(range.end.character)]
+    }
+
+    pub fn to_line_position(&self, position: Position) -> LinePosition {
+        let mut code_units = 0;
+        let mut character = 0;
+        for ch in self.line(position.line).chars() {
+            if code_units >= position.character as usize {
+                break;
+            }
+            code_units += ch.len_utf16();
+            character += 1;
+        }
+        LinePosition {
+            line: position.line,
+            character,
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::{collections::HashMap, error::Error};
+
+use bible_api::api::{
+    bible_api::{BibleAPI, LinePosition, LineRange, Located},
+    passage::{
+        errors::ReferenceError,
+        formatting_template::{PassageFormatter, PassageFormatterBuilder},
+        passage::Passage,
+    },
+};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{
+        DocumentLinkRequest, DocumentSymbolRequest, GotoDefinition, HoverRequest,
+        Request as LspRequest,
+    },
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DocumentLink, DocumentLinkOptions, DocumentLinkParams,
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, Location,
+    MarkupContent, MarkupKind, OneOf, Position, PublishDiagnosticsParams, Range,
+    ServerCapabilities, SymbolKind, TextDocumentIdentifier, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Uri, WorkDoneProgressOptions,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::position::LineIndex;
+
+/// - Go-to-definition opens chapters as virtual documents, like `bible:///1%20John/4.md`
+/// - Clients get their content with a [`VirtualTextDocument`] request
+const VIRTUAL_DOCUMENT_SCHEME: &str = "bible:///";
+
+/// The `# Book Chapter` heading and the blank line after it
+const VIRTUAL_DOCUMENT_HEADER_LINES: u32 = 2;
+
+pub enum VirtualTextDocument {}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualTextDocumentParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+impl LspRequest for VirtualTextDocument {
+    type Params = VirtualTextDocumentParams;
+    type Result = Option<String>;
+    const METHOD: &'static str = "bible/virtualTextDocument";
+}
+
+pub struct Server<'a> {
+    api: &'a BibleAPI,
+    connection: Connection,
+    /// the latest text of every open document
+    documents: HashMap<Uri, String>,
+    hover_formatter: PassageFormatter,
+}
+
+impl<'a> Server<'a> {
+    pub fn new(api: &'a BibleAPI, connection: Connection) -> Self {
+        Self {
+            api,
+            connection,
+            documents: HashMap::new(),
+            // markdown needs a blank line to separate paragraphs
+            hover_formatter: PassageFormatterBuilder::new().join_verses("\n\n").build(),
+        }
+    }
+
+    pub fn capabilities() -> ServerCapabilities {
+        ServerCapabilities {
+            text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            definition_provider: Some(OneOf::Left(true)),
+            document_link_provider: Some(DocumentLinkOptions {
+                resolve_provider: Some(false),
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            }),
+            document_symbol_provider: Some(OneOf::Left(true)),
+            ..Default::default()
+        }
+    }
+
+    pub fn run(mut self) -> Result<(), Box<dyn Error>> {
+        let receiver = self.connection.receiver.clone();
+        for message in &receiver {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<(), Box<dyn Error>> {
+        match request.method.as_str() {
+            HoverRequest::METHOD => self.respond(request, Self::hover),
+            GotoDefinition::METHOD => self.respond(request, Self::definition),
+            DocumentLinkRequest::METHOD => self.respond(request, Self::document_links),
+            DocumentSymbolRequest::METHOD => self.respond(request, Self::document_symbols),
+            VirtualTextDocument::METHOD => self.respond(request, Self::virtual_text_document),
+            _ => self.send(Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {}", request.method),
+            )),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), Box<dyn Error>> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.documents
+                    .insert(uri.clone(), params.text_document.text);
+                self.publish_diagnostics(uri)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                // the sync kind is full, so the last change is the whole document
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents.insert(uri.clone(), change.text);
+                }
+                self.publish_diagnostics(uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                self.notify::<PublishDiagnostics>(PublishDiagnosticsParams::new(
+                    params.text_document.uri,
+                    vec![],
+                    None,
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn respond<P: DeserializeOwned, R: Serialize>(
+        &self,
+        request: Request,
+        handler: impl FnOnce(&Self, P) -> R,
+    ) -> Result<(), Box<dyn Error>> {
+        let response = match serde_json::from_value(request.params) {
+            Ok(params) => Response::new_ok(request.id, handler(self, params)),
+            Err(error) => Response::new_err(
+                request.id,
+                ErrorCode::InvalidParams as i32,
+                error.to_string(),
+            ),
+        };
+        self.send(response)
+    }
+
+    fn send(&self, message: impl Into<Message>) -> Result<(), Box<dyn Error>> {
+        self.connection.sender.send(message.into())?;
+        Ok(())
+    }
+
+    fn notify<N: LspNotification>(&self, params: N::Params) -> Result<(), Box<dyn Error>> {
+        self.send(Notification::new(N::METHOD.to_string(), params))
+    }
+
+    /// All references in a document, along with the document's line index
+    fn references(&self, uri: &Uri) -> Option<(LineIndex<'_>, Vec<Located<Passage<'_>>>)> {
+        let text = self.documents.get(uri)?;
+        let references = self
+            .api
+            .find_and_parse_all_references(text)
+            .unwrap_or_default();
+        Some((LineIndex::new(text), references))
+    }
+
+    /// The valid reference under the cursor
+    fn reference_at(
+        &self,
+        uri: &Uri,
+        position: Position,
+    ) -> Option<(LineIndex<'_>, Located<Passage<'_>>)> {
+        let (lines, references) = self.references(uri)?;
+        let position = lines.to_line_position(position);
+        let located = references
+            .into_iter()
+            .find(|located| located.lined_range.contains(position))?;
+        if self.validate(&lines, &located).is_some() {
+            return None;
+        }
+        Some((lines, located))
+    }
+
+    /// What is wrong with a reference (if anything), and which part of it is wrong
+    fn validate(
+        &self,
+        lines: &LineIndex,
+        located: &Located<Passage>,
+    ) -> Option<(ReferenceError, LineRange)> {
+        let reference = lines.slice(located.lined_range);
+        let error = self.api.try_parse_reference(reference).err()?;
+        // references never span multiple lines
+        let span = error.span();
+        let position = |byte_index: usize| LinePosition {
+            line: located.lined_range.start.line,
+            character: located.lined_range.start.character
+                + reference[..byte_index].chars().count() as u32,
+        };
+        let range = LineRange {
+            start: position(span.start),
+            end: position(span.end),
+        };
+        Some((error, range))
+    }
+
+    fn publish_diagnostics(&self, uri: Uri) -> Result<(), Box<dyn Error>> {
+        let diagnostics = self
+            .references(&uri)
+            .map(|(lines, references)| {
+                references
+                    .iter()
+                    .filter_map(|located| {
+                        let (error, range) = self.validate(&lines, located)?;
+                        Some(Diagnostic {
+                            range: lines.to_lsp_range(range),
+                            severity: Some(DiagnosticSeverity::WARNING),
+                            source: Some(String::from("bible-lsp")),
+                            message: error.to_string(),
+                            ..Default::default()
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.notify::<PublishDiagnostics>(PublishDiagnosticsParams::new(uri, diagnostics, None))
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let (lines, located) = self.reference_at(&position.text_document.uri, position.position)?;
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: located.content.format(&self.hover_formatter),
+            }),
+            range: Some(lines.to_lsp_range(located.lined_range)),
+        })
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let (_, located) = self.reference_at(&position.text_document.uri, position.position)?;
+        let passage = located.content;
+        let segment = passage.segments.first()?;
+        let line = VIRTUAL_DOCUMENT_HEADER_LINES + segment.get_starting_verse() as u32 - 1;
+        Some(GotoDefinitionResponse::Scalar(Location {
+            uri: chapter_uri(passage.book.get_name(), segment.get_starting_chapter())?,
+            range: Range::new(Position::new(line, 0), Position::new(line, 0)),
+        }))
+    }
+
+    fn document_links(&self, params: DocumentLinkParams) -> Option<Vec<DocumentLink>> {
+        let (lines, references) = self.references(&params.text_document.uri)?;
+        let links = references
+            .iter()
+            .filter(|located| self.validate(&lines, located).is_none())
+            .filter_map(|located| {
+                let passage = &located.content;
+                let chapter = passage.segments.first()?.get_starting_chapter();
+                Some(DocumentLink {
+                    range: lines.to_lsp_range(located.lined_range),
+                    target: chapter_uri(passage.book.get_name(), chapter),
+                    tooltip: Some(passage.label()),
+                    data: None,
+                })
+            })
+            .collect();
+        Some(links)
+    }
+
+    fn document_symbols(&self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let (lines, references) = self.references(&params.text_document.uri)?;
+        let symbols = references
+            .iter()
+            .map(|located| {
+                let range = lines.to_lsp_range(located.lined_range);
+                #[allow(deprecated)]
+                DocumentSymbol {
+                    name: located.content.label(),
+                    detail: None,
+                    kind: SymbolKind::STRING,
+                    tags: None,
+                    deprecated: None,
+                    range,
+                    selection_range: range,
+                    children: None,
+                }
+            })
+            .collect();
+        Some(DocumentSymbolResponse::Nested(symbols))
+    }
+
+    /**
+    Returns text like the following (for `bible:///John/3.md`):
+
+    ```text
+    # John 3
+
+    [3:1] There was a man of the Pharisees, named Nicodemus, a ruler of the Jews:
+    [3:2] The same came to Jesus by night, and said unto him, ...
+    ```
+    */
+    fn virtual_text_document(&self, params: VirtualTextDocumentParams) -> Option<String> {
+        let (book, chapter) = params
+            .text_document
+            .uri
+            .as_str()
+            .strip_prefix(VIRTUAL_DOCUMENT_SCHEME)?
+            .strip_suffix(".md")?
+            .rsplit_once('/')?;
+        let book_id = self
+            .api
+            .get_bible()
+            .get_book_id(&book.replace("%20", " "))?;
+        let chapter = self
+            .api
+            .get_bible()
+            .get_chapter(book_id, chapter.parse().ok()?)?;
+        let book = chapter.get_book().get_name();
+
+        let mut text = format!("# {} {}\n", book, chapter.chapter_number());
+        for verse in 1..=chapter.verse_count() {
+            let content = chapter
+                .get_verse(verse)
+                .and_then(|verse| verse.get_content())
+                .unwrap_or("");
+            text.push_str(&format!(
+                "\n[{}:{}] {}",
+                chapter.chapter_number(),
+                verse,
+                content
+            ));
+        }
+        Some(text)
+    }
+}
+
+fn chapter_uri(book: &str, chapter: usize) -> Option<Uri> {
+    format!(
+        "{}{}/{}.md",
+        VIRTUAL_DOCUMENT_SCHEME,
+        book.replace(' ', "%20"),
+        chapter
+    )
+    .parse()
+    .ok()
+}
@@ -0,0 +1,3 @@
+pub mod api;
+pub mod bible_data;
+pub mod related_media;
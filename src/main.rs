@@ -1,15 +1,13 @@
 use std::{path::Path, time::Instant};
 
-use api::{bible_api::BibleAPI, passage::formatting_template::PassageFormatterBuilder};
-use bible_data::{
-    bible_data::BibleData,
-    formats::{json::JSONBible, parse::ParseBibleData},
+use bible_api::{
+    api::{bible_api::BibleAPI, passage::formatting_template::PassageFormatterBuilder},
+    bible_data::{
+        bible_data::BibleData,
+        formats::{json::JSONBible, parse::ParseBibleData},
+    },
+    related_media::related_media::RelatedMedia,
 };
-use related_media::related_media::RelatedMedia;
-
-pub mod api;
-pub mod bible_data;
-pub mod related_media;
 
 fn main() {
     let path = Path::new("/home/dglinuxtemple/esv.json");
@@ -0,0 +1,413 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::passage::segments::{ChapterVerse, PassageSegment},
+    bible_data::formats::osis::{book_id, parse_osis_id},
+};
+
+use super::{
+    overlapping_ranges::{ChapterRangePair, OverlapMap},
+    related_media::MapExtensions,
+};
+
+/// A verse of a book (where Genesis = 1)
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BookVerse {
+    pub book: usize,
+    pub chapter: usize,
+    pub verse: usize,
+}
+
+/// A reference from a verse to a related passage
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CrossReference {
+    pub from: BookVerse,
+    /// the book of `to` (where Genesis = 1)
+    pub to_book: usize,
+    /// - Whole chapters have an `end_verse` of 0, since the length of the chapter isn't known
+    /// - Ex: `Gen.1` is `FullChapterRange(1-1)`
+    pub to: PassageSegment,
+    /// - How many people voted for this reference on OpenBible.info (which can be negative)
+    /// - [`None`] for lists like the TSK, which are already in order of relevance
+    pub votes: Option<i64>,
+}
+
+impl CrossReference {
+    /// The verses this reference is to
+    fn target(&self) -> (usize, ChapterRangePair) {
+        (self.to_book, ChapterRangePair::from_segment(&self.to))
+    }
+
+    /// References without votes always have enough
+    fn has_votes(&self, min_votes: Option<i64>) -> bool {
+        match (self.votes, min_votes) {
+            (Some(votes), Some(min_votes)) => votes >= min_votes,
+            _ => true,
+        }
+    }
+}
+
+/**
+- An OSIS reference (or range of them) and its book
+- Ex: `Gen.1.1`, `Prov.8.22-Prov.8.30`, `John.1.1-3`, or `Ps.23` (a whole chapter)
+- Ranges that go into another book aren't supported
+*/
+fn parse_osis_range(reference: &str) -> Option<(usize, PassageSegment)> {
+    let (start, end) = match reference.split_once('-') {
+        Some((start, end)) => (start, Some(end)),
+        None => (reference, None),
+    };
+    let (book, start_chapter, start_verse) = parse_osis_id(start)?;
+    let Some(end) = end else {
+        return Some((
+            book_id(book)?,
+            osis_segment((start_chapter, start_verse), (start_chapter, start_verse))?,
+        ));
+    };
+    let (end_chapter, end_verse) = match end.split('.').count() {
+        // just the verse (Ex: the `3` of `John.1.1-3`)
+        1 => (start_chapter, end.parse().ok()?),
+        // the chapter and verse (Ex: the `2.3` of `John.1.1-2.3`)
+        2 => {
+            let (chapter, verse) = end.split_once('.')?;
+            (chapter.parse().ok()?, verse.parse().ok()?)
+        }
+        _ => {
+            let (end_book, chapter, verse) = parse_osis_id(end)?;
+            if !end_book.eq_ignore_ascii_case(book) {
+                return None;
+            }
+            (chapter, verse)
+        }
+    };
+    Some((
+        book_id(book)?,
+        osis_segment((start_chapter, start_verse), (end_chapter, end_verse))?,
+    ))
+}
+
+/// - From `start` to `end` (a chapter and verse, where verse 0 is the whole chapter)
+/// - `end` can't be before `start`
+fn osis_segment(start: (usize, usize), end: (usize, usize)) -> Option<PassageSegment> {
+    if end < start {
+        return None;
+    }
+    Some(match (start.1, end.1) {
+        (0, 0) => PassageSegment::full_chapter_range(start.0, end.0, 0),
+        (0, _) | (_, 0) => return None,
+        _ => PassageSegment::between(
+            ChapterVerse {
+                chapter: start.0,
+                verse: start.1,
+            },
+            ChapterVerse {
+                chapter: end.0,
+                verse: end.1,
+            },
+        ),
+    })
+}
+
+/// A single verse in OSIS (Ex: `Gen.1.1`)
+fn parse_osis_verse(reference: &str) -> Option<BookVerse> {
+    let (book, chapter, verse) = parse_osis_id(reference)?;
+    if verse == 0 {
+        return None;
+    }
+    Some(BookVerse {
+        book: book_id(book)?,
+        chapter,
+        verse,
+    })
+}
+
+/**
+Cross references from the [OpenBible.info](https://www.openbible.info/labs/cross-references/) TSV
+(the columns are separated by tabs)
+
+```text
+From Verse    To Verse    Votes    #www.openbible.info CC-BY 2024-11-23
+Gen.1.1    Heb.11.3    1155
+Gen.1.1    Prov.8.22-Prov.8.30    327
+```
+*/
+pub fn parse_open_bible(contents: &str) -> Result<Vec<CrossReference>, Box<dyn std::error::Error>> {
+    let mut references = vec![];
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("From Verse") || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || {
+            format!(
+                "line {} is not a valid cross reference: `{}`",
+                index + 1,
+                line
+            )
+        };
+        let mut columns = line.split('\t');
+        let (Some(from), Some(to), Some(votes)) = (columns.next(), columns.next(), columns.next())
+        else {
+            Err(invalid())?
+        };
+        let from = parse_osis_verse(from).ok_or_else(invalid)?;
+        let (to_book, to) = parse_osis_range(to).ok_or_else(invalid)?;
+        let votes = votes.trim().parse().map_err(|_| invalid())?;
+        references.push(CrossReference {
+            from,
+            to_book,
+            to,
+            votes: Some(votes),
+        });
+    }
+    Ok(references)
+}
+
+pub fn parse_open_bible_file(
+    path: &Path,
+) -> Result<Vec<CrossReference>, Box<dyn std::error::Error>> {
+    parse_open_bible(&std::fs::read_to_string(path)?)
+}
+
+/**
+- Cross references from a list like the Treasury of Scripture Knowledge, where each line is a verse
+  and the passages it references, most relevant first
+- The verse is separated from its references by a tab (or space)
+- The references are in OSIS, and are separated by spaces, `,` or `;`
+
+```text
+Gen.1.1    Ps.33.6; John.1.1-3; Heb.11.3
+Gen.1.2    Job.26.13; Ps.104.30
+```
+*/
+pub fn parse_tsk(contents: &str) -> Result<Vec<CrossReference>, Box<dyn std::error::Error>> {
+    let mut references = vec![];
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |reference: &str| {
+            format!(
+                "line {} has an invalid reference: `{}`",
+                index + 1,
+                reference
+            )
+        };
+        let (from, list) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let from = parse_osis_verse(from).ok_or_else(|| invalid(from))?;
+        for reference in list
+            .split(|ch: char| ch.is_whitespace() || ch == ',' || ch == ';')
+            .filter(|reference| !reference.is_empty())
+        {
+            let (to_book, to) = parse_osis_range(reference).ok_or_else(|| invalid(reference))?;
+            references.push(CrossReference {
+                from,
+                to_book,
+                to,
+                votes: None,
+            });
+        }
+    }
+    Ok(references)
+}
+
+pub fn parse_tsk_file(path: &Path) -> Result<Vec<CrossReference>, Box<dyn std::error::Error>> {
+    parse_tsk(&std::fs::read_to_string(path)?)
+}
+
+/**
+All of the loaded cross references, indexed by the verse they are from and the verses they are to
+
+- References use the versification of the default translation (like related media)
+- Results are sorted by relevance, so the most votes come first, then references without votes in
+  the order they were added
+*/
+#[derive(Default)]
+pub struct CrossReferences {
+    references: Vec<CrossReference>,
+    // the index of each reference by the verse it is from
+    outgoing: BTreeMap<BookVerse, Vec<usize>>,
+    // the index of each reference by the book, then by the verses it is to
+    incoming: BTreeMap<usize, OverlapMap<ChapterRangePair, Vec<usize>>>,
+}
+
+impl CrossReferences {
+    pub fn add_cross_references(&mut self, list: Vec<CrossReference>) {
+        for reference in list {
+            let index = self.references.len();
+            self.outgoing.get_or_insert_mut(&reference.from).push(index);
+            let (book, verses) = reference.target();
+            self.incoming
+                .get_or_insert_mut(&book)
+                .get_or_insert_mut(&verses)
+                .push(index);
+            self.references.push(reference);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.references.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+
+    /**
+    - The references from any verse of `segments`, most relevant first
+    - `min_votes` leaves out references with fewer votes (references without votes are kept)
+    - A passage that several verses reference is only included once
+    */
+    pub fn outgoing(
+        &self,
+        book: usize,
+        segments: &[PassageSegment],
+        min_votes: Option<i64>,
+    ) -> Vec<&CrossReference> {
+        let indices = segments.iter().flat_map(|segment| {
+            let start = BookVerse {
+                book,
+                chapter: segment.get_starting_chapter(),
+                verse: segment.get_starting_verse(),
+            };
+            let end = BookVerse {
+                verse: ChapterRangePair::from_segment(segment).end_verse,
+                chapter: segment.get_ending_chapter(),
+                book,
+            };
+            self.outgoing
+                .range(start..=end)
+                .flat_map(|(_, indices)| indices.iter().copied())
+        });
+        self.by_relevance(indices, min_votes, CrossReference::target)
+    }
+
+    /**
+    - The references to any verse of `segments`, most relevant first
+    - `min_votes` leaves out references with fewer votes (references without votes are kept)
+    - A verse that references several verses of `segments` is only included once
+    */
+    pub fn incoming(
+        &self,
+        book: usize,
+        segments: &[PassageSegment],
+        min_votes: Option<i64>,
+    ) -> Vec<&CrossReference> {
+        let Some(incoming) = self.incoming.get(&book) else {
+            return vec![];
+        };
+        let indices = segments.iter().flat_map(|segment| {
+            incoming
+                .iter_overlapping(ChapterRangePair::from_segment(segment))
+                .flat_map(|(_, indices)| indices.iter().copied())
+        });
+        self.by_relevance(indices, min_votes, |reference| reference.from)
+    }
+
+    /// The references at `indices` that have enough votes, without duplicates (by `key`)
+    fn by_relevance<K: Ord>(
+        &self,
+        indices: impl Iterator<Item = usize>,
+        min_votes: Option<i64>,
+        key: impl Fn(&CrossReference) -> K,
+    ) -> Vec<&CrossReference> {
+        let mut indices = indices
+            .filter(|index| self.references[*index].has_votes(min_votes))
+            .collect::<Vec<_>>();
+        // `None` is less than any number of votes, so references without votes come last
+        indices.sort_by_key(|index| (Reverse(self.references[*index].votes), *index));
+        let mut added = BTreeSet::new();
+        indices
+            .into_iter()
+            .map(|index| &self.references[index])
+            .filter(|reference| added.insert(key(reference)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPEN_BIBLE: &str = "From Verse\tTo Verse\tVotes\t#www.openbible.info CC-BY 2024-11-23
+Gen.1.1\tProv.8.22-Prov.8.30\t327
+Gen.1.1\tHeb.11.3\t1155
+Gen.1.1\tIsa.45.18\t3
+Gen.1.2\tJer.4.23\t-2
+John.1.3\tHeb.11.3\t412
+";
+
+    fn labels(references: &[&CrossReference], from: bool) -> Vec<String> {
+        references
+            .iter()
+            .map(|reference| {
+                if from {
+                    format!("{}:{}", reference.from.chapter, reference.from.verse)
+                } else {
+                    format!("{} {}", reference.to_book, reference.to.label())
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn open_bible_cross_references() {
+        let mut references = CrossReferences::default();
+        references.add_cross_references(parse_open_bible(OPEN_BIBLE).unwrap());
+        assert_eq!(references.len(), 5);
+
+        let genesis = [PassageSegment::chapter_verse_range(1, 1, 2)];
+        assert_eq!(
+            labels(&references.outgoing(1, &genesis, None), false),
+            ["58 11:3", "20 8:22-30", "23 45:18", "24 4:23"]
+        );
+        assert_eq!(
+            labels(&references.outgoing(1, &genesis, Some(10)), false),
+            ["58 11:3", "20 8:22-30"]
+        );
+
+        // John 1:3 and Genesis 1:1 both reference Hebrews 11:3
+        let hebrews = [PassageSegment::chapter_verse(11, 3)];
+        assert_eq!(
+            labels(&references.incoming(58, &hebrews, None), true),
+            ["1:1", "1:3"]
+        );
+        let proverbs = [PassageSegment::chapter_verse_range(8, 29, 31)];
+        assert_eq!(
+            labels(&references.incoming(20, &proverbs, None), true),
+            ["1:1"]
+        );
+        assert!(references.incoming(20, &proverbs, Some(500)).is_empty());
+
+        assert!(parse_open_bible("Gen.1.1\tHeb.11.3\tmany").is_err());
+    }
+
+    #[test]
+    fn tsk_cross_references() {
+        let list = "Gen.1.1\tPs.33.6; John.1.1-3, Heb.11.3\n\nGen.1.2 Job.26.13 Ps.104";
+        let mut references = CrossReferences::default();
+        references.add_cross_references(parse_tsk(list).unwrap());
+        let genesis = [PassageSegment::full_chapter_range(1, 1, 31)];
+        // without votes, they stay in order
+        assert_eq!(
+            labels(&references.outgoing(1, &genesis, Some(10)), false),
+            ["19 33:6", "43 1:1-3", "58 11:3", "18 26:13", "19 104"]
+        );
+        let psalm = [PassageSegment::chapter_verse(104, 30)];
+        assert_eq!(
+            labels(&references.incoming(19, &psalm, None), true),
+            ["1:2"]
+        );
+
+        assert!(parse_tsk("Gen.1.1 Heb.11.3-Jas.1.1").is_err());
+        assert!(parse_tsk("Gen.1.1 Gen.1.5-1").is_err());
+        assert!(parse_tsk("Gen.1.1 Gen.2.1-1.31").is_err());
+    }
+}
@@ -7,9 +7,9 @@ use crate::api::passage::segments::{PassageSegment, PassageSegments};
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WordIndices {
     #[serde(skip_serializing_if = "Option::is_none")]
-    start: Option<usize>,
+    pub start: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    end: Option<usize>,
+    pub end: Option<usize>,
 }
 
 /// This is wrapped in an arc so that way the size is smaller when unused
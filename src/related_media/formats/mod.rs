@@ -1 +1,3 @@
 pub mod json;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
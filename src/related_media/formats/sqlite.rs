@@ -0,0 +1,137 @@
+use rusqlite::{params, Connection};
+
+use crate::related_media::related_media::RelatedMedia;
+
+/// The schema that [`write_related_media`] creates (if it isn't there already)
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS media (
+    id INTEGER PRIMARY KEY,
+    content TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS media_tags (
+    media_id INTEGER NOT NULL REFERENCES media(id),
+    tag TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS media_references (
+    id INTEGER PRIMARY KEY,
+    media_id INTEGER NOT NULL REFERENCES media(id),
+    book_id INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS media_segments (
+    reference_id INTEGER NOT NULL REFERENCES media_references(id),
+    start_chapter INTEGER NOT NULL,
+    start_verse INTEGER NOT NULL,
+    end_chapter INTEGER NOT NULL,
+    end_verse INTEGER NOT NULL,
+    -- Ex: `1:1-4` or `23` (for a whole chapter)
+    label TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS media_words (
+    reference_id INTEGER NOT NULL REFERENCES media_references(id),
+    translation TEXT NOT NULL,
+    start_word INTEGER,
+    end_word INTEGER
+);
+";
+
+/**
+Writes related media into a normalized schema in `connection`
+
+- This can be the same database as a translation (from
+  [`SQLiteBible::write`](crate::bible_data::formats::sqlite::SQLiteBible::write)), so that the
+  `book_id` of each reference is a book of it
+- Each segment of a reference is a row of `media_segments`, with the verses it starts and ends at
+*/
+pub fn write_related_media(
+    connection: &Connection,
+    media: &[RelatedMedia],
+) -> rusqlite::Result<()> {
+    let transaction = connection.unchecked_transaction()?;
+    transaction.execute_batch(SCHEMA)?;
+    for item in media.iter() {
+        transaction.execute(
+            "INSERT INTO media (content) VALUES (?1)",
+            params![item.content],
+        )?;
+        let media_id = transaction.last_insert_rowid();
+        for tag in item.tags.iter() {
+            transaction.execute(
+                "INSERT INTO media_tags VALUES (?1, ?2)",
+                params![media_id, tag],
+            )?;
+        }
+        for reference in item.references.iter() {
+            transaction.execute(
+                "INSERT INTO media_references (media_id, book_id) VALUES (?1, ?2)",
+                params![media_id, reference.book],
+            )?;
+            let reference_id = transaction.last_insert_rowid();
+            for segment in reference.segments.iter() {
+                transaction.execute(
+                    "INSERT INTO media_segments VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        reference_id,
+                        segment.get_starting_chapter(),
+                        segment.get_starting_verse(),
+                        segment.get_ending_chapter(),
+                        segment.get_ending_verse(),
+                        segment.label()
+                    ],
+                )?;
+            }
+            for (translation, words) in reference.words.iter().flat_map(|words| words.iter()) {
+                transaction.execute(
+                    "INSERT INTO media_words VALUES (?1, ?2, ?3, ?4)",
+                    params![reference_id, translation, words.start, words.end],
+                )?;
+            }
+        }
+    }
+    transaction.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_media() {
+        let media: Vec<RelatedMedia> = serde_json::from_str(
+            r#"[{
+                "tags": ["sermon", "grace"],
+                "references": [{
+                    "book": 49,
+                    "segments": [{ "chapter": 2, "verse": 8 }, { "chapter": 2, "verses": { "start": 9, "end": 10 } }],
+                    "words": { "KJV": { "start": 2 } }
+                }],
+                "content": "Saved by grace"
+            }]"#,
+        )
+        .unwrap();
+        let connection = Connection::open_in_memory().unwrap();
+        write_related_media(&connection, &media).unwrap();
+        // it can be written more than once
+        write_related_media(&connection, &media).unwrap();
+
+        let labels = connection
+            .prepare("SELECT label FROM media_segments WHERE reference_id = 1")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(labels, ["2:8", "2:9-10"]);
+        let tags: usize = connection
+            .query_row("SELECT COUNT(*) FROM media_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tags, 4);
+        let word: (String, Option<usize>, Option<usize>) = connection
+            .query_row(
+                "SELECT translation, start_word, end_word FROM media_words",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(word, (String::from("KJV"), Some(2), None));
+    }
+}
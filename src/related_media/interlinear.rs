@@ -0,0 +1,380 @@
+use std::{collections::BTreeMap, ops::Range, path::Path, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+use super::{cross_references::BookVerse, morphology::Morphology};
+
+/// A word of the original language text of a verse
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterlinearWord {
+    /// - The word as it is written
+    /// - Ex: `Ἐν` or `בְּרֵאשִׁית`
+    pub text: String,
+    /// Ex: `En`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transliteration: Option<String>,
+    /// - The dictionary form
+    /// - Ex: `ἐν`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lemma: Option<String>,
+    /// Ex: `G1722` or `H7225`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strong: Option<String>,
+    /// Ex: `PREP` (Robinson) or `HR/Ncfsa` (OSHB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub morph: Option<String>,
+    /// - A short English translation
+    /// - Ex: `In`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gloss: Option<String>,
+    /// - The byte ranges of the words this is translated as, in the content of the verse in the
+    ///   translation the interlinear is aligned to
+    /// - This can be several ranges (Ex: `was ... made` for one Greek verb), or none (Ex: the
+    ///   Greek article)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alignment: Vec<Range<usize>>,
+}
+
+impl InterlinearWord {
+    /// The parsed `morph` code (when it is a valid Robinson or OSHB code)
+    pub fn morphology(&self) -> Option<Morphology> {
+        Morphology::parse(self.morph.as_deref()?).ok()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JSONInterlinearVerse {
+    /// book id where Genesis = 1
+    pub book: usize,
+    pub chapter: usize,
+    pub verse: usize,
+    pub words: Vec<InterlinearWord>,
+}
+
+/**
+Interlinear data for any number of verses
+
+```json
+{
+  "translation": "KJV",
+  "verses": [
+    {
+      "book": 43,
+      "chapter": 1,
+      "verse": 1,
+      "words": [
+        {
+          "text": "Ἐν",
+          "transliteration": "En",
+          "lemma": "ἐν",
+          "strong": "G1722",
+          "morph": "PREP",
+          "gloss": "In",
+          "alignment": [{ "start": 0, "end": 2 }]
+        }
+      ]
+    }
+  ]
+}
+```
+*/
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JSONInterlinear {
+    /// the abbreviation of the translation that the words are aligned to (if they are)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation: Option<String>,
+    pub verses: Vec<JSONInterlinearVerse>,
+}
+
+impl JSONInterlinear {
+    pub fn parse_file(path: &Path) -> Result<JSONInterlinear, Box<dyn std::error::Error>> {
+        let contents = &std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(contents)?)
+    }
+}
+
+/// The original language words of a verse
+#[derive(Clone, Debug)]
+pub struct InterlinearVerse {
+    /// the abbreviation of the translation that the words are aligned to (if they are)
+    pub translation: Option<Rc<str>>,
+    pub words: Vec<InterlinearWord>,
+}
+
+/**
+All of the loaded interlinear data, by verse
+
+- Verses use the versification of the default translation (like related media)
+*/
+#[derive(Default)]
+pub struct Interlinear(BTreeMap<BookVerse, InterlinearVerse>);
+
+impl Interlinear {
+    /// Verses that are already loaded are replaced
+    pub fn add_interlinear(&mut self, interlinear: JSONInterlinear) {
+        let translation = interlinear.translation.map(Rc::from);
+        for verse in interlinear.verses {
+            let key = BookVerse {
+                book: verse.book,
+                chapter: verse.chapter,
+                verse: verse.verse,
+            };
+            self.0.insert(
+                key,
+                InterlinearVerse {
+                    translation: translation.clone(),
+                    words: verse.words,
+                },
+            );
+        }
+    }
+
+    pub fn get_verse(
+        &self,
+        book: usize,
+        chapter: usize,
+        verse: usize,
+    ) -> Option<&InterlinearVerse> {
+        self.0.get(&BookVerse {
+            book,
+            chapter,
+            verse,
+        })
+    }
+
+    /// Every verse with interlinear data, in canonical order
+    pub fn iter(&self) -> impl Iterator<Item = (&BookVerse, &InterlinearVerse)> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/**
+- A Strong's number without its leading zeros, in uppercase
+- Ex: `g0025` is `G25`, and `H7225a` stays the same
+- Returns [`None`] when it isn't a Strong's number
+*/
+pub fn normalize_strong(strong: &str) -> Option<String> {
+    let strong = strong.trim();
+    let mut chars = strong.chars();
+    let language = chars.next()?.to_ascii_uppercase();
+    if language != 'G' && language != 'H' {
+        return None;
+    }
+    let rest = chars.as_str();
+    let digits = rest.len()
+        - rest
+            .trim_start_matches(|ch: char| ch.is_ascii_digit())
+            .len();
+    if digits == 0 {
+        return None;
+    }
+    let number = rest[..digits].parse::<usize>().ok()?;
+    Some(format!("{}{}{}", language, number, &rest[digits..]))
+}
+
+/// An entry of a Strong's dictionary
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LexiconEntry {
+    /// Ex: `ἀγαπάω`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lemma: Option<String>,
+    /// - Ex: `agapáō`
+    /// - This is `translit` in the Greek dictionary and `xlit` in the Hebrew one
+    #[serde(
+        default,
+        alias = "translit",
+        alias = "xlit",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub transliteration: Option<String>,
+    /// Ex: `ag-ap-ah'-o`
+    #[serde(default, alias = "pron", skip_serializing_if = "Option::is_none")]
+    pub pronunciation: Option<String>,
+    /// Ex: `perhaps from ἄγαν (much) (or compare H5689)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation: Option<String>,
+    /// Ex: `to love (in a social or moral sense)`
+    #[serde(
+        default,
+        alias = "strongs_def",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub definition: Option<String>,
+    /// - How the word is translated in the KJV
+    /// - Ex: `(be-)love(-ed)`
+    #[serde(default, alias = "kjv_def", skip_serializing_if = "Option::is_none")]
+    pub kjv_usage: Option<String>,
+}
+
+/**
+Strong's Hebrew and Greek dictionaries, by Strong's number
+
+- They can be loaded from the [Open Scriptures](https://github.com/openscriptures/strongs)
+  dictionaries (`strongs-greek-dictionary.js` and `strongs-hebrew-dictionary.js`), or from JSON
+  with the same shape
+- Ex: `{ "G25": { "lemma": "ἀγαπάω", "translit": "agapáō", "strongs_def": "to love", ... } }`
+*/
+#[derive(Clone, Debug, Default)]
+pub struct Lexicon(BTreeMap<String, LexiconEntry>);
+
+impl Lexicon {
+    /// The JavaScript around the dictionary (like `var strongsGreekDictionary = `) is ignored
+    pub fn parse(contents: &str) -> Result<Lexicon, Box<dyn std::error::Error>> {
+        let start = contents
+            .find('{')
+            .ok_or("the dictionary is missing its `{`")?;
+        let end = contents
+            .rfind('}')
+            .ok_or("the dictionary is missing its `}`")?;
+        let entries: BTreeMap<String, LexiconEntry> = serde_json::from_str(&contents[start..=end])?;
+        let mut lexicon = Lexicon::default();
+        lexicon.add_entries(entries);
+        Ok(lexicon)
+    }
+
+    pub fn parse_file(path: &Path) -> Result<Lexicon, Box<dyn std::error::Error>> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Entries that aren't a Strong's number are skipped
+    pub fn add_entries(&mut self, entries: impl IntoIterator<Item = (String, LexiconEntry)>) {
+        for (strong, entry) in entries {
+            if let Some(strong) = normalize_strong(&strong) {
+                self.0.insert(strong, entry);
+            }
+        }
+    }
+
+    /// Adds all of the entries of another lexicon (like the Greek one to the Hebrew one)
+    pub fn extend(&mut self, lexicon: Lexicon) {
+        self.0.extend(lexicon.0);
+    }
+
+    /**
+    - Ex: `G25`, `g0025`, or `H7225`
+    - A number with a letter after it (like `H1254a`) falls back to the number without it
+    */
+    pub fn get(&self, strong: &str) -> Option<&LexiconEntry> {
+        let strong = normalize_strong(strong)?;
+        self.0.get(&strong).or_else(|| {
+            let number = strong.trim_end_matches(|ch: char| ch.is_ascii_alphabetic());
+            // the language letter is at the start, so an empty number means it was all letters
+            (number.len() < strong.len()).then(|| self.0.get(number))?
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// A word of the original language text, with what it is translated as and its dictionary entry
+#[derive(Clone, Debug)]
+pub struct AlignedWord<'a> {
+    pub word: &'a InterlinearWord,
+    /// - The words of the verse that this is translated as, in order
+    /// - This is empty when the verse isn't in the translation the interlinear is aligned to
+    pub translation: Vec<&'a str>,
+    pub entry: Option<&'a LexiconEntry>,
+}
+
+/**
+- Each word of `verse`, aligned to `content` (the content of the verse in the translation it is
+  aligned to)
+- Alignments outside of `content` are left out
+*/
+pub fn align_words<'a>(
+    verse: &'a InterlinearVerse,
+    content: Option<&'a str>,
+    lexicon: &'a Lexicon,
+) -> Vec<AlignedWord<'a>> {
+    verse
+        .words
+        .iter()
+        .map(|word| AlignedWord {
+            word,
+            translation: content.map_or(vec![], |content| {
+                word.alignment
+                    .iter()
+                    .filter_map(|range| content.get(range.clone()))
+                    .collect()
+            }),
+            entry: word
+                .strong
+                .as_deref()
+                .and_then(|strong| lexicon.get(strong)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GREEK_DICTIONARY: &str = r#"var strongsGreekDictionary = {"G25":{"strongs_def":" to love (in a social or moral sense)","derivation":"perhaps from ἄγαν (much) (or compare H5689);","translit":"agapáō","lemma":"ἀγαπάω","kjv_def":"(be-)love(-ed)"},"G3056":{"lemma":"λόγος","translit":"lógos","strongs_def":"something said","kjv_def":"word"}}; module.exports = strongsGreekDictionary;"#;
+
+    #[test]
+    fn strongs_lexicon() {
+        let lexicon = Lexicon::parse(GREEK_DICTIONARY).unwrap();
+        assert_eq!(lexicon.len(), 2);
+        let entry = lexicon.get("g0025").unwrap();
+        assert_eq!(entry.transliteration.as_deref(), Some("agapáō"));
+        assert_eq!(entry.kjv_usage.as_deref(), Some("(be-)love(-ed)"));
+        assert_eq!(
+            lexicon.get("G3056a").unwrap().lemma.as_deref(),
+            Some("λόγος")
+        );
+        assert!(lexicon.get("G1").is_none());
+        assert!(lexicon.get("love").is_none());
+
+        assert_eq!(normalize_strong("H07225"), Some("H7225".to_string()));
+        assert_eq!(normalize_strong("H1254a"), Some("H1254a".to_string()));
+        assert_eq!(normalize_strong("X12"), None);
+    }
+
+    #[test]
+    fn aligned_words() {
+        let json: JSONInterlinear = serde_json::from_str(
+            r#"{
+  "translation": "KJV",
+  "verses": [
+    {
+      "book": 43,
+      "chapter": 1,
+      "verse": 1,
+      "words": [
+        { "text": "Ἐν", "strong": "G1722", "gloss": "In", "alignment": [{ "start": 0, "end": 2 }] },
+        { "text": "ἀρχῇ", "strong": "G746", "alignment": [{ "start": 7, "end": 16 }] },
+        { "text": "ὁ" },
+        { "text": "λόγος", "strong": "G3056", "alignment": [{ "start": 25, "end": 29 }] }
+      ]
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+        let mut interlinear = Interlinear::default();
+        interlinear.add_interlinear(json);
+        let verse = interlinear.get_verse(43, 1, 1).unwrap();
+        assert_eq!(verse.translation.as_deref(), Some("KJV"));
+
+        let lexicon = Lexicon::parse(GREEK_DICTIONARY).unwrap();
+        let content = "In the beginning was the Word";
+        let words = align_words(verse, Some(content), &lexicon);
+        let translations = words
+            .iter()
+            .map(|word| word.translation.join(" "))
+            .collect::<Vec<_>>();
+        assert_eq!(translations, ["In", "beginning", "", "Word"]);
+        assert_eq!(words[3].entry.unwrap().kjv_usage.as_deref(), Some("word"));
+        assert!(align_words(verse, None, &lexicon)[0].translation.is_empty());
+    }
+}
@@ -6,3 +6,4 @@ pub mod related_media;
 // implementations
 pub mod cross_references;
 pub mod interlinear;
+pub mod morphology;
@@ -0,0 +1,1337 @@
+use std::fmt::{self, Display};
+
+use crate::api::passage::formatting_template::TemplateFormatting;
+
+/**
+Defines an enum of morphology values, along with how each is displayed
+
+- Ex: `Tense::Aorist` is displayed as `aorist`
+*/
+macro_rules! morphology_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[$variant_meta:meta])* $variant:ident => $text:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+        }
+
+        impl $name {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $text,)*
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+    };
+}
+
+morphology_enum!(Language {
+    Greek => "Greek",
+    Hebrew => "Hebrew",
+    Aramaic => "Aramaic",
+});
+
+morphology_enum!(PartOfSpeech {
+    Noun => "noun",
+    Adjective => "adjective",
+    Article => "article",
+    Verb => "verb",
+    Pronoun => "pronoun",
+    Adverb => "adverb",
+    Conjunction => "conjunction",
+    Preposition => "preposition",
+    Particle => "particle",
+    Interjection => "interjection",
+    /// OSHB suffixes (like a pronominal suffix)
+    Suffix => "suffix",
+    /// Robinson `HEB`, a transliterated Hebrew word
+    HebrewWord => "Hebrew word",
+    /// Robinson `ARAM`, a transliterated Aramaic word
+    AramaicWord => "Aramaic word",
+});
+
+morphology_enum!(
+    /// A more specific part of speech (Ex: a `personal` pronoun, or a `proper` noun)
+    Kind {
+        // nouns and adjectives
+        Common => "common",
+        Proper => "proper",
+        Gentilic => "gentilic",
+        Cardinal => "cardinal",
+        Ordinal => "ordinal",
+        Letter => "letter",
+        Indeclinable => "indeclinable",
+        Comparative => "comparative",
+        Superlative => "superlative",
+        // pronouns
+        Personal => "personal",
+        Relative => "relative",
+        Reciprocal => "reciprocal",
+        Demonstrative => "demonstrative",
+        Correlative => "correlative",
+        Interrogative => "interrogative",
+        Indefinite => "indefinite",
+        CorrelativeOrInterrogative => "correlative or interrogative",
+        Reflexive => "reflexive",
+        Possessive => "possessive",
+        // particles, adverbs, and conjunctions
+        Conditional => "conditional",
+        Negative => "negative",
+        Affirmation => "affirmation",
+        DefiniteArticle => "definite article",
+        Exhortation => "exhortation",
+        Interjection => "interjection",
+        DirectObjectMarker => "direct object marker",
+        // suffixes
+        Directional => "directional",
+        ParagogicHe => "paragogic he",
+        ParagogicNun => "paragogic nun",
+        Pronominal => "pronominal",
+        // other Robinson suffixes
+        Attic => "Attic",
+        Abbreviated => "abbreviated",
+        Apocopated => "apocopated",
+        Crasis => "crasis",
+    }
+);
+
+morphology_enum!(Tense {
+    Present => "present",
+    Imperfect => "imperfect",
+    Future => "future",
+    Aorist => "aorist",
+    Perfect => "perfect",
+    Pluperfect => "pluperfect",
+    SecondFuture => "second future",
+    SecondAorist => "second aorist",
+    SecondPerfect => "second perfect",
+    SecondPluperfect => "second pluperfect",
+    /// Robinson `X` (like some imperatives)
+    NoTense => "no tense stated",
+    /// Hebrew weqatal
+    SequentialPerfect => "sequential perfect",
+    /// Hebrew wayyiqtol
+    SequentialImperfect => "sequential imperfect",
+});
+
+morphology_enum!(Voice {
+    Active => "active",
+    Middle => "middle",
+    Passive => "passive",
+    MiddleOrPassive => "middle or passive",
+    MiddleDeponent => "middle deponent",
+    PassiveDeponent => "passive deponent",
+    MiddleOrPassiveDeponent => "middle or passive deponent",
+    ImpersonalActive => "impersonal active",
+    NoVoice => "no voice stated",
+});
+
+morphology_enum!(Mood {
+    Indicative => "indicative",
+    Subjunctive => "subjunctive",
+    Optative => "optative",
+    Imperative => "imperative",
+    Infinitive => "infinitive",
+    Participle => "participle",
+    /// Robinson `R`, a participle with the sense of an imperative
+    ImperativeParticiple => "imperative participle",
+    Cohortative => "cohortative",
+    Jussive => "jussive",
+    InfinitiveAbsolute => "infinitive absolute",
+    InfinitiveConstruct => "infinitive construct",
+});
+
+morphology_enum!(
+    /// The Hebrew and Aramaic verb stems (binyanim)
+    Stem {
+        Qal => "qal",
+        Niphal => "niphal",
+        Piel => "piel",
+        Pual => "pual",
+        Hiphil => "hiphil",
+        Hophal => "hophal",
+        Hithpael => "hithpael",
+        Polel => "polel",
+        Polal => "polal",
+        Hithpolel => "hithpolel",
+        Poel => "poel",
+        Poal => "poal",
+        Palel => "palel",
+        Pulal => "pulal",
+        QalPassive => "qal passive",
+        Pilpel => "pilpel",
+        Polpal => "polpal",
+        Hithpalpel => "hithpalpel",
+        Nithpael => "nithpael",
+        Pealal => "pealal",
+        Pilel => "pilel",
+        Hothpaal => "hothpaal",
+        Tiphil => "tiphil",
+        Hishtaphel => "hishtaphel",
+        Nithpalel => "nithpalel",
+        Nithpoel => "nithpoel",
+        Hithpoel => "hithpoel",
+        // Aramaic
+        Peal => "peal",
+        Peil => "peil",
+        Hithpeel => "hithpeel",
+        Pael => "pael",
+        Ithpaal => "ithpaal",
+        Hithpaal => "hithpaal",
+        Aphel => "aphel",
+        Haphel => "haphel",
+        Saphel => "saphel",
+        Shaphel => "shaphel",
+        Ithpeel => "ithpeel",
+        Ishtaphel => "ishtaphel",
+        Hithaphel => "hithaphel",
+        Ithpoel => "ithpoel",
+        Hephal => "hephal",
+        Tiphel => "tiphel",
+        Palpel => "palpel",
+        Ithpalpel => "ithpalpel",
+        Ithpolel => "ithpolel",
+        Ittaphal => "ittaphal",
+    }
+);
+
+morphology_enum!(Person {
+    First => "first person",
+    Second => "second person",
+    Third => "third person",
+});
+
+morphology_enum!(Case {
+    Nominative => "nominative",
+    Genitive => "genitive",
+    Dative => "dative",
+    Accusative => "accusative",
+    Vocative => "vocative",
+});
+
+morphology_enum!(Number {
+    Singular => "singular",
+    Plural => "plural",
+    Dual => "dual",
+});
+
+morphology_enum!(Gender {
+    Masculine => "masculine",
+    Feminine => "feminine",
+    Neuter => "neuter",
+    Common => "common",
+    /// OSHB `b`, masculine or feminine
+    Both => "masculine or feminine",
+});
+
+morphology_enum!(
+    /// The state of a Hebrew or Aramaic noun
+    State {
+        Absolute => "absolute",
+        Construct => "construct",
+        Determined => "determined",
+    }
+);
+
+/// A word, or part of one (like the preposition of `לְ/דָוִד`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Morpheme {
+    pub part_of_speech: PartOfSpeech,
+    pub kind: Option<Kind>,
+    pub stem: Option<Stem>,
+    pub tense: Option<Tense>,
+    pub voice: Option<Voice>,
+    pub mood: Option<Mood>,
+    /// for possessive pronouns, this is the person of the possessor
+    pub person: Option<Person>,
+    /// the number of the possessor, for possessive pronouns (Ex: `our`)
+    pub possessor_number: Option<Number>,
+    pub case: Option<Case>,
+    pub number: Option<Number>,
+    pub gender: Option<Gender>,
+    pub state: Option<State>,
+}
+
+impl Morpheme {
+    fn new(part_of_speech: PartOfSpeech) -> Self {
+        Self {
+            part_of_speech,
+            kind: None,
+            stem: None,
+            tense: None,
+            voice: None,
+            mood: None,
+            person: None,
+            possessor_number: None,
+            case: None,
+            number: None,
+            gender: None,
+            state: None,
+        }
+    }
+
+    fn with_kind(part_of_speech: PartOfSpeech, kind: Kind) -> Self {
+        Self {
+            kind: Some(kind),
+            ..Self::new(part_of_speech)
+        }
+    }
+
+    /// Ex: `aorist active indicative` or `qal perfect`
+    fn verb_description(&self) -> Vec<&'static str> {
+        [
+            self.stem.map(|stem| stem.name()),
+            self.tense.map(|tense| tense.name()),
+            self.voice.map(|voice| voice.name()),
+            self.mood.map(|mood| mood.name()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Ex: `third person singular` or `nominative singular masculine`
+    fn inflection_description(&self) -> Vec<String> {
+        let possessor = self
+            .possessor_number
+            .map(|number| format!("{} possessor", number.name()));
+        let person = match possessor {
+            Some(possessor) => self
+                .person
+                .map(|person| format!("{} {}", person.name(), possessor)),
+            None => self.person.map(|person| person.name().to_string()),
+        };
+        [
+            person,
+            self.case.map(|case| case.name().to_string()),
+            self.number.map(|number| number.name().to_string()),
+            self.gender.map(|gender| gender.name().to_string()),
+            self.state.map(|state| state.name().to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// Ex: `Verb, aorist active indicative, third person singular`
+impl Display for Morpheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.kind {
+            Some(kind) => format!("{} {}", kind.name(), self.part_of_speech.name()),
+            None => self.part_of_speech.name().to_string(),
+        };
+        let mut chars = name.chars();
+        if let Some(first) = chars.next() {
+            write!(f, "{}{}", first.to_uppercase(), chars.as_str())?;
+        }
+        let verb = self.verb_description();
+        if !verb.is_empty() {
+            write!(f, ", {}", verb.join(" "))?;
+        }
+        let inflection = self.inflection_description();
+        if !inflection.is_empty() {
+            write!(f, ", {}", inflection.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/**
+A parsed morphology code
+
+- Greek uses the [Robinson](https://github.com/byztxt/robinson-documentation) codes, like
+  `V-AAI-3S`
+- Hebrew and Aramaic use the [OSHB](https://hb.openscriptures.org/parsing/HebrewMorphologyCodes.html)
+  codes, like `HVqp3ms`, where a word can have several morphemes (Ex: `HR/Ncfsa` is a preposition
+  and a noun)
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Morphology {
+    pub language: Language,
+    /// the morphemes of the word, in order (Robinson codes only have one)
+    pub morphemes: Vec<Morpheme>,
+}
+
+impl Morphology {
+    /// - Either a Robinson or an OSHB code
+    /// - Ex: `V-2AAI-3S`, `N-NSF`, `HC/Vqw3ms`, or `ANcmsd`
+    pub fn parse(code: &str) -> Result<Morphology, String> {
+        let code = code.trim();
+        // OSHB codes start with the language, and Robinson codes that look like that (like `ADV`)
+        // have extra letters, so they aren't valid OSHB codes
+        parse_oshb(code).or_else(|_| parse_robinson(code))
+    }
+
+    /**
+    - The morpheme that isn't a prefix or a suffix, which is what the word is
+    - Ex: the verb of `HC/Vqw3ms` (and with the conjunction)
+    */
+    pub fn main(&self) -> &Morpheme {
+        let is_affix = |morpheme: &&Morpheme| {
+            matches!(
+                (morpheme.part_of_speech, morpheme.kind),
+                (PartOfSpeech::Conjunction, _)
+                    | (PartOfSpeech::Preposition, _)
+                    | (PartOfSpeech::Suffix, _)
+                    | (PartOfSpeech::Particle, Some(Kind::DefiniteArticle))
+            )
+        };
+        self.morphemes
+            .iter()
+            .find(|morpheme| !is_affix(morpheme))
+            .or(self.morphemes.last())
+            .expect("a morphology has at least one morpheme")
+    }
+}
+
+/// Ex: `Conjunction + Verb, qal sequential imperfect, third person masculine singular`
+impl Display for Morphology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, morpheme) in self.morphemes.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" + ")?;
+            }
+            write!(f, "{}", morpheme)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+- The parts of the main morpheme (see [`Morphology::main`]), or `description` for all of it
+- Parts that the word doesn't have are empty
+- Ex: `{tense} {voice} {mood}` is `aorist active indicative` for `V-AAI-3S`
+*/
+impl TemplateFormatting for Morphology {
+    fn variables(&self, variable: &str) -> Result<String, String> {
+        fn name<T: Display>(value: Option<T>) -> String {
+            value.map_or(String::new(), |value| value.to_string())
+        }
+        let main = self.main();
+        Ok(match variable {
+            "description" => self.to_string(),
+            "language" => self.language.to_string(),
+            "part_of_speech" => main.part_of_speech.to_string(),
+            "kind" => name(main.kind),
+            "stem" => name(main.stem),
+            "tense" => name(main.tense),
+            "voice" => name(main.voice),
+            "mood" => name(main.mood),
+            "person" => name(main.person),
+            "case" => name(main.case),
+            "number" => name(main.number),
+            "gender" => name(main.gender),
+            "state" => name(main.state),
+            _ => Err(format!(
+                "'{}' is not a valid template identifier.",
+                variable
+            ))?,
+        })
+    }
+}
+
+fn robinson_case(ch: char) -> Option<Case> {
+    Some(match ch {
+        'N' => Case::Nominative,
+        'G' => Case::Genitive,
+        'D' => Case::Dative,
+        'A' => Case::Accusative,
+        'V' => Case::Vocative,
+        _ => None?,
+    })
+}
+
+fn robinson_number(ch: char) -> Option<Number> {
+    Some(match ch {
+        'S' => Number::Singular,
+        'P' => Number::Plural,
+        _ => None?,
+    })
+}
+
+fn robinson_gender(ch: char) -> Option<Gender> {
+    Some(match ch {
+        'M' => Gender::Masculine,
+        'F' => Gender::Feminine,
+        'N' => Gender::Neuter,
+        _ => None?,
+    })
+}
+
+fn robinson_person(ch: char) -> Option<Person> {
+    Some(match ch {
+        '1' => Person::First,
+        '2' => Person::Second,
+        '3' => Person::Third,
+        _ => None?,
+    })
+}
+
+fn robinson_tense(tense: &str) -> Option<Tense> {
+    Some(match tense {
+        "P" => Tense::Present,
+        "I" => Tense::Imperfect,
+        "F" => Tense::Future,
+        "A" => Tense::Aorist,
+        "R" => Tense::Perfect,
+        "L" => Tense::Pluperfect,
+        "X" => Tense::NoTense,
+        "2F" => Tense::SecondFuture,
+        "2A" => Tense::SecondAorist,
+        "2R" => Tense::SecondPerfect,
+        "2L" => Tense::SecondPluperfect,
+        _ => None?,
+    })
+}
+
+fn robinson_voice(ch: char) -> Option<Voice> {
+    Some(match ch {
+        'A' => Voice::Active,
+        'M' => Voice::Middle,
+        'P' => Voice::Passive,
+        'E' => Voice::MiddleOrPassive,
+        'D' => Voice::MiddleDeponent,
+        'O' => Voice::PassiveDeponent,
+        'N' => Voice::MiddleOrPassiveDeponent,
+        'Q' => Voice::ImpersonalActive,
+        'X' => Voice::NoVoice,
+        _ => None?,
+    })
+}
+
+fn robinson_mood(ch: char) -> Option<Mood> {
+    Some(match ch {
+        'I' => Mood::Indicative,
+        'S' => Mood::Subjunctive,
+        'O' => Mood::Optative,
+        'M' => Mood::Imperative,
+        'N' => Mood::Infinitive,
+        'P' => Mood::Participle,
+        'R' => Mood::ImperativeParticiple,
+        _ => None?,
+    })
+}
+
+/// The suffixes that can be at the end of a Robinson code (Ex: the `-C` of `A-NSM-C`)
+fn robinson_suffix(suffix: &str) -> Option<Kind> {
+    Some(match suffix {
+        "C" => Kind::Comparative,
+        "S" => Kind::Superlative,
+        "N" => Kind::Negative,
+        "I" => Kind::Interrogative,
+        "K" => Kind::Crasis,
+        "ATT" => Kind::Attic,
+        "ABB" => Kind::Abbreviated,
+        "AP" => Kind::Apocopated,
+        _ => None?,
+    })
+}
+
+/// - Fills in the case, number, and gender from `chars` (Ex: `NSM`)
+/// - The gender is optional (Ex: `P-1GS` doesn't have one)
+fn robinson_declension(morpheme: &mut Morpheme, chars: &[char]) -> Option<()> {
+    let [case, number, rest @ ..] = chars else {
+        return None;
+    };
+    morpheme.case = Some(robinson_case(*case)?);
+    morpheme.number = Some(robinson_number(*number)?);
+    match rest {
+        [] => {}
+        [gender] => morpheme.gender = Some(robinson_gender(*gender)?),
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Ex: `V-AAI-3S`, `N-NSF`, `P-1GS`, `CONJ`, or `A-NSM-C`
+pub fn parse_robinson(code: &str) -> Result<Morphology, String> {
+    let morpheme = robinson_morpheme(code)
+        .ok_or_else(|| format!("`{}` is not a valid Robinson morphology code", code))?;
+    Ok(Morphology {
+        language: Language::Greek,
+        morphemes: vec![morpheme],
+    })
+}
+
+fn robinson_morpheme(code: &str) -> Option<Morpheme> {
+    let mut parts = code.split('-');
+    let prefix = parts.next()?;
+    let parts = parts.collect::<Vec<_>>();
+    let chars = |part: &str| part.chars().collect::<Vec<_>>();
+    match (prefix, parts.as_slice()) {
+        // indeclinable words
+        ("ADV", rest) => indeclinable(PartOfSpeech::Adverb, None, rest),
+        ("CONJ", rest) => indeclinable(PartOfSpeech::Conjunction, None, rest),
+        ("COND", rest) => indeclinable(PartOfSpeech::Conjunction, Some(Kind::Conditional), rest),
+        ("PRT", rest) => indeclinable(PartOfSpeech::Particle, None, rest),
+        ("PREP", []) => Some(Morpheme::new(PartOfSpeech::Preposition)),
+        ("INJ", []) => Some(Morpheme::new(PartOfSpeech::Interjection)),
+        ("HEB", []) => Some(Morpheme::new(PartOfSpeech::HebrewWord)),
+        ("ARAM", []) => Some(Morpheme::new(PartOfSpeech::AramaicWord)),
+        ("N", ["PRI"]) => Some(Morpheme::with_kind(PartOfSpeech::Noun, Kind::Proper)),
+        ("N", ["LI"]) => Some(Morpheme::with_kind(PartOfSpeech::Noun, Kind::Letter)),
+        ("N", ["OI"]) => Some(Morpheme::with_kind(PartOfSpeech::Noun, Kind::Indeclinable)),
+        ("A", ["NUI"]) => Some(Morpheme::with_kind(PartOfSpeech::Adjective, Kind::Cardinal)),
+        ("V", [verb, rest @ ..]) => robinson_verb(verb, rest),
+        // pronouns with a person
+        ("P", [declension, rest @ ..]) if declension.starts_with(['1', '2']) => {
+            let declension = chars(declension);
+            let mut morpheme = Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Personal);
+            morpheme.person = robinson_person(declension[0]);
+            robinson_declension(&mut morpheme, &declension[1..])
+                .and_then(|_| with_suffix(morpheme, rest))
+        }
+        ("F", [declension, rest @ ..]) => {
+            let declension = chars(declension);
+            let mut morpheme = Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Reflexive);
+            morpheme.person = robinson_person(*declension.first()?);
+            robinson_declension(&mut morpheme, declension.get(1..)?)
+                .and_then(|_| with_suffix(morpheme, rest))
+        }
+        ("S", [declension, rest @ ..]) => {
+            // Ex: `1SNSM` is `my` (first person singular possessor), nominative singular masculine
+            let declension = chars(declension);
+            let mut morpheme = Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Possessive);
+            morpheme.person = robinson_person(*declension.first()?);
+            morpheme.possessor_number = robinson_number(*declension.get(1)?);
+            robinson_declension(&mut morpheme, declension.get(2..)?)
+                .and_then(|_| with_suffix(morpheme, rest))
+        }
+        (prefix, [declension, rest @ ..]) => {
+            let mut morpheme = match prefix {
+                "N" => Morpheme::new(PartOfSpeech::Noun),
+                "A" => Morpheme::new(PartOfSpeech::Adjective),
+                "T" => Morpheme::new(PartOfSpeech::Article),
+                "P" => Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Personal),
+                "R" => Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Relative),
+                "C" => Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Reciprocal),
+                "D" => Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Demonstrative),
+                "K" => Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Correlative),
+                "I" => Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Interrogative),
+                "X" => Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::Indefinite),
+                "Q" => Morpheme::with_kind(PartOfSpeech::Pronoun, Kind::CorrelativeOrInterrogative),
+                _ => return None,
+            };
+            robinson_declension(&mut morpheme, &chars(declension))
+                .and_then(|_| with_suffix(morpheme, rest))
+        }
+        _ => None,
+    }
+}
+
+/// Ex: `ADV` or `ADV-I` (interrogative)
+fn indeclinable(
+    part_of_speech: PartOfSpeech,
+    kind: Option<Kind>,
+    suffix: &[&str],
+) -> Option<Morpheme> {
+    let morpheme = Morpheme {
+        kind,
+        ..Morpheme::new(part_of_speech)
+    };
+    with_suffix(morpheme, suffix)
+}
+
+/// The kind of `morpheme` is the suffix at the end of the code, if there is one
+fn with_suffix(mut morpheme: Morpheme, suffix: &[&str]) -> Option<Morpheme> {
+    match suffix {
+        [] => {}
+        [suffix] => morpheme.kind = Some(robinson_suffix(suffix)?),
+        _ => return None,
+    }
+    Some(morpheme)
+}
+
+/// Ex: `AAI` and `["3S"]`, `PAP` and `["NSM"]`, or `2AAN`
+fn robinson_verb(verb: &str, rest: &[&str]) -> Option<Morpheme> {
+    let mut morpheme = Morpheme::new(PartOfSpeech::Verb);
+    let tense_length = if verb.starts_with('2') { 2 } else { 1 };
+    morpheme.tense = Some(robinson_tense(verb.get(..tense_length)?)?);
+    let mut chars = verb.get(tense_length..)?.chars();
+    morpheme.voice = Some(robinson_voice(chars.next()?)?);
+    let mood = robinson_mood(chars.next()?)?;
+    morpheme.mood = Some(mood);
+    if chars.next().is_some() {
+        return None;
+    }
+    let rest = match (mood, rest) {
+        (Mood::Infinitive, rest) => rest,
+        (Mood::Participle | Mood::ImperativeParticiple, [declension, rest @ ..]) => {
+            robinson_declension(&mut morpheme, &declension.chars().collect::<Vec<_>>())?;
+            rest
+        }
+        (_, [inflection, rest @ ..]) => {
+            let [person, number] = inflection.chars().collect::<Vec<_>>()[..] else {
+                return None;
+            };
+            morpheme.person = Some(robinson_person(person)?);
+            morpheme.number = Some(robinson_number(number)?);
+            rest
+        }
+        _ => return None,
+    };
+    with_suffix(morpheme, rest)
+}
+
+/// - The value of an OSHB position, where `x` is not applicable
+/// - Returns `Err` when the character isn't one of the values
+fn oshb_value<T>(ch: Option<char>, value: impl Fn(char) -> Option<T>) -> Result<Option<T>, ()> {
+    match ch {
+        None | Some('x') => Ok(None),
+        Some(ch) => value(ch).map(Some).ok_or(()),
+    }
+}
+
+fn oshb_person(ch: char) -> Option<Person> {
+    robinson_person(ch)
+}
+
+fn oshb_gender(ch: char) -> Option<Gender> {
+    Some(match ch {
+        'b' => Gender::Both,
+        'c' => Gender::Common,
+        'f' => Gender::Feminine,
+        'm' => Gender::Masculine,
+        _ => None?,
+    })
+}
+
+fn oshb_number(ch: char) -> Option<Number> {
+    Some(match ch {
+        'd' => Number::Dual,
+        'p' => Number::Plural,
+        's' => Number::Singular,
+        _ => None?,
+    })
+}
+
+fn oshb_state(ch: char) -> Option<State> {
+    Some(match ch {
+        'a' => State::Absolute,
+        'c' => State::Construct,
+        'd' => State::Determined,
+        _ => None?,
+    })
+}
+
+fn hebrew_stem(ch: char) -> Option<Stem> {
+    Some(match ch {
+        'q' => Stem::Qal,
+        'N' => Stem::Niphal,
+        'p' => Stem::Piel,
+        'P' => Stem::Pual,
+        'h' => Stem::Hiphil,
+        'H' => Stem::Hophal,
+        't' => Stem::Hithpael,
+        'o' => Stem::Polel,
+        'O' => Stem::Polal,
+        'r' => Stem::Hithpolel,
+        'm' => Stem::Poel,
+        'M' => Stem::Poal,
+        'k' => Stem::Palel,
+        'K' => Stem::Pulal,
+        'Q' => Stem::QalPassive,
+        'l' => Stem::Pilpel,
+        'L' => Stem::Polpal,
+        'f' => Stem::Hithpalpel,
+        'D' => Stem::Nithpael,
+        'j' => Stem::Pealal,
+        'i' => Stem::Pilel,
+        'u' => Stem::Hothpaal,
+        'c' => Stem::Tiphil,
+        'v' => Stem::Hishtaphel,
+        'w' => Stem::Nithpalel,
+        'y' => Stem::Nithpoel,
+        'z' => Stem::Hithpoel,
+        _ => None?,
+    })
+}
+
+fn aramaic_stem(ch: char) -> Option<Stem> {
+    Some(match ch {
+        'q' => Stem::Peal,
+        'Q' => Stem::Peil,
+        'u' => Stem::Hithpeel,
+        'p' => Stem::Pael,
+        'P' => Stem::Ithpaal,
+        'M' => Stem::Hithpaal,
+        'a' => Stem::Aphel,
+        'h' => Stem::Haphel,
+        's' => Stem::Saphel,
+        'e' => Stem::Shaphel,
+        'H' => Stem::Hophal,
+        'i' => Stem::Ithpeel,
+        't' => Stem::Hishtaphel,
+        'v' => Stem::Ishtaphel,
+        'w' => Stem::Hithaphel,
+        'o' => Stem::Polel,
+        'z' => Stem::Ithpoel,
+        'r' => Stem::Hithpolel,
+        'f' => Stem::Hithpalpel,
+        'b' => Stem::Hephal,
+        'c' => Stem::Tiphel,
+        'm' => Stem::Poel,
+        'l' => Stem::Palpel,
+        'L' => Stem::Ithpalpel,
+        'O' => Stem::Ithpolel,
+        'G' => Stem::Ittaphal,
+        _ => None?,
+    })
+}
+
+/// The tense, mood, and voice of an OSHB verb type (Ex: `w` is a sequential imperfect)
+fn oshb_verb_type(ch: char) -> Option<(Option<Tense>, Option<Mood>, Option<Voice>)> {
+    Some(match ch {
+        'p' => (Some(Tense::Perfect), None, None),
+        'q' => (Some(Tense::SequentialPerfect), None, None),
+        'i' => (Some(Tense::Imperfect), None, None),
+        'w' => (Some(Tense::SequentialImperfect), None, None),
+        'h' => (None, Some(Mood::Cohortative), None),
+        'j' => (None, Some(Mood::Jussive), None),
+        'v' => (None, Some(Mood::Imperative), None),
+        'r' => (None, Some(Mood::Participle), Some(Voice::Active)),
+        's' => (None, Some(Mood::Participle), Some(Voice::Passive)),
+        'a' => (None, Some(Mood::InfinitiveAbsolute), None),
+        'c' => (None, Some(Mood::InfinitiveConstruct), None),
+        _ => None?,
+    })
+}
+
+/// Ex: `Vqp3ms`, `Ncfsa`, or `R`
+fn oshb_morpheme(language: Language, code: &str) -> Result<Morpheme, ()> {
+    let mut chars = code.chars();
+    let part_of_speech = chars.next().ok_or(())?;
+    let mut next = || chars.next();
+    let morpheme = match part_of_speech {
+        'A' => {
+            let kind = oshb_value(next(), |ch| {
+                Some(match ch {
+                    'a' => None,
+                    'c' => Some(Kind::Cardinal),
+                    'g' => Some(Kind::Gentilic),
+                    'o' => Some(Kind::Ordinal),
+                    _ => None?,
+                })
+            })?
+            .flatten();
+            Morpheme {
+                kind,
+                gender: oshb_value(next(), oshb_gender)?,
+                number: oshb_value(next(), oshb_number)?,
+                state: oshb_value(next(), oshb_state)?,
+                ..Morpheme::new(PartOfSpeech::Adjective)
+            }
+        }
+        'C' => Morpheme::new(PartOfSpeech::Conjunction),
+        'D' => Morpheme::new(PartOfSpeech::Adverb),
+        'N' => {
+            let kind = oshb_value(next(), |ch| {
+                Some(match ch {
+                    'c' => Kind::Common,
+                    'g' => Kind::Gentilic,
+                    'p' => Kind::Proper,
+                    _ => None?,
+                })
+            })?;
+            Morpheme {
+                kind,
+                gender: oshb_value(next(), oshb_gender)?,
+                number: oshb_value(next(), oshb_number)?,
+                state: oshb_value(next(), oshb_state)?,
+                ..Morpheme::new(PartOfSpeech::Noun)
+            }
+        }
+        'P' => {
+            let kind = oshb_value(next(), |ch| {
+                Some(match ch {
+                    'd' => Kind::Demonstrative,
+                    'f' => Kind::Indefinite,
+                    'i' => Kind::Interrogative,
+                    'p' => Kind::Personal,
+                    'r' => Kind::Relative,
+                    _ => None?,
+                })
+            })?;
+            Morpheme {
+                kind,
+                person: oshb_value(next(), oshb_person)?,
+                gender: oshb_value(next(), oshb_gender)?,
+                number: oshb_value(next(), oshb_number)?,
+                ..Morpheme::new(PartOfSpeech::Pronoun)
+            }
+        }
+        'R' => Morpheme {
+            kind: oshb_value(next(), |ch| (ch == 'd').then_some(Kind::DefiniteArticle))?,
+            ..Morpheme::new(PartOfSpeech::Preposition)
+        },
+        'S' => {
+            let kind = oshb_value(next(), |ch| {
+                Some(match ch {
+                    'd' => Kind::Directional,
+                    'h' => Kind::ParagogicHe,
+                    'n' => Kind::ParagogicNun,
+                    'p' => Kind::Pronominal,
+                    _ => None?,
+                })
+            })?;
+            Morpheme {
+                kind,
+                person: oshb_value(next(), oshb_person)?,
+                gender: oshb_value(next(), oshb_gender)?,
+                number: oshb_value(next(), oshb_number)?,
+                ..Morpheme::new(PartOfSpeech::Suffix)
+            }
+        }
+        'T' => Morpheme {
+            kind: oshb_value(next(), |ch| {
+                Some(match ch {
+                    'a' => Kind::Affirmation,
+                    'd' => Kind::DefiniteArticle,
+                    'e' => Kind::Exhortation,
+                    'i' => Kind::Interrogative,
+                    'j' => Kind::Interjection,
+                    'm' => Kind::Demonstrative,
+                    'n' => Kind::Negative,
+                    'o' => Kind::DirectObjectMarker,
+                    'r' => Kind::Relative,
+                    _ => None?,
+                })
+            })?,
+            ..Morpheme::new(PartOfSpeech::Particle)
+        },
+        'V' => {
+            let stem = match language {
+                Language::Aramaic => oshb_value(next(), aramaic_stem)?,
+                _ => oshb_value(next(), hebrew_stem)?,
+            };
+            let (tense, mood, voice) = oshb_value(next(), oshb_verb_type)?.unwrap_or_default();
+            let mut morpheme = Morpheme {
+                stem,
+                tense,
+                mood,
+                voice,
+                ..Morpheme::new(PartOfSpeech::Verb)
+            };
+            match mood {
+                Some(Mood::Participle) => {
+                    morpheme.gender = oshb_value(next(), oshb_gender)?;
+                    morpheme.number = oshb_value(next(), oshb_number)?;
+                    morpheme.state = oshb_value(next(), oshb_state)?;
+                }
+                Some(Mood::InfinitiveAbsolute | Mood::InfinitiveConstruct) => {}
+                _ => {
+                    morpheme.person = oshb_value(next(), oshb_person)?;
+                    morpheme.gender = oshb_value(next(), oshb_gender)?;
+                    morpheme.number = oshb_value(next(), oshb_number)?;
+                }
+            }
+            morpheme
+        }
+        _ => return Err(()),
+    };
+    if chars.next().is_some() {
+        return Err(());
+    }
+    Ok(morpheme)
+}
+
+/// Ex: `HVqp3ms`, `HR/Ncfsa`, or `ANcmsd` (Aramaic)
+pub fn parse_oshb(code: &str) -> Result<Morphology, String> {
+    let invalid = || format!("`{}` is not a valid OSHB morphology code", code);
+    let language = match code.chars().next() {
+        Some('H') => Language::Hebrew,
+        Some('A') => Language::Aramaic,
+        _ => return Err(invalid()),
+    };
+    let morphemes = code[1..]
+        .split('/')
+        .map(|morpheme| oshb_morpheme(language, morpheme))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+    Ok(Morphology {
+        language,
+        morphemes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::passage::formatting_template::FormattingTemplate;
+
+    use super::*;
+
+    fn describe(code: &str) -> String {
+        Morphology::parse(code).unwrap().to_string()
+    }
+
+    #[test]
+    fn robinson_codes() {
+        assert_eq!(
+            describe("V-AAI-3S"),
+            "Verb, aorist active indicative, third person singular"
+        );
+        assert_eq!(
+            describe("V-2AAM-2P"),
+            "Verb, second aorist active imperative, second person plural"
+        );
+        assert_eq!(
+            describe("V-PAP-NSM"),
+            "Verb, present active participle, nominative singular masculine"
+        );
+        assert_eq!(describe("V-PAN"), "Verb, present active infinitive");
+        assert_eq!(describe("N-NSF"), "Noun, nominative singular feminine");
+        assert_eq!(describe("N-PRI"), "Proper noun");
+        assert_eq!(
+            describe("A-ASN-C"),
+            "Comparative adjective, accusative singular neuter"
+        );
+        assert_eq!(describe("T-GPM"), "Article, genitive plural masculine");
+        assert_eq!(
+            describe("P-1GS"),
+            "Personal pronoun, first person genitive singular"
+        );
+        assert_eq!(
+            describe("P-GSM"),
+            "Personal pronoun, genitive singular masculine"
+        );
+        assert_eq!(
+            describe("F-3ASM"),
+            "Reflexive pronoun, third person accusative singular masculine"
+        );
+        assert_eq!(
+            describe("S-1PNSM"),
+            "Possessive pronoun, first person plural possessor nominative singular masculine"
+        );
+        assert_eq!(describe("ADV-I"), "Interrogative adverb");
+        assert_eq!(describe("CONJ"), "Conjunction");
+        assert_eq!(describe("COND"), "Conditional conjunction");
+        assert_eq!(describe("PRT-N"), "Negative particle");
+        assert_eq!(describe("HEB"), "Hebrew word");
+        assert_eq!(describe("ARAM"), "Aramaic word");
+        assert_eq!(
+            Morphology::parse("V-AAI-3S").unwrap().language,
+            Language::Greek
+        );
+
+        for invalid in [
+            "V-AAI", "V-AAZ-3S", "N-NS-Q", "N-NSMM", "Z-NSM", "V-AAI-4S", "",
+        ] {
+            assert!(Morphology::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn robinson_tables() {
+        let tenses = [
+            ("P", Tense::Present),
+            ("I", Tense::Imperfect),
+            ("F", Tense::Future),
+            ("A", Tense::Aorist),
+            ("R", Tense::Perfect),
+            ("L", Tense::Pluperfect),
+            ("X", Tense::NoTense),
+            ("2F", Tense::SecondFuture),
+            ("2A", Tense::SecondAorist),
+            ("2R", Tense::SecondPerfect),
+            ("2L", Tense::SecondPluperfect),
+        ];
+        let voices = [
+            ('A', Voice::Active),
+            ('M', Voice::Middle),
+            ('P', Voice::Passive),
+            ('E', Voice::MiddleOrPassive),
+            ('D', Voice::MiddleDeponent),
+            ('O', Voice::PassiveDeponent),
+            ('N', Voice::MiddleOrPassiveDeponent),
+            ('Q', Voice::ImpersonalActive),
+            ('X', Voice::NoVoice),
+        ];
+        let finite_moods = [
+            ('I', Mood::Indicative),
+            ('S', Mood::Subjunctive),
+            ('O', Mood::Optative),
+            ('M', Mood::Imperative),
+        ];
+        for (tense_code, tense) in tenses {
+            for (voice_code, voice) in voices {
+                for (mood_code, mood) in finite_moods {
+                    for (person_code, person) in [
+                        ('1', Person::First),
+                        ('2', Person::Second),
+                        ('3', Person::Third),
+                    ] {
+                        for (number_code, number) in
+                            [('S', Number::Singular), ('P', Number::Plural)]
+                        {
+                            let code = format!(
+                                "V-{tense_code}{voice_code}{mood_code}-{person_code}{number_code}"
+                            );
+                            let morphology = Morphology::parse(&code).unwrap();
+                            let verb = morphology.main();
+                            assert_eq!(verb.tense, Some(tense), "{}", code);
+                            assert_eq!(verb.voice, Some(voice), "{}", code);
+                            assert_eq!(verb.mood, Some(mood), "{}", code);
+                            assert_eq!(verb.person, Some(person), "{}", code);
+                            assert_eq!(verb.number, Some(number), "{}", code);
+                        }
+                    }
+                }
+                let code = format!("V-{tense_code}{voice_code}N");
+                assert_eq!(
+                    Morphology::parse(&code).unwrap().main().mood,
+                    Some(Mood::Infinitive)
+                );
+                for mood_code in ['P', 'R'] {
+                    let code = format!("V-{tense_code}{voice_code}{mood_code}-DPF");
+                    let verb = Morphology::parse(&code).unwrap().main().clone();
+                    assert_eq!(verb.case, Some(Case::Dative), "{}", code);
+                    assert_eq!(verb.gender, Some(Gender::Feminine), "{}", code);
+                }
+            }
+        }
+
+        let prefixes = [
+            ("N", PartOfSpeech::Noun, None),
+            ("A", PartOfSpeech::Adjective, None),
+            ("T", PartOfSpeech::Article, None),
+            ("R", PartOfSpeech::Pronoun, Some(Kind::Relative)),
+            ("C", PartOfSpeech::Pronoun, Some(Kind::Reciprocal)),
+            ("D", PartOfSpeech::Pronoun, Some(Kind::Demonstrative)),
+            ("K", PartOfSpeech::Pronoun, Some(Kind::Correlative)),
+            ("I", PartOfSpeech::Pronoun, Some(Kind::Interrogative)),
+            ("X", PartOfSpeech::Pronoun, Some(Kind::Indefinite)),
+            (
+                "Q",
+                PartOfSpeech::Pronoun,
+                Some(Kind::CorrelativeOrInterrogative),
+            ),
+        ];
+        let cases = [
+            ('N', Case::Nominative),
+            ('G', Case::Genitive),
+            ('D', Case::Dative),
+            ('A', Case::Accusative),
+            ('V', Case::Vocative),
+        ];
+        let genders = [
+            ('M', Gender::Masculine),
+            ('F', Gender::Feminine),
+            ('N', Gender::Neuter),
+        ];
+        for (prefix, part_of_speech, kind) in prefixes {
+            for (case_code, case) in cases {
+                for (gender_code, gender) in genders {
+                    let code = format!("{prefix}-{case_code}P{gender_code}");
+                    let morpheme = Morphology::parse(&code).unwrap().main().clone();
+                    assert_eq!(morpheme.part_of_speech, part_of_speech, "{}", code);
+                    assert_eq!(morpheme.kind, kind, "{}", code);
+                    assert_eq!(morpheme.case, Some(case), "{}", code);
+                    assert_eq!(morpheme.number, Some(Number::Plural), "{}", code);
+                    assert_eq!(morpheme.gender, Some(gender), "{}", code);
+                }
+            }
+        }
+
+        let suffixes = [
+            ("C", Kind::Comparative),
+            ("S", Kind::Superlative),
+            ("N", Kind::Negative),
+            ("I", Kind::Interrogative),
+            ("K", Kind::Crasis),
+            ("ATT", Kind::Attic),
+            ("ABB", Kind::Abbreviated),
+            ("AP", Kind::Apocopated),
+        ];
+        for (suffix, kind) in suffixes {
+            let code = format!("A-NSM-{suffix}");
+            assert_eq!(
+                Morphology::parse(&code).unwrap().main().kind,
+                Some(kind),
+                "{}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn oshb_codes() {
+        assert_eq!(
+            describe("HVqp3ms"),
+            "Verb, qal perfect, third person singular masculine"
+        );
+        assert_eq!(
+            describe("HC/Vqw3ms"),
+            "Conjunction + Verb, qal sequential imperfect, third person singular masculine"
+        );
+        assert_eq!(
+            describe("HR/Ncfsa"),
+            "Preposition + Common noun, singular feminine absolute"
+        );
+        assert_eq!(
+            describe("HTd/Ncmpa"),
+            "Definite article particle + Common noun, plural masculine absolute"
+        );
+        assert_eq!(
+            describe("HVqrmsa"),
+            "Verb, qal active participle, singular masculine absolute"
+        );
+        assert_eq!(describe("HVhc"), "Verb, hiphil infinitive construct");
+        assert_eq!(
+            describe("HNcmsc/Sp3ms"),
+            "Common noun, singular masculine construct + Pronominal suffix, third person singular masculine"
+        );
+        assert_eq!(
+            describe("HPdxms"),
+            "Demonstrative pronoun, singular masculine"
+        );
+        assert_eq!(describe("HNp"), "Proper noun");
+        assert_eq!(
+            describe("AVqp3ms"),
+            "Verb, peal perfect, third person singular masculine"
+        );
+        assert_eq!(
+            describe("ANcmsd"),
+            "Common noun, singular masculine determined"
+        );
+
+        let morphology = Morphology::parse("HC/Vqw3ms").unwrap();
+        assert_eq!(morphology.language, Language::Hebrew);
+        assert_eq!(morphology.main().stem, Some(Stem::Qal));
+        assert_eq!(morphology.main().tense, Some(Tense::SequentialImperfect));
+
+        let template = FormattingTemplate::from_template("{stem} {tense} ({person})").unwrap();
+        assert_eq!(
+            template
+                .fill(&Morphology::parse("HVqp3ms").unwrap())
+                .unwrap(),
+            "qal perfect (third person)"
+        );
+
+        for invalid in ["HVqp3msz", "HVZp3ms", "HZ", "H", "HNcmsa/"] {
+            assert!(Morphology::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn oshb_tables() {
+        let hebrew_stems = "qNpPhHtoOrmMkKQlLfDjiucvwyz";
+        let aramaic_stems = "qQupPMahseHitvwozrfbcmlLOG";
+        for (language, stems) in [("H", hebrew_stems), ("A", aramaic_stems)] {
+            let mut parsed = vec![];
+            for stem in stems.chars() {
+                let code = format!("{language}V{stem}p3ms");
+                let verb = Morphology::parse(&code).unwrap().main().clone();
+                parsed.push(verb.stem.unwrap());
+            }
+            // every code is a different stem
+            let mut unique = parsed.clone();
+            unique.sort_by_key(|stem| stem.name());
+            unique.dedup();
+            assert_eq!(unique.len(), parsed.len(), "{}", language);
+        }
+
+        let verb_types = [
+            ('p', Some(Tense::Perfect), None),
+            ('q', Some(Tense::SequentialPerfect), None),
+            ('i', Some(Tense::Imperfect), None),
+            ('w', Some(Tense::SequentialImperfect), None),
+            ('h', None, Some(Mood::Cohortative)),
+            ('j', None, Some(Mood::Jussive)),
+            ('v', None, Some(Mood::Imperative)),
+        ];
+        for (verb_type, tense, mood) in verb_types {
+            for (person_code, person) in [
+                ('1', Person::First),
+                ('2', Person::Second),
+                ('3', Person::Third),
+            ] {
+                for (gender_code, gender) in [
+                    ('b', Gender::Both),
+                    ('c', Gender::Common),
+                    ('f', Gender::Feminine),
+                    ('m', Gender::Masculine),
+                ] {
+                    for (number_code, number) in [
+                        ('d', Number::Dual),
+                        ('p', Number::Plural),
+                        ('s', Number::Singular),
+                    ] {
+                        let code = format!("HVq{verb_type}{person_code}{gender_code}{number_code}");
+                        let verb = Morphology::parse(&code).unwrap().main().clone();
+                        assert_eq!(verb.tense, tense, "{}", code);
+                        assert_eq!(verb.mood, mood, "{}", code);
+                        assert_eq!(verb.person, Some(person), "{}", code);
+                        assert_eq!(verb.gender, Some(gender), "{}", code);
+                        assert_eq!(verb.number, Some(number), "{}", code);
+                    }
+                }
+            }
+        }
+        for (verb_type, voice) in [('r', Voice::Active), ('s', Voice::Passive)] {
+            for (state_code, state) in [
+                ('a', State::Absolute),
+                ('c', State::Construct),
+                ('d', State::Determined),
+            ] {
+                let code = format!("HVq{verb_type}fp{state_code}");
+                let verb = Morphology::parse(&code).unwrap().main().clone();
+                assert_eq!(verb.mood, Some(Mood::Participle), "{}", code);
+                assert_eq!(verb.voice, Some(voice), "{}", code);
+                assert_eq!(verb.state, Some(state), "{}", code);
+            }
+        }
+
+        let particles = [
+            ('a', Kind::Affirmation),
+            ('d', Kind::DefiniteArticle),
+            ('e', Kind::Exhortation),
+            ('i', Kind::Interrogative),
+            ('j', Kind::Interjection),
+            ('m', Kind::Demonstrative),
+            ('n', Kind::Negative),
+            ('o', Kind::DirectObjectMarker),
+            ('r', Kind::Relative),
+        ];
+        for (code, kind) in particles {
+            let morphology = Morphology::parse(&format!("HT{code}")).unwrap();
+            assert_eq!(morphology.morphemes[0].kind, Some(kind));
+        }
+        let suffixes = [
+            ('d', Kind::Directional),
+            ('h', Kind::ParagogicHe),
+            ('n', Kind::ParagogicNun),
+            ('p', Kind::Pronominal),
+        ];
+        for (code, kind) in suffixes {
+            let morphology = Morphology::parse(&format!("HNcmsa/S{code}")).unwrap();
+            assert_eq!(morphology.morphemes[1].kind, Some(kind));
+        }
+        let pronouns = [
+            ('d', Kind::Demonstrative),
+            ('f', Kind::Indefinite),
+            ('i', Kind::Interrogative),
+            ('p', Kind::Personal),
+            ('r', Kind::Relative),
+        ];
+        for (code, kind) in pronouns {
+            let morphology = Morphology::parse(&format!("HP{code}")).unwrap();
+            assert_eq!(morphology.main().kind, Some(kind));
+        }
+        for (code, kind) in [
+            ('a', None),
+            ('c', Some(Kind::Cardinal)),
+            ('g', Some(Kind::Gentilic)),
+            ('o', Some(Kind::Ordinal)),
+        ] {
+            let morphology = Morphology::parse(&format!("HA{code}msa")).unwrap();
+            assert_eq!(morphology.main().kind, kind);
+        }
+    }
+}
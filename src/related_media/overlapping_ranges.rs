@@ -4,48 +4,144 @@ use std::{
 };
 
 use itertools::Itertools;
+use once_cell::unsync::OnceCell;
 use serde::{Deserialize, Serialize};
 
+use crate::api::passage::segments::PassageSegment;
+
 pub trait OverlapsWith {
-    fn overlaps_with(&self, other: &Self) -> bool;
+    /// - Where a range starts and ends, in order
+    /// - Ex: a verse, or a chapter and a verse
+    type Point: Ord + Copy;
+
+    fn start(&self) -> Self::Point;
+
+    fn end(&self) -> Self::Point;
+
+    /// Both ends are inclusive
+    fn overlaps_with(&self, other: &Self) -> bool {
+        other.start() <= self.end() && self.start() <= other.end()
+    }
+}
+
+/**
+An interval tree of ranges, stored as an array sorted by their starts
+
+- Each range is the root of the ranges before and after it (like a binary search), and
+  `max_ends` has the latest end in each subtree, so whole subtrees that end too early are skipped
+- Finding the `k` ranges that overlap is `O(log n + k)`
+*/
+struct IntervalIndex<K: OverlapsWith> {
+    keys: Vec<K>,
+    max_ends: Vec<K::Point>,
+}
+
+impl<K: OverlapsWith + Clone> IntervalIndex<K> {
+    fn build<'a>(keys: impl Iterator<Item = &'a K>) -> Self
+    where
+        K: 'a,
+    {
+        let mut keys = keys.cloned().collect_vec();
+        keys.sort_by_key(|key| key.start());
+        let mut max_ends = keys.iter().map(|key| key.end()).collect_vec();
+        Self::build_max_ends(&keys, &mut max_ends, 0, keys.len());
+        Self { keys, max_ends }
+    }
+
+    /// The latest end of the subtree of `low..high`
+    fn build_max_ends(
+        keys: &[K],
+        max_ends: &mut [K::Point],
+        low: usize,
+        high: usize,
+    ) -> Option<K::Point> {
+        if low >= high {
+            return None;
+        }
+        let middle = low + (high - low) / 2;
+        let max_end = [
+            Self::build_max_ends(keys, max_ends, low, middle),
+            Some(keys[middle].end()),
+            Self::build_max_ends(keys, max_ends, middle + 1, high),
+        ]
+        .into_iter()
+        .flatten()
+        .max();
+        max_ends[middle] = max_end?;
+        max_end
+    }
+
+    fn overlapping<'a>(&'a self, this: &K, low: usize, high: usize, output: &mut Vec<&'a K>) {
+        if low >= high {
+            return;
+        }
+        let middle = low + (high - low) / 2;
+        // everything in this subtree ends before `this` starts
+        if self.max_ends[middle] < this.start() {
+            return;
+        }
+        self.overlapping(this, low, middle, output);
+        // this, and everything after it, starts after `this` ends
+        if self.keys[middle].start() > this.end() {
+            return;
+        }
+        if this.overlaps_with(&self.keys[middle]) {
+            output.push(&self.keys[middle]);
+        }
+        self.overlapping(this, middle + 1, high, output);
+    }
 }
 
-pub struct OverlapMap<K: Ord + OverlapsWith, V>(BTreeMap<K, V>);
-impl<K: Ord + OverlapsWith, V> Deref for OverlapMap<K, V> {
+/**
+A map of ranges that can find every range that overlaps with another range
+
+- The interval index is built by the first lookup, and it is rebuilt after the map is changed
+*/
+pub struct OverlapMap<K: Ord + OverlapsWith + Clone, V> {
+    map: BTreeMap<K, V>,
+    index: OnceCell<IntervalIndex<K>>,
+}
+
+impl<K: Ord + OverlapsWith + Clone, V> Deref for OverlapMap<K, V> {
     type Target = BTreeMap<K, V>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.map
     }
 }
 
-impl<K: Ord + OverlapsWith, V> DerefMut for OverlapMap<K, V> {
+impl<K: Ord + OverlapsWith + Clone, V> DerefMut for OverlapMap<K, V> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        // the keys might change
+        self.index.take();
+        &mut self.map
     }
 }
 
-impl<K: Ord + OverlapsWith, V> Default for OverlapMap<K, V> {
+impl<K: Ord + OverlapsWith + Clone, V> Default for OverlapMap<K, V> {
     fn default() -> Self {
-        Self(BTreeMap::new())
+        Self {
+            map: BTreeMap::new(),
+            index: OnceCell::new(),
+        }
     }
 }
 
-impl<K: Ord + OverlapsWith, V> OverlapMap<K, V> {
+impl<K: Ord + OverlapsWith + Clone, V> OverlapMap<K, V> {
+    /// Every entry whose range overlaps with `this`, in order
     pub fn iter_overlapping(&self, this: K) -> impl Iterator<Item = (&K, &V)> {
-        // I could maybe do some heuristics to search through less, but this is fine for
-        // now
-        self.0
-            .iter()
-            .filter(move |(key, _)| this.overlaps_with(key))
+        let index = self
+            .index
+            .get_or_init(|| IntervalIndex::build(self.map.keys()));
+        let mut keys = vec![];
+        index.overlapping(&this, 0, index.keys.len(), &mut keys);
+        // the index is sorted by where the ranges start, but the map might not be
+        keys.sort_unstable();
+        keys.into_iter()
+            .filter_map(|key| self.map.get_key_value(key))
     }
 
     pub fn get_overlapping(&self, this: K) -> Vec<(&K, &V)> {
-        // I could maybe do some heuristics to search through less, but this is fine for
-        // now
-        // self.0
-        //     .iter()
-        //     .filter(|(key, _)| this.overlaps_with(key))
         self.iter_overlapping(this).collect_vec()
     }
 
@@ -96,6 +192,16 @@ impl RangePair {
 }
 
 impl OverlapsWith for RangePair {
+    type Point = usize;
+
+    fn start(&self) -> usize {
+        self.start
+    }
+
+    fn end(&self) -> usize {
+        self.end
+    }
+
     fn overlaps_with(&self, other: &Self) -> bool {
         // checking overlap by checking if there is space between their edges
         !(
@@ -138,9 +244,51 @@ impl ChapterRangePair {
             end_verse: verse,
         }
     }
+
+    /// - The verses of a segment, so it can be looked up in an [`OverlapMap`]
+    /// - Whole chapters that haven't been resolved in a book (an ending verse of 0) go to the end
+    ///   of their last chapter
+    pub fn from_segment(segment: &PassageSegment) -> Self {
+        let end_verse = match segment.get_ending_verse() {
+            0 => usize::MAX,
+            verse => verse,
+        };
+        Self::new(
+            segment.get_starting_chapter(),
+            segment.get_starting_verse(),
+            segment.get_ending_chapter(),
+            end_verse,
+        )
+    }
+
+    /// The segment of these verses, where whole chapters are a [`PassageSegment::FullChapterRange`]
+    /// again
+    pub fn to_segment(&self) -> PassageSegment {
+        if self.start_verse == 1 && self.end_verse == usize::MAX {
+            PassageSegment::full_chapter_range(self.start_chapter, self.end_chapter, 0)
+        } else {
+            PassageSegment::chapter_range(
+                self.start_chapter,
+                self.start_verse,
+                self.end_chapter,
+                self.end_verse,
+            )
+        }
+    }
 }
 
 impl OverlapsWith for ChapterRangePair {
+    /// (chapter, verse)
+    type Point = (usize, usize);
+
+    fn start(&self) -> (usize, usize) {
+        (self.start_chapter, self.start_verse)
+    }
+
+    fn end(&self) -> (usize, usize) {
+        (self.end_chapter, self.end_verse)
+    }
+
     fn overlaps_with(&self, other: &Self) -> bool {
         // checking overlap by checking if there is space between their edges
         !(
@@ -257,4 +405,56 @@ mod tests {
             false
         );
     }
+
+    #[test]
+    fn test_overlap_map() {
+        let mut map = OverlapMap::default();
+        for (start, end) in [(1, 3), (2, 2), (4, 9), (5, 6), (10, 10), (1, 20), (7, 8)] {
+            map.insert(RangePair::new(start, end), (start, end));
+        }
+        let overlapping = |map: &OverlapMap<RangePair, (usize, usize)>, start, end| {
+            map.iter_overlapping(RangePair::new(start, end))
+                .map(|(_, value)| *value)
+                .collect_vec()
+        };
+        assert_eq!(overlapping(&map, 5, 5), [(1, 20), (4, 9), (5, 6)]);
+        assert_eq!(overlapping(&map, 3, 4), [(1, 3), (1, 20), (4, 9)]);
+        assert_eq!(overlapping(&map, 21, 30), []);
+
+        // the index is rebuilt after the map changes
+        map.insert(RangePair::new(21, 22), (21, 22));
+        map.remove(&RangePair::new(1, 20));
+        assert_eq!(overlapping(&map, 5, 5), [(4, 9), (5, 6)]);
+        assert_eq!(overlapping(&map, 21, 30), [(21, 22)]);
+
+        // the same as checking every range
+        for start in 0..25 {
+            for end in start..25 {
+                let this = RangePair::new(start, end);
+                let expected = map
+                    .iter()
+                    .filter(|(key, _)| this.overlaps_with(key))
+                    .map(|(_, value)| *value)
+                    .collect_vec();
+                assert_eq!(overlapping(&map, start, end), expected);
+            }
+        }
+
+        let mut map = OverlapMap::default();
+        map.insert(ChapterRangePair::new(1, 5, 3, 2), "1:5-3:2");
+        map.insert(ChapterRangePair::new(2, 1, 2, 9), "2:1-9");
+        map.insert(ChapterRangePair::new(3, 3, 4, 1), "3:3-4:1");
+        assert_eq!(
+            map.iter_overlapping(ChapterRangePair::from_chapter_verse(3, 2))
+                .map(|(_, value)| *value)
+                .collect_vec(),
+            ["1:5-3:2"]
+        );
+        assert_eq!(
+            map.iter_overlapping(ChapterRangePair::new(2, 5, 3, 3))
+                .map(|(_, value)| *value)
+                .collect_vec(),
+            ["1:5-3:2", "2:1-9", "3:3-4:1"]
+        );
+    }
 }
@@ -1,11 +1,12 @@
-use std::{collections::BTreeMap, path::Path, rc::Rc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    rc::Rc,
+};
 
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
-use crate::api::passage::segments::{
-    ChapterRange, ChapterVerse, ChapterVerseRange, PassageSegment,
-};
+use crate::api::passage::segments::{ChapterVerse, ChapterVerseRange, PassageSegment};
 
 use super::{
     formats::json::JSONRelatedMedia,
@@ -53,11 +54,22 @@ impl RelatedMediaBookOrganizer {
         &self,
         book: usize,
         passage_segment: PassageSegment,
-    ) -> Option<Vec<RelatedMediaProximity>> {
+    ) -> Option<Vec<RelatedMediaProximity<'_>>> {
         let media_book = self.0.get(&book)?;
         media_book.get_passage_media(passage_segment)
     }
 
+    /// The related media of every index that overlaps with `passage_segment` (see
+    /// [`RelatedMediaBook::get_all_passage_media`])
+    pub fn get_all_related_media(
+        &self,
+        book: usize,
+        passage_segment: PassageSegment,
+    ) -> Option<Vec<RelatedMediaProximity<'_>>> {
+        let media_book = self.0.get(&book)?;
+        media_book.get_all_passage_media(passage_segment)
+    }
+
     pub fn add_related_media(&mut self, list: Vec<RelatedMedia>) {
         for item in list {
             let rc_item = Rc::new(item);
@@ -80,16 +92,14 @@ impl RelatedMediaBookOrganizer {
                             let list = chapter_map.get_or_insert_mut(&verses);
                             list.push(rc_item.clone());
                         }
-                        PassageSegment::ChapterRange(ChapterRange { start, end }) => {
-                            let chapter_range_pair = ChapterRangePair::new(
-                                start.chapter,
-                                start.verse,
-                                end.chapter,
-                                end.verse,
-                            );
+                        // whole chapters are stored like `1:1-3:24` (or to the end of the last
+                        // chapter when they haven't been resolved in a book)
+                        PassageSegment::ChapterRange(_)
+                        | PassageSegment::FullChapterRange(_)
+                        | PassageSegment::FullBook(_) => {
                             let list = media_book
                                 .chapter_range
-                                .get_or_insert_mut(&chapter_range_pair);
+                                .get_or_insert_mut(&ChapterRangePair::from_segment(seg));
                             list.push(rc_item.clone())
                         }
                     };
@@ -112,24 +122,161 @@ pub struct RelatedMediaBook {
     chapter_range: OverlapMap<ChapterRangePair, Vec<RelatedMediaRef>>,
 }
 
+/// How the passage of related media overlaps with the passage it was found with
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Overlap {
+    /// the same verses
+    Exact,
+    /// the related media is about part of the passage (Ex: `John 3:16` for `John 3`)
+    Within,
+    /// the related media is about all of the passage and more (Ex: `John 3` for `John 3:16`)
+    Surrounding,
+    /// they share some verses (Ex: `John 3:14-18` for `John 3:16-21`)
+    Partial,
+}
+
+impl Overlap {
+    fn between(proximity: &PassageSegment, passage_segment: &PassageSegment) -> Self {
+        let start = |segment: &PassageSegment| {
+            (segment.get_starting_chapter(), segment.get_starting_verse())
+        };
+        // an ending verse of 0 is the end of the chapter
+        let end = |segment: &PassageSegment| match segment.get_ending_verse() {
+            0 => (segment.get_ending_chapter(), usize::MAX),
+            verse => (segment.get_ending_chapter(), verse),
+        };
+        let (media_start, media_end) = (start(proximity), end(proximity));
+        let (passage_start, passage_end) = (start(passage_segment), end(passage_segment));
+        if (media_start, media_end) == (passage_start, passage_end) {
+            Overlap::Exact
+        } else if media_start >= passage_start && media_end <= passage_end {
+            Overlap::Within
+        } else if media_start <= passage_start && media_end >= passage_end {
+            Overlap::Surrounding
+        } else {
+            Overlap::Partial
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct RelatedMediaProximity<'a> {
-    related_media: &'a Vec<RelatedMediaRef>,
-    proximity: PassageSegment,
+    pub related_media: Vec<&'a RelatedMediaRef>,
+    /// the passage that the related media is about
+    pub proximity: PassageSegment,
+    /// how `proximity` overlaps with the passage it was found with
+    pub overlap: Overlap,
+}
+
+impl<'a> RelatedMediaProximity<'a> {
+    fn new(
+        related_media: &'a [RelatedMediaRef],
+        proximity: PassageSegment,
+        passage_segment: &PassageSegment,
+    ) -> Self {
+        Self {
+            related_media: related_media.iter().collect(),
+            overlap: Overlap::between(&proximity, passage_segment),
+            proximity,
+        }
+    }
 }
 
 impl RelatedMediaBook {
+    /**
+    - The related media of every index that overlaps with `passage_segment` (Ex: `John 3:16` also
+      finds media about `John 3:14-21` and `John 2:23-3:21`)
+    - Each related media is only included once, with the most specific passage it is about (single
+      verses, then verse ranges, then chapter ranges)
+    - `None` when `passage_segment` ends before it starts
+    */
     pub fn get_all_passage_media(
         &self,
         passage_segment: PassageSegment,
     ) -> Option<Vec<RelatedMediaProximity<'_>>> {
-        let start_verse = passage_segment.get_starting_verse();
-        let end_verse = passage_segment.get_ending_verse();
-        let start_chapter = passage_segment.get_starting_chapter();
-        let end_chapter = passage_segment.get_ending_chapter();
+        // an ending verse of 0 is the end of the chapter (Ex: `full_chapter_range(1, 1, 0)`)
+        let range_pair = ChapterRangePair::from_segment(&passage_segment);
+        let ChapterRangePair {
+            start_chapter,
+            start_verse,
+            end_chapter,
+            end_verse,
+        } = range_pair;
+        if (end_chapter, end_verse) < (start_chapter, start_verse) {
+            return None;
+        }
+        // the verses of `chapter` that are in the passage
+        let chapter_verses = |chapter: usize| {
+            let start = if chapter == start_chapter {
+                start_verse
+            } else {
+                1
+            };
+            let end = if chapter == end_chapter {
+                end_verse
+            } else {
+                usize::MAX
+            };
+            (start, end)
+        };
+
+        let verses = self
+            .chapter_verse
+            .range(start_chapter..=end_chapter)
+            .flat_map(|(chapter, verses)| {
+                let (start, end) = chapter_verses(*chapter);
+                verses
+                    .range(start..=end)
+                    .map(move |(verse, related_media)| {
+                        (
+                            related_media,
+                            PassageSegment::chapter_verse(*chapter, *verse),
+                        )
+                    })
+            });
+        let verse_ranges = self
+            .chapter_verse_range
+            .range(start_chapter..=end_chapter)
+            .flat_map(|(chapter, ranges)| {
+                let (start, end) = chapter_verses(*chapter);
+                ranges.iter_overlapping(RangePair::new(start, end)).map(
+                    move |(key, related_media)| {
+                        (
+                            related_media,
+                            PassageSegment::chapter_verse_range(*chapter, key.start, key.end),
+                        )
+                    },
+                )
+            });
+        let chapter_ranges = self
+            .chapter_range
+            .iter_overlapping(range_pair)
+            .map(|(key, related_media)| (related_media, key.to_segment()));
 
-        // self.get_chapter_verse_media()
-        todo!()
+        let mut seen: HashSet<*const RelatedMedia> = HashSet::new();
+        let overlapping = verses
+            .chain(verse_ranges)
+            .chain(chapter_ranges)
+            .filter_map(|(related_media, proximity)| {
+                let related_media = related_media
+                    .iter()
+                    .filter(|item| seen.insert(Rc::as_ptr(item)))
+                    .collect_vec();
+                if related_media.is_empty() {
+                    return None;
+                }
+                Some(RelatedMediaProximity {
+                    related_media,
+                    overlap: Overlap::between(&proximity, &passage_segment),
+                    proximity,
+                })
+            })
+            .collect_vec();
+        if overlapping.is_empty() {
+            None
+        } else {
+            Some(overlapping)
+        }
     }
 
     pub fn get_passage_media(
@@ -143,8 +290,10 @@ impl RelatedMediaBook {
             PassageSegment::ChapterVerseRange(ChapterVerseRange { chapter, verses }) => {
                 self.get_chapter_verse_range_media(chapter, verses.start, verses.end)
             }
-            PassageSegment::ChapterRange(ChapterRange { start, end }) => {
-                self.get_chapter_range_media(start.chapter, start.verse, end.chapter, end.verse)
+            PassageSegment::ChapterRange(_)
+            | PassageSegment::FullChapterRange(_)
+            | PassageSegment::FullBook(_) => {
+                self.get_overlapping_chapter_range_media(&passage_segment)
             }
         }
     }
@@ -155,13 +304,11 @@ impl RelatedMediaBook {
         verse: usize,
         // Option<&Vec<RelatedMediaRef>>
     ) -> Option<RelatedMediaProximity<'_>> {
+        let proximity = PassageSegment::chapter_verse(chapter, verse);
         self.chapter_verse
             .get(&chapter)?
             .get(&verse)
-            .map(|related_media| RelatedMediaProximity {
-                related_media,
-                proximity: PassageSegment::chapter_verse(chapter, verse),
-            })
+            .map(|related_media| RelatedMediaProximity::new(related_media, proximity, &proximity))
     }
 
     pub fn get_chapter_verse_range_media(
@@ -171,13 +318,17 @@ impl RelatedMediaBook {
         end_verse: usize,
     ) -> Option<Vec<RelatedMediaProximity<'_>>> {
         let range_pair = RangePair::new(start_verse, end_verse);
+        let passage_segment = PassageSegment::chapter_verse_range(chapter, start_verse, end_verse);
         let overlapping = self
             .chapter_verse_range
             .get(&chapter)?
             .iter_overlapping(range_pair)
-            .map(|(key, related_media)| RelatedMediaProximity {
-                related_media,
-                proximity: PassageSegment::chapter_verse_range(chapter, key.start, key.end),
+            .map(|(key, related_media)| {
+                RelatedMediaProximity::new(
+                    related_media,
+                    PassageSegment::chapter_verse_range(chapter, key.start, key.end),
+                    &passage_segment,
+                )
             })
             .collect_vec();
         Some(overlapping)
@@ -190,18 +341,25 @@ impl RelatedMediaBook {
         end_chapter: usize,
         end_verse: usize,
     ) -> Option<Vec<RelatedMediaProximity<'_>>> {
-        let range_pair = ChapterRangePair::new(start_chapter, start_verse, end_chapter, end_verse);
+        self.get_overlapping_chapter_range_media(&PassageSegment::chapter_range(
+            start_chapter,
+            start_verse,
+            end_chapter,
+            end_verse,
+        ))
+    }
+
+    /// The chapter ranges that overlap with `passage_segment`, where an ending verse of 0 is the
+    /// end of the chapter
+    fn get_overlapping_chapter_range_media(
+        &self,
+        passage_segment: &PassageSegment,
+    ) -> Option<Vec<RelatedMediaProximity<'_>>> {
         let overlapping = self
             .chapter_range
-            .iter_overlapping(range_pair)
-            .map(|(key, related_media)| RelatedMediaProximity {
-                related_media,
-                proximity: PassageSegment::chapter_range(
-                    key.start_chapter,
-                    key.start_verse,
-                    key.end_chapter,
-                    key.end_verse,
-                ),
+            .iter_overlapping(ChapterRangePair::from_segment(passage_segment))
+            .map(|(key, related_media)| {
+                RelatedMediaProximity::new(related_media, key.to_segment(), passage_segment)
             })
             .collect_vec();
         Some(overlapping)
@@ -210,8 +368,146 @@ impl RelatedMediaBook {
 
 #[test]
 fn import_related_media() {
-    let content =
-        std::fs::read_to_string(Path::new("/home/dglinuxtemple/related_media.json")).unwrap();
+    let content = std::fs::read_to_string("/home/dglinuxtemple/related_media.json").unwrap();
     let related_media: Vec<RelatedMedia> = serde_json::from_str(&content).unwrap();
     dbg!(&related_media);
 }
+
+#[test]
+fn all_passage_media() {
+    let related_media: Vec<RelatedMedia> = serde_json::from_str(
+        r#"[
+  { "tags": ["sermon"], "content": "For God so loved", "references": [{ "book": 43, "segments": [{ "chapter": 3, "verse": 16 }] }] },
+  { "tags": ["sermon"], "content": "Born again", "references": [{ "book": 43, "segments": [{ "chapter": 3, "verses": { "start": 1, "end": 21 } }] }] },
+  { "tags": ["notes"], "content": "Nicodemus", "references": [{ "book": 43, "segments": [{ "start": { "chapter": 2, "verse": 23 }, "end": { "chapter": 3, "verse": 21 } }, { "chapter": 3, "verse": 16 }] }] },
+  { "tags": ["sermon"], "content": "The Bread of Life", "references": [{ "book": 43, "segments": [{ "chapter": 6, "verses": { "start": 22, "end": 59 } }] }] }
+]"#,
+    )
+    .unwrap();
+    let mut organizer = RelatedMediaBookOrganizer::default();
+    organizer.add_related_media(related_media);
+
+    let found = organizer
+        .get_all_related_media(43, PassageSegment::chapter_verse(3, 16))
+        .unwrap();
+    let found = found
+        .iter()
+        .map(|proximity| {
+            (
+                proximity
+                    .related_media
+                    .iter()
+                    .map(|media| media.content.as_str())
+                    .collect_vec(),
+                proximity.overlap,
+            )
+        })
+        .collect_vec();
+    // `Nicodemus` is only found once (by its verse)
+    assert_eq!(
+        found,
+        [
+            (vec!["For God so loved", "Nicodemus"], Overlap::Exact),
+            (vec!["Born again"], Overlap::Surrounding),
+        ]
+    );
+
+    let found = organizer
+        .get_all_related_media(43, PassageSegment::chapter_range(3, 20, 6, 30))
+        .unwrap();
+    let overlaps = found
+        .iter()
+        .map(|proximity| (proximity.related_media.len(), proximity.overlap))
+        .collect_vec();
+    assert_eq!(
+        overlaps,
+        [
+            (1, Overlap::Partial),
+            (1, Overlap::Partial),
+            (1, Overlap::Partial)
+        ]
+    );
+    assert!(organizer
+        .get_all_related_media(43, PassageSegment::chapter_verse(10, 1))
+        .is_none());
+    assert!(organizer
+        .get_all_related_media(1, PassageSegment::chapter_verse(1, 1))
+        .is_none());
+    assert!(organizer
+        .get_all_related_media(43, PassageSegment::chapter_range(3, 21, 3, 16))
+        .is_none());
+}
+
+#[test]
+fn whole_chapter_passage_media() {
+    let related_media: Vec<RelatedMedia> = serde_json::from_str(
+        r#"[
+  { "tags": ["sermon"], "content": "Thanksgiving", "references": [{ "book": 50, "segments": [{ "chapter": 1, "verse": 3 }] }] },
+  { "tags": ["sermon"], "content": "To live is Christ", "references": [{ "book": 50, "segments": [{ "chapter": 1, "verses": { "start": 19, "end": 26 } }] }] },
+  { "tags": ["sermon"], "content": "Humility", "references": [{ "book": 50, "segments": [{ "start": { "chapter": 1, "verse": 27 }, "end": { "chapter": 2, "verse": 11 } }] }] }
+]"#,
+    )
+    .unwrap();
+    let mut organizer = RelatedMediaBookOrganizer::default();
+    organizer.add_related_media(related_media);
+
+    // Philippians 1, where the ending verse is 0
+    let found = organizer
+        .get_all_related_media(50, PassageSegment::full_chapter_range(1, 1, 0))
+        .unwrap();
+    let found = found
+        .iter()
+        .map(|proximity| {
+            (
+                proximity.related_media[0].content.as_str(),
+                proximity.overlap,
+            )
+        })
+        .collect_vec();
+    assert_eq!(
+        found,
+        [
+            ("Thanksgiving", Overlap::Within),
+            ("To live is Christ", Overlap::Within),
+            ("Humility", Overlap::Partial),
+        ]
+    );
+
+    let humility = organizer
+        .get_related_media(50, PassageSegment::full_chapter_range(1, 1, 0))
+        .unwrap();
+    assert_eq!(humility.len(), 1);
+    assert_eq!(humility[0].related_media[0].content, "Humility");
+    assert_eq!(humility[0].overlap, Overlap::Partial);
+}
+
+#[test]
+fn store_whole_chapter_media() {
+    let related_media: Vec<RelatedMedia> = serde_json::from_str(
+        r#"[
+  { "tags": ["sermon"], "content": "Philippians 1", "references": [{ "book": 50, "segments": [{ "start_chapter": 1, "end_chapter": 1, "end_verse": 0 }] }] }
+]"#,
+    )
+    .unwrap();
+    let mut organizer = RelatedMediaBookOrganizer::default();
+    organizer.add_related_media(related_media);
+
+    let found = organizer
+        .get_all_related_media(50, PassageSegment::chapter_verse(1, 3))
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].related_media[0].content, "Philippians 1");
+    assert!(matches!(
+        found[0].proximity,
+        PassageSegment::FullChapterRange(_)
+    ));
+    assert_eq!(found[0].overlap, Overlap::Surrounding);
+
+    let found = organizer
+        .get_related_media(50, PassageSegment::chapter_range(1, 30, 2, 1))
+        .unwrap();
+    assert_eq!(found[0].overlap, Overlap::Partial);
+    assert!(organizer
+        .get_all_related_media(50, PassageSegment::chapter_verse(2, 1))
+        .is_none());
+}
@@ -0,0 +1,249 @@
+use std::{
+    io::{BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use lsp_server::{Message, Notification, Request, RequestId, Response};
+use serde_json::{json, Value};
+
+const DOCUMENT_URI: &str = "file:///notes.md";
+
+const DOCUMENT: &str = "# Sermon notes
+
+Key verse: John 3:16-17 (see also 1 John 4:8).
+“Naïve” 📖 readers skip Ephesians 9:99 — it doesn't exist,
+but Eph 2:8-9 does.
+";
+
+/// Drives `bible-lsp` with JSON-RPC messages over stdio
+struct Client {
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i32,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_bible-lsp"))
+            .arg(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/kjv.json"
+            ))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = process.stdin.take().unwrap();
+        let stdout = BufReader::new(process.stdout.take().unwrap());
+        let mut client = Self {
+            process,
+            stdin,
+            stdout,
+            next_id: 0,
+        };
+
+        let result = client.request("initialize", json!({ "capabilities": {} }));
+        assert_eq!(result["capabilities"]["hoverProvider"], json!(true));
+        client.notify("initialized", json!({}));
+        client.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": DOCUMENT_URI,
+                    "languageId": "markdown",
+                    "version": 1,
+                    "text": DOCUMENT,
+                }
+            }),
+        );
+        client
+    }
+
+    fn send(&mut self, message: Message) {
+        message.write(&mut self.stdin).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn read(&mut self) -> Message {
+        Message::read(&mut self.stdout).unwrap().unwrap()
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(Notification::new(method.to_string(), params).into());
+    }
+
+    /// Skips any notifications until the response to this request arrives
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        self.send(Request::new(id.clone(), method.to_string(), params).into());
+        loop {
+            if let Message::Response(Response {
+                id: response_id,
+                result,
+                error,
+            }) = self.read()
+            {
+                assert_eq!(response_id, id);
+                assert!(error.is_none(), "{:?}", error);
+                return result.unwrap_or(Value::Null);
+            }
+        }
+    }
+
+    fn read_notification(&mut self, method: &str) -> Value {
+        loop {
+            if let Message::Notification(notification) = self.read() {
+                if notification.method == method {
+                    return notification.params;
+                }
+            }
+        }
+    }
+
+    fn shutdown(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        assert!(self.process.wait().unwrap().success());
+    }
+}
+
+fn position(line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": DOCUMENT_URI },
+        "position": { "line": line, "character": character },
+    })
+}
+
+#[test]
+fn diagnostics_for_references_that_do_not_exist() {
+    let mut client = Client::start();
+    let params = client.read_notification("textDocument/publishDiagnostics");
+    assert_eq!(params["uri"], DOCUMENT_URI);
+
+    let diagnostics = params["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0]["message"],
+        "Ephesians 9 does not exist (Ephesians has 6 chapters)"
+    );
+    // `“Naïve” 📖 readers skip Ephesians ` is 33 characters, but 34 UTF-16 code units
+    assert_eq!(
+        diagnostics[0]["range"],
+        json!({
+            "start": { "line": 3, "character": 34 },
+            "end": { "line": 3, "character": 38 },
+        })
+    );
+
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": DOCUMENT_URI, "version": 2 },
+            "contentChanges": [{ "text": "Jude 2:1 and John 3:99" }],
+        }),
+    );
+    let params = client.read_notification("textDocument/publishDiagnostics");
+    assert_eq!(
+        params["diagnostics"][0]["message"],
+        "Jude 2 does not exist (Jude has 1 chapter)"
+    );
+    assert_eq!(
+        params["diagnostics"][1]["message"],
+        "John 3:99 does not exist (John 3 has 36 verses)"
+    );
+    client.shutdown();
+}
+
+#[test]
+fn hover_shows_the_passage() {
+    let mut client = Client::start();
+    let hover = client.request("textDocument/hover", position(2, 16));
+    let text = hover["contents"]["value"].as_str().unwrap();
+    assert!(text.starts_with("### John 3:16-17\n\n[3:16] For God so loved the world"));
+    assert!(text.contains("\n\n[3:17] For God sent not his Son"));
+    assert_eq!(
+        hover["range"],
+        json!({
+            "start": { "line": 2, "character": 11 },
+            "end": { "line": 2, "character": 23 },
+        })
+    );
+
+    // nothing to show for a reference that doesn't exist, or for plain text
+    assert_eq!(
+        client.request("textDocument/hover", position(3, 30)),
+        Value::Null
+    );
+    assert_eq!(
+        client.request("textDocument/hover", position(0, 3)),
+        Value::Null
+    );
+    client.shutdown();
+}
+
+#[test]
+fn links_and_symbols_for_every_reference() {
+    let mut client = Client::start();
+    let symbols = client.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": DOCUMENT_URI } }),
+    );
+    let names = symbols
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| symbol["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "John 3:16-17",
+            "1 John 4:8",
+            "Ephesians 9:99",
+            "Ephesians 2:8-9"
+        ]
+    );
+
+    let links = client.request(
+        "textDocument/documentLink",
+        json!({ "textDocument": { "uri": DOCUMENT_URI } }),
+    );
+    let targets = links
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|link| link["target"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        targets,
+        vec![
+            "bible:///John/3.md",
+            "bible:///1%20John/4.md",
+            "bible:///Ephesians/2.md"
+        ]
+    );
+    client.shutdown();
+}
+
+#[test]
+fn definition_opens_the_chapter() {
+    let mut client = Client::start();
+    let location = client.request("textDocument/definition", position(4, 6));
+    assert_eq!(location["uri"], "bible:///Ephesians/2.md");
+    let line = location["range"]["start"]["line"].as_u64().unwrap() as usize;
+
+    let text = client.request(
+        "bible/virtualTextDocument",
+        json!({ "textDocument": { "uri": "bible:///Ephesians/2.md" } }),
+    );
+    let text = text.as_str().unwrap();
+    assert!(text.starts_with("# Ephesians 2\n\n[2:1] "));
+    assert_eq!(
+        text.lines().nth(line).unwrap(),
+        "[2:8] For by grace are ye saved through faith; and that not of yourselves: it is the gift of God:"
+    );
+    assert_eq!(text.lines().count(), 2 + 22);
+    client.shutdown();
+}